@@ -0,0 +1,6 @@
+-- Rôle de chaque membre au sein de sa tontine
+ALTER TABLE tontine_members
+    ADD COLUMN role VARCHAR(50) NOT NULL DEFAULT 'member'; -- 'owner', 'treasurer', 'member', 'auditor'
+
+ALTER TABLE tontine_members
+    ADD CONSTRAINT tontine_members_role_check CHECK (role IN ('owner', 'treasurer', 'member', 'auditor'));
@@ -0,0 +1,14 @@
+-- Codes à usage unique envoyés par SMS (seul le hash est stocké)
+CREATE TABLE otp_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    purpose VARCHAR(50) NOT NULL, -- 'login'
+    code_hash VARCHAR(128) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    consumed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT otp_codes_purpose_check CHECK (purpose IN ('login'))
+);
+
+CREATE INDEX idx_otp_codes_user_purpose ON otp_codes(user_id, purpose);
@@ -0,0 +1,24 @@
+-- Verrouillage progressif après des échecs de connexion répétés, par compte et par adresse IP :
+-- les échecs venus d'une adresse ne bloquent pas le titulaire qui se connecte depuis une autre
+CREATE TABLE login_lockouts (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    ip_address VARCHAR(64) NOT NULL,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (user_id, ip_address)
+);
+
+-- Journal des tentatives de connexion échouées
+CREATE TABLE failed_login_attempts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    identifier VARCHAR(255) NOT NULL, -- email ou téléphone saisi
+    method VARCHAR(50) NOT NULL, -- 'password', 'otp'
+    reason VARCHAR(100) NOT NULL,
+    ip_address VARCHAR(64),
+    user_agent TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_failed_login_attempts_user_id ON failed_login_attempts(user_id);
+CREATE INDEX idx_failed_login_attempts_identifier ON failed_login_attempts(identifier);
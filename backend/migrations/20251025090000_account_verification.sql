@@ -0,0 +1,12 @@
+-- Vérification de l'email et du téléphone à l'inscription
+ALTER TABLE users
+    ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN phone_verified_at TIMESTAMP WITH TIME ZONE;
+
+-- Les comptes existants sont considérés comme vérifiés
+UPDATE users SET email_verified_at = created_at, phone_verified_at = created_at;
+
+-- Nouveaux usages des codes à usage unique
+ALTER TABLE otp_codes DROP CONSTRAINT otp_codes_purpose_check;
+ALTER TABLE otp_codes
+    ADD CONSTRAINT otp_codes_purpose_check CHECK (purpose IN ('login', 'verify_email', 'verify_phone'));
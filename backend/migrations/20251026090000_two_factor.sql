@@ -0,0 +1,23 @@
+-- Double authentification TOTP
+ALTER TABLE users
+    ADD COLUMN totp_secret VARCHAR(64),
+    ADD COLUMN totp_enabled_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN totp_last_used_step BIGINT, -- empêche la réutilisation d'un même code
+    ADD COLUMN totp_backup_codes TEXT[] NOT NULL DEFAULT '{}'; -- hashs des codes de secours restants
+
+-- Dernière vérification du second facteur pour la session (actions sensibles)
+ALTER TABLE sessions
+    ADD COLUMN two_factor_verified_at TIMESTAMP WITH TIME ZONE;
+
+-- Connexions en attente du second facteur
+CREATE TABLE two_factor_challenges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    challenge_hash VARCHAR(128) UNIQUE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    consumed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_two_factor_challenges_user_id ON two_factor_challenges(user_id);
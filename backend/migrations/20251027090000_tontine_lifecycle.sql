@@ -0,0 +1,26 @@
+-- Cycle de vie des tontines : draft -> recruiting -> running -> completed / cancelled
+UPDATE tontines SET status = 'running'
+WHERE status = 'active' AND EXISTS (SELECT 1 FROM tontine_rounds tr WHERE tr.tontine_id = tontines.id);
+
+UPDATE tontines SET status = 'recruiting' WHERE status = 'active';
+
+UPDATE tontines SET status = 'draft'
+WHERE status NOT IN ('draft', 'recruiting', 'running', 'completed', 'cancelled');
+
+ALTER TABLE tontines ALTER COLUMN status SET DEFAULT 'draft';
+ALTER TABLE tontines
+    ADD CONSTRAINT tontines_status_check
+    CHECK (status IN ('draft', 'recruiting', 'running', 'completed', 'cancelled'));
+
+-- Historique des changements de statut (audit)
+CREATE TABLE tontine_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_tontine_status_history_tontine_id ON tontine_status_history(tontine_id);
@@ -0,0 +1,24 @@
+-- Tâches planifiées exécutées par le planificateur en arrière-plan (persistées pour survivre aux redémarrages)
+CREATE TABLE scheduled_jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    job_type VARCHAR(50) NOT NULL CHECK (job_type IN ('open_round', 'mark_late_contributions')),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    tontine_round_id UUID REFERENCES tontine_rounds(id) ON DELETE CASCADE,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'done', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    locked_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (job_type, tontine_round_id)
+);
+
+CREATE INDEX idx_scheduled_jobs_due ON scheduled_jobs(status, run_at);
+
+-- Ouverture planifiée des tours déjà générés pour les tontines en cours
+INSERT INTO scheduled_jobs (job_type, tontine_id, tontine_round_id, run_at)
+SELECT 'open_round', tr.tontine_id, tr.id, tr.round_date
+FROM tontine_rounds tr
+JOIN tontines t ON tr.tontine_id = t.id
+WHERE t.status = 'running' AND tr.status = 'pending' AND tr.round_date IS NOT NULL;
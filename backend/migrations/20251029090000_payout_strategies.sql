@@ -0,0 +1,19 @@
+-- Règle de désignation des bénéficiaires : 'fixed', 'random_draw', 'per_round_draw', 'auction'
+ALTER TABLE tontines ADD COLUMN payout_strategy VARCHAR(50) NOT NULL DEFAULT 'fixed'
+    CHECK (payout_strategy IN ('fixed', 'random_draw', 'per_round_draw', 'auction'));
+
+-- Tirages au sort enregistrés pour vérification par les membres :
+-- le résultat se recalcule à partir de la graine et de la liste ordonnée des candidats
+CREATE TABLE beneficiary_draws (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    tontine_round_id UUID REFERENCES tontine_rounds(id) ON DELETE CASCADE, -- NULL pour le tirage complet au démarrage
+    strategy VARCHAR(50) NOT NULL,
+    seed VARCHAR(64) NOT NULL,
+    candidates UUID[] NOT NULL,
+    result UUID[] NOT NULL,
+    drawn_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_beneficiary_draws_tontine_id ON beneficiary_draws(tontine_id);
@@ -0,0 +1,30 @@
+-- Utilisation de la remise des enchères : 'interest' (conservée par la tontine) ou 'redistribute' (partagée entre les autres membres)
+ALTER TABLE tontines ADD COLUMN bid_discount_policy VARCHAR(50) NOT NULL DEFAULT 'interest'
+    CHECK (bid_discount_policy IN ('interest', 'redistribute'));
+
+-- Fenêtre d'enchères d'un tour
+CREATE TABLE bid_windows (
+    tontine_round_id UUID PRIMARY KEY REFERENCES tontine_rounds(id) ON DELETE CASCADE,
+    opened_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    min_bid DECIMAL(15,2) NOT NULL DEFAULT 0,
+    closes_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved')),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Offres des membres : la remise proposée sur le pot pour le recevoir à ce tour
+CREATE TABLE bids (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_round_id UUID NOT NULL REFERENCES tontine_rounds(id) ON DELETE CASCADE,
+    member_id UUID NOT NULL REFERENCES tontine_members(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    amount DECIMAL(15,2) NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'withdrawn', 'won', 'lost')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Une seule offre active par membre et par tour
+CREATE UNIQUE INDEX idx_bids_active_member ON bids(tontine_round_id, member_id) WHERE status = 'active';
+CREATE INDEX idx_bids_round_id ON bids(tontine_round_id);
@@ -0,0 +1,27 @@
+-- Règle d'amende de retard, une par tontine
+CREATE TABLE penalty_rules (
+    tontine_id UUID PRIMARY KEY REFERENCES tontines(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('flat', 'percentage')), -- montant fixe ou pourcentage de la cotisation
+    amount DECIMAL(15,2) NOT NULL CHECK (amount > 0),
+    per_day BOOLEAN NOT NULL DEFAULT false, -- appliquée par jour de retard ou une seule fois
+    grace_days INTEGER NOT NULL DEFAULT 0 CHECK (grace_days >= 0),
+    cap DECIMAL(15,2) CHECK (cap > 0), -- plafond de l'amende par cotisation
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Amendes calculées lors du règlement d'une cotisation après l'échéance du tour
+CREATE TABLE penalties (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    contribution_id UUID NOT NULL UNIQUE REFERENCES contributions(id) ON DELETE CASCADE,
+    member_id UUID NOT NULL REFERENCES tontine_members(id) ON DELETE CASCADE,
+    amount DECIMAL(15,2) NOT NULL,
+    days_late INTEGER NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'unpaid' CHECK (status IN ('unpaid', 'paid', 'waived')),
+    assessed_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    settled_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_penalties_tontine_id ON penalties(tontine_id);
+CREATE INDEX idx_penalties_member_id ON penalties(member_id);
@@ -0,0 +1,9 @@
+-- Paiement des cotisations en plusieurs versements, à activer par tontine.
+-- Les tontines existantes le conservent puisqu'elles ont pu déjà en recevoir.
+ALTER TABLE tontines ADD COLUMN allow_partial_payments BOOLEAN NOT NULL DEFAULT false;
+UPDATE tontines SET allow_partial_payments = true;
+
+-- Montants strictement positifs
+ALTER TABLE tontines ADD CONSTRAINT tontines_amount_per_member_positive CHECK (amount_per_member > 0) NOT VALID;
+ALTER TABLE contributions ADD CONSTRAINT contributions_amount_positive CHECK (amount > 0) NOT VALID;
+ALTER TABLE transactions ADD CONSTRAINT transactions_amount_positive CHECK (amount > 0) NOT VALID;
@@ -0,0 +1,20 @@
+-- Versement d'un tour dont toutes les cotisations ne sont pas réglées :
+-- refusé ('reject') ou limité aux cotisations payées ('pay_collected')
+ALTER TABLE tontines ADD COLUMN payout_shortfall_policy VARCHAR(50) NOT NULL DEFAULT 'reject'
+    CHECK (payout_shortfall_policy IN ('reject', 'pay_collected'));
+
+-- Versement du pot d'un tour à son bénéficiaire, un seul par tour
+CREATE TABLE round_payouts (
+    tontine_round_id UUID PRIMARY KEY REFERENCES tontine_rounds(id) ON DELETE CASCADE,
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    beneficiary_user_id UUID NOT NULL REFERENCES users(id),
+    collected_amount DECIMAL(15,2) NOT NULL, -- cotisations payées au moment du versement
+    discount DECIMAL(15,2) NOT NULL DEFAULT 0, -- remise d'enchère retenue
+    amount DECIMAL(15,2) NOT NULL CHECK (amount > 0), -- montant versé
+    shortfall DECIMAL(15,2) NOT NULL DEFAULT 0, -- cotisations manquantes
+    paid_by UUID REFERENCES users(id),
+    paid_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_round_payouts_tontine_id ON round_payouts(tontine_id);
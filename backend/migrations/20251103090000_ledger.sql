@@ -0,0 +1,111 @@
+-- Grand livre en partie double : les transactions et amendes réglées y sont journalisées,
+-- les soldes sont dérivés des écritures
+
+-- Comptes : portefeuille de chaque membre, pot et réserve (remises d'enchères) de chaque tontine,
+-- amendes et frais
+CREATE TABLE ledger_accounts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('member_wallet', 'tontine_pot', 'tontine_reserve', 'penalties', 'fees')),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id), -- renseigné uniquement pour les portefeuilles
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((kind = 'member_wallet') = (user_id IS NOT NULL))
+);
+
+CREATE UNIQUE INDEX idx_ledger_accounts_unique
+    ON ledger_accounts(kind, tontine_id, COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid));
+
+-- Écritures : une par transaction réglée ou amende payée, contre-passée si elle est annulée
+CREATE TABLE journal_entries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    transaction_id UUID REFERENCES transactions(id),
+    penalty_id UUID REFERENCES penalties(id),
+    reversal_of UUID UNIQUE REFERENCES journal_entries(id),
+    description TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_journal_entries_tontine_id ON journal_entries(tontine_id);
+CREATE INDEX idx_journal_entries_transaction_id ON journal_entries(transaction_id);
+
+-- Lignes d'écriture : montant positif au crédit du compte, négatif au débit
+CREATE TABLE ledger_postings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    entry_id UUID NOT NULL REFERENCES journal_entries(id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES ledger_accounts(id) ON DELETE CASCADE,
+    amount DECIMAL(15,2) NOT NULL CHECK (amount <> 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_ledger_postings_entry_id ON ledger_postings(entry_id);
+CREATE INDEX idx_ledger_postings_account_id ON ledger_postings(account_id);
+
+-- Invariant : les lignes de chaque écriture s'équilibrent, vérifié en fin de transaction
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced()
+RETURNS TRIGGER AS $$
+DECLARE
+    total DECIMAL(15,2);
+BEGIN
+    SELECT COALESCE(SUM(amount), 0) INTO total FROM ledger_postings WHERE entry_id = NEW.entry_id;
+    IF total <> 0 THEN
+        RAISE EXCEPTION 'Écriture % déséquilibrée (solde %)', NEW.entry_id, total;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE CONSTRAINT TRIGGER ledger_postings_balanced
+    AFTER INSERT OR UPDATE ON ledger_postings
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+-- Reprise de l'historique : transactions réglées et amendes payées
+CREATE TEMPORARY TABLE ledger_backfill AS
+SELECT uuid_generate_v4() as entry_id, t.id as transaction_id, NULL::uuid as penalty_id, t.tontine_id, t.amount,
+       COALESCE(t.description, t.transaction_type) as description, t.created_at,
+       CASE t.transaction_type
+           WHEN 'contribution' THEN 'member_wallet'
+           WHEN 'bid_dividend' THEN 'tontine_reserve'
+           ELSE 'tontine_pot'
+       END as debit_kind,
+       CASE WHEN t.transaction_type = 'contribution' THEN t.from_user_id END as debit_user_id,
+       CASE t.transaction_type
+           WHEN 'contribution' THEN 'tontine_pot'
+           WHEN 'bid_discount' THEN 'tontine_reserve'
+           ELSE 'member_wallet'
+       END as credit_kind,
+       CASE WHEN t.transaction_type IN ('payout', 'refund', 'bid_dividend') THEN t.to_user_id END as credit_user_id
+FROM transactions t
+WHERE t.status = 'completed'
+  AND t.amount > 0
+  AND (t.transaction_type <> 'contribution' OR t.from_user_id IS NOT NULL)
+  AND (t.transaction_type NOT IN ('payout', 'refund', 'bid_dividend') OR t.to_user_id IS NOT NULL)
+UNION ALL
+SELECT uuid_generate_v4(), NULL, p.id, p.tontine_id, p.amount, 'Amende de retard', COALESCE(p.settled_at, p.assessed_at),
+       'member_wallet', tm.user_id, 'penalties', NULL
+FROM penalties p
+JOIN tontine_members tm ON p.member_id = tm.id
+WHERE p.status = 'paid';
+
+INSERT INTO ledger_accounts (kind, tontine_id, user_id)
+SELECT DISTINCT debit_kind, tontine_id, debit_user_id FROM ledger_backfill
+UNION
+SELECT DISTINCT credit_kind, tontine_id, credit_user_id FROM ledger_backfill
+ON CONFLICT DO NOTHING;
+
+INSERT INTO journal_entries (id, tontine_id, transaction_id, penalty_id, description, created_at)
+SELECT entry_id, tontine_id, transaction_id, penalty_id, description, created_at FROM ledger_backfill;
+
+INSERT INTO ledger_postings (entry_id, account_id, amount, created_at)
+SELECT b.entry_id, a.id, -b.amount, b.created_at
+FROM ledger_backfill b
+JOIN ledger_accounts a ON a.kind = b.debit_kind AND a.tontine_id = b.tontine_id
+    AND a.user_id IS NOT DISTINCT FROM b.debit_user_id
+UNION ALL
+SELECT b.entry_id, a.id, b.amount, b.created_at
+FROM ledger_backfill b
+JOIN ledger_accounts a ON a.kind = b.credit_kind AND a.tontine_id = b.tontine_id
+    AND a.user_id IS NOT DISTINCT FROM b.credit_user_id;
+
+DROP TABLE ledger_backfill;
@@ -0,0 +1,16 @@
+-- Paiements mobile money : une cotisation MobileMoney reste en attente jusqu'à la confirmation de l'opérateur
+
+UPDATE payment_transactions SET status = 'pending' WHERE status IS NULL;
+
+ALTER TABLE payment_transactions
+    ALTER COLUMN status SET NOT NULL,
+    ADD CONSTRAINT payment_transactions_status_check CHECK (status IN ('pending', 'successful', 'failed')),
+    ADD CONSTRAINT payment_transactions_amount_positive CHECK (amount > 0) NOT VALID;
+
+-- Une référence opérateur ne peut correspondre qu'à un seul paiement
+CREATE UNIQUE INDEX idx_payment_transactions_provider_reference
+    ON payment_transactions(provider, provider_reference)
+    WHERE provider_reference IS NOT NULL;
+
+CREATE TRIGGER update_payment_transactions_updated_at BEFORE UPDATE ON payment_transactions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
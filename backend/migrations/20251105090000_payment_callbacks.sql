@@ -0,0 +1,3 @@
+-- Transaction `contribution` liée au paiement mobile money : en attente à la demande,
+-- réglée ou échouée avec le paiement à la notification de l'opérateur
+ALTER TABLE payment_transactions ADD COLUMN transaction_id UUID REFERENCES transactions(id);
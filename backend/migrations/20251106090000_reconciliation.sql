@@ -0,0 +1,38 @@
+-- Rapprochement des relevés des opérateurs avec les cotisations enregistrées
+
+-- Import d'un relevé pour une tontine
+CREATE TABLE reconciliation_runs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tontine_id UUID NOT NULL REFERENCES tontines(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL, -- 'MTNMobileMoney', 'OrangeMoney', 'Mock'
+    source_format VARCHAR(10) NOT NULL CHECK (source_format IN ('csv', 'json')),
+    period_start TIMESTAMP WITH TIME ZONE, -- bornes des cotisations comparées, NULL si le relevé n'est pas daté
+    period_end TIMESTAMP WITH TIME ZONE,
+    lines_count INTEGER NOT NULL,
+    imported_by UUID REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_reconciliation_runs_tontine_id ON reconciliation_runs(tontine_id);
+
+-- Résultat du rapprochement, ligne de relevé et/ou cotisation
+CREATE TABLE reconciliation_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    run_id UUID NOT NULL REFERENCES reconciliation_runs(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('matched', 'missing_in_statement', 'missing_in_records', 'duplicate', 'amount_mismatch')),
+    statement_reference VARCHAR(255),
+    statement_amount DECIMAL(15,2),
+    statement_phone VARCHAR(50),
+    statement_date TIMESTAMP WITH TIME ZONE,
+    payment_transaction_id UUID REFERENCES payment_transactions(id) ON DELETE SET NULL,
+    contribution_id UUID REFERENCES contributions(id) ON DELETE SET NULL,
+    recorded_amount DECIMAL(15,2),
+    resolution VARCHAR(50) CHECK (resolution IN ('accepted', 'contribution_failed')),
+    resolution_note TEXT,
+    resolved_by UUID REFERENCES users(id),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK (kind <> 'matched' OR resolution IS NULL)
+);
+
+CREATE INDEX idx_reconciliation_items_run_id ON reconciliation_items(run_id);
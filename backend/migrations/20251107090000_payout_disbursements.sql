@@ -0,0 +1,18 @@
+-- Versement des tours par mobile money : le paiement sortant est suivi dans payment_transactions
+-- et le tour reste 'disbursing' jusqu'à la confirmation de l'opérateur
+
+ALTER TABLE payment_transactions
+    ADD COLUMN direction VARCHAR(20) NOT NULL DEFAULT 'collection' CHECK (direction IN ('collection', 'disbursement')),
+    ADD COLUMN tontine_round_id UUID REFERENCES tontine_rounds(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_payment_transactions_round ON payment_transactions(tontine_round_id);
+
+-- Versement en attente de confirmation ('pending') ou effectué ('completed')
+ALTER TABLE round_payouts
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'completed' CHECK (status IN ('pending', 'completed')),
+    ADD COLUMN payment_method VARCHAR(50) NOT NULL DEFAULT 'manual' CHECK (payment_method IN ('manual', 'mobile_money'));
+
+-- Envoi du versement à l'opérateur, rejoué avec un délai croissant en cas d'échec temporaire
+ALTER TABLE scheduled_jobs DROP CONSTRAINT scheduled_jobs_job_type_check;
+ALTER TABLE scheduled_jobs ADD CONSTRAINT scheduled_jobs_job_type_check
+    CHECK (job_type IN ('open_round', 'mark_late_contributions', 'disburse_payout'));
@@ -0,0 +1,14 @@
+-- Chaque cotisation payée est journalisée par sa transaction 'contribution' (portefeuille du membre → pot),
+-- contre-passée si la cotisation échoue ou est supprimée
+ALTER TABLE contributions
+    ADD COLUMN transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_contributions_transaction ON contributions(transaction_id);
+
+-- Cotisations mobile money : la transaction est celle créée avec le paiement
+UPDATE contributions c
+SET transaction_id = pt.transaction_id
+FROM payment_transactions pt
+WHERE pt.contribution_id = c.id
+  AND pt.direction = 'collection'
+  AND pt.transaction_id IS NOT NULL;
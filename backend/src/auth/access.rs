@@ -0,0 +1,204 @@
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::model::tontine_members::TontineRole;
+use crate::errors::AppError;
+
+/// Actions soumises à autorisation au sein d'une tontine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Consulter la tontine, ses membres et son calendrier de tours
+    ViewTontine,
+    /// Consulter les résumés (tours, membres, finances)
+    ViewSummaries,
+    /// Consulter les cotisations et transactions de tous les membres
+    ViewAllRecords,
+    /// Modifier ou supprimer la tontine
+    ManageTontine,
+    /// Ajouter, modifier ou retirer des membres
+    ManageMembers,
+    /// Créer, modifier ou supprimer des tours
+    ManageRounds,
+    /// Enregistrer et valider les cotisations de n'importe quel membre
+    RecordContributions,
+    /// Clôturer ou annuler un tour
+    CompleteRounds,
+    /// Émettre des paiements et transactions pour la tontine
+    IssuePayouts,
+    /// Cotiser pour son propre compte
+    Contribute,
+}
+
+impl Permission {
+    pub fn is_granted_to(&self, role: TontineRole) -> bool {
+        match role {
+            TontineRole::Owner => true,
+            TontineRole::Treasurer => !matches!(
+                self,
+                Permission::ManageTontine | Permission::ManageMembers | Permission::ManageRounds
+            ),
+            TontineRole::Member => matches!(self, Permission::ViewTontine | Permission::Contribute),
+            TontineRole::Auditor => matches!(self, Permission::ViewTontine | Permission::ViewSummaries),
+        }
+    }
+}
+
+pub struct AccessControl;
+
+impl AccessControl {
+    /// Rôle de l'utilisateur dans la tontine : le créateur est toujours propriétaire,
+    /// les autres utilisateurs doivent être membres actifs
+    pub async fn role_in_tontine(pool: &PgPool, tontine_id: Uuid, user_id: Uuid) -> Result<TontineRole, AppError> {
+        let row = sqlx::query(
+            "SELECT t.created_by, tm.role
+             FROM tontines t
+             LEFT JOIN tontine_members tm ON tm.tontine_id = t.id AND tm.user_id = $2 AND tm.is_active = true
+             WHERE t.id = $1"
+        )
+        .bind(tontine_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Err(AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id))),
+        };
+
+        let created_by: Uuid = row.get("created_by");
+        if created_by == user_id {
+            return Ok(TontineRole::Owner);
+        }
+
+        match row.get::<Option<String>, _>("role") {
+            Some(role) => TontineRole::try_from(role).map_err(AppError::InternalServerError),
+            None => Err(AppError::Forbidden("Accès refusé à cette tontine".to_string())),
+        }
+    }
+
+    /// Vérifie que le rôle de l'utilisateur dans la tontine lui accorde la permission demandée
+    pub async fn require(pool: &PgPool, tontine_id: Uuid, user_id: Uuid, permission: Permission) -> Result<TontineRole, AppError> {
+        let role = Self::role_in_tontine(pool, tontine_id, user_id).await?;
+
+        if !permission.is_granted_to(role) {
+            return Err(AppError::Forbidden("Votre rôle dans cette tontine ne permet pas cette action".to_string()));
+        }
+
+        Ok(role)
+    }
+
+    /// Comme `require`, mais autorise aussi l'utilisateur à accéder à ses propres données
+    pub async fn require_own_or(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        user_id: Uuid,
+        data_owners: &[Uuid],
+        permission: Permission,
+    ) -> Result<TontineRole, AppError> {
+        let role = Self::role_in_tontine(pool, tontine_id, user_id).await?;
+
+        if !permission.is_granted_to(role) && !data_owners.contains(&user_id) {
+            return Err(AppError::Forbidden("Accès refusé aux données d'un autre membre".to_string()));
+        }
+
+        Ok(role)
+    }
+
+    /// Vérifie que l'utilisateur agit sur son propre compte
+    pub fn ensure_same_user(current_user_id: Uuid, target_user_id: Uuid) -> Result<(), AppError> {
+        if current_user_id != target_user_id {
+            return Err(AppError::Forbidden("Accès refusé aux données d'un autre utilisateur".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Un utilisateur ne peut consulter que son propre compte ou celui d'un membre
+    /// d'une tontine dont il fait partie (membre actif ou créateur)
+    pub async fn ensure_can_view_user(pool: &PgPool, current_user_id: Uuid, target_user_id: Uuid) -> Result<(), AppError> {
+        if current_user_id == target_user_id {
+            return Ok(());
+        }
+
+        let shares_tontine: bool = sqlx::query_scalar(
+            "SELECT EXISTS (
+                 SELECT 1
+                 FROM tontines t
+                 WHERE (t.created_by = $1 OR EXISTS (
+                         SELECT 1 FROM tontine_members tm
+                         WHERE tm.tontine_id = t.id AND tm.user_id = $1 AND tm.is_active = true))
+                   AND (t.created_by = $2 OR EXISTS (
+                         SELECT 1 FROM tontine_members tm
+                         WHERE tm.tontine_id = t.id AND tm.user_id = $2 AND tm.is_active = true))
+             )"
+        )
+        .bind(current_user_id)
+        .bind(target_user_id)
+        .fetch_one(pool)
+        .await?;
+
+        if !shares_tontine {
+            return Err(AppError::Forbidden("Accès refusé aux données d'un autre utilisateur".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Tontine et utilisateur associés à une adhésion
+    pub async fn member_context(pool: &PgPool, member_id: Uuid) -> Result<(Uuid, Uuid), AppError> {
+        let row = sqlx::query("SELECT tontine_id, user_id FROM tontine_members WHERE id = $1")
+            .bind(member_id)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok((row.get("tontine_id"), row.get("user_id"))),
+            None => Err(AppError::NotFound(format!("Membre avec l'ID {} non trouvé", member_id))),
+        }
+    }
+
+    pub async fn tontine_id_of_round(pool: &PgPool, round_id: Uuid) -> Result<Uuid, AppError> {
+        let tontine_id: Option<Uuid> = sqlx::query_scalar("SELECT tontine_id FROM tontine_rounds WHERE id = $1")
+            .bind(round_id)
+            .fetch_optional(pool)
+            .await?;
+
+        tontine_id.ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))
+    }
+
+    /// Tontine et utilisateur cotisant associés à une contribution
+    pub async fn contribution_context(pool: &PgPool, contribution_id: Uuid) -> Result<(Uuid, Uuid), AppError> {
+        let row = sqlx::query(
+            "SELECT tr.tontine_id, tm.user_id
+             FROM contributions c
+             JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
+             JOIN tontine_members tm ON c.member_id = tm.id
+             WHERE c.id = $1"
+        )
+        .bind(contribution_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok((row.get("tontine_id"), row.get("user_id"))),
+            None => Err(AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id))),
+        }
+    }
+
+    /// Tontine et utilisateurs (émetteur, destinataire) associés à une transaction
+    pub async fn transaction_context(pool: &PgPool, transaction_id: Uuid) -> Result<(Uuid, Vec<Uuid>), AppError> {
+        let row = sqlx::query("SELECT tontine_id, from_user_id, to_user_id FROM transactions WHERE id = $1")
+            .bind(transaction_id)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let parties = [row.get::<Option<Uuid>, _>("from_user_id"), row.get::<Option<Uuid>, _>("to_user_id")]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                Ok((row.get("tontine_id"), parties))
+            },
+            None => Err(AppError::NotFound(format!("Transaction avec l'ID {} non trouvée", transaction_id))),
+        }
+    }
+}
@@ -1,26 +1,34 @@
-use std::env;
 use jsonwebtoken::{DecodingKey, EncodingKey};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::settings::JwtSettings;
 
 pub struct JwtConfig {
     pub secret: String,
     pub expiration: i64,
+    pub refresh_expiration: i64,
     pub encoding_key: EncodingKey,
     pub decoding_key: DecodingKey,
 }
 
+static JWT_SETTINGS: OnceCell<JwtSettings> = OnceCell::new();
+
 pub static JWT_CONFIG: Lazy<JwtConfig> = Lazy::new(|| {
-    let secret = env::var("JWT_SECRET")
-        .expect("JWT_SECRET must be set");
-    let expiration = env::var("JWT_EXPIRATION")
-        .unwrap_or_else(|_| "86400".to_string()) // 24 heures par défaut
-        .parse()
-        .expect("JWT_EXPIRATION must be a number");
+    let settings = JWT_SETTINGS
+        .get()
+        .expect("auth::config::init doit être appelé au démarrage");
 
     JwtConfig {
-        secret: secret.clone(),
-        expiration,
-        encoding_key: EncodingKey::from_secret(secret.as_ref()),
-        decoding_key: DecodingKey::from_secret(secret.as_ref()),
+        secret: settings.secret.clone(),
+        expiration: settings.expiration,
+        refresh_expiration: settings.refresh_expiration,
+        encoding_key: EncodingKey::from_secret(settings.secret.as_ref()),
+        decoding_key: DecodingKey::from_secret(settings.secret.as_ref()),
     }
-});
\ No newline at end of file
+});
+
+/// Installe la configuration JWT validée par `Settings::load`
+pub fn init(settings: &JwtSettings) {
+    JWT_SETTINGS.get_or_init(|| settings.clone());
+    Lazy::force(&JWT_CONFIG);
+}
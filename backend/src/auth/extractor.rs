@@ -0,0 +1,32 @@
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use futures::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::auth::models::Claims;
+use crate::errors::AppError;
+
+/// Utilisateur connecté, construit à partir des claims placés par `auth::middleware::validator`
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: Uuid,
+    pub email: String,
+    pub session_id: Uuid,
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let user = req.extensions()
+            .get::<Claims>()
+            .map(|claims| AuthenticatedUser {
+                id: claims.sub,
+                email: claims.email.clone(),
+                session_id: claims.sid,
+            })
+            .ok_or_else(|| AppError::AuthenticationError("Token invalide".to_string()));
+
+        ready(user)
+    }
+}
@@ -1,25 +1,42 @@
-use actix_web::{dev::ServiceRequest, Error, HttpMessage};
+use actix_web::{dev::ServiceRequest, web, Error, HttpMessage};
 use actix_web_httpauth::extractors::bearer::{BearerAuth, Config};
 use actix_web_httpauth::extractors::AuthenticationError;
+use sqlx::PgPool;
 
 use crate::auth::service::AuthService;
+use crate::errors::AppError;
 
 pub async fn validator(
     req: ServiceRequest,
     credentials: BearerAuth,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
     let token = credentials.token();
-    
-    match AuthService::verify_token(token) {
-        Ok(claims) => {
-            req.extensions_mut().insert(claims);
-            Ok(req)
-        }
-        Err(_) => {
-            let config = req.app_data::<Config>()
-                .cloned()
-                .unwrap_or_default();
-            Err((AuthenticationError::from(config).into(), req))
+
+    let claims = match AuthService::verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err((unauthorized(&req), req)),
+    };
+
+    // Vérifier que la session du jeton est toujours ouverte (déconnexion, changement de mot de passe, etc.).
+    // Sans base, la révocation ne peut pas être vérifiée : la requête est refusée
+    let pool = match req.app_data::<web::Data<PgPool>>().cloned() {
+        Some(pool) => pool,
+        None => {
+            let error = AppError::InternalServerError("Base de données non configurée pour l'authentification".to_string());
+            return Err((error.into(), req));
         }
+    };
+    if AuthService::ensure_session_active(&pool, &claims).await.is_err() {
+        return Err((unauthorized(&req), req));
     }
-}
\ No newline at end of file
+
+    req.extensions_mut().insert(claims);
+    Ok(req)
+}
+
+fn unauthorized(req: &ServiceRequest) -> Error {
+    let config = req.app_data::<Config>()
+        .cloned()
+        .unwrap_or_default();
+    AuthenticationError::from(config).into()
+}
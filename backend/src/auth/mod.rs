@@ -1,4 +1,8 @@
 pub mod config;
 pub mod models;
 pub mod service;
-pub mod middleware;
\ No newline at end of file
+pub mod middleware;
+pub mod access;
+pub mod extractor;
+pub mod rate_limit;
+pub mod totp;
\ No newline at end of file
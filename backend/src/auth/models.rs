@@ -8,14 +8,76 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OtpRequest {
+    pub phone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OtpLoginRequest {
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub email: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyPhoneRequest {
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthResponse {
     pub access_token: String,
+    pub refresh_token: String,
     pub token_type: String,
     pub expires_in: i64,
     pub user: UserAuthResponse,
 }
 
+/// Réponse d'une connexion lorsque la double authentification est activée
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+/// Résultat du premier facteur : jetons de session ou défi TOTP à relever
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Authenticated(AuthResponse),
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserAuthResponse {
     pub id: Uuid,
@@ -31,6 +93,12 @@ pub struct Claims {
     pub email: String,    // User email
     pub exp: i64,         // Expiration timestamp
     pub iat: i64,         // Issued at timestamp
+    pub sid: Uuid,        // Session ID
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,120 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web, Error, HttpMessage, HttpRequest,
+};
+
+use crate::auth::models::Claims;
+use crate::errors::AppError;
+use crate::settings::RateLimitSettings;
+
+/// Au-delà de ce nombre de clés suivies, les fenêtres expirées sont purgées
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Limiteur à fenêtre glissante, partagé entre les workers
+pub struct RateLimiter {
+    per_ip: usize,
+    per_account: usize,
+    window: Duration,
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_ip: usize, per_account: usize, window: Duration) -> Self {
+        Self {
+            per_ip,
+            per_account,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_settings(settings: &RateLimitSettings) -> Self {
+        Self::new(settings.per_ip, settings.per_account, Duration::from_secs(settings.window_seconds))
+    }
+
+    /// Enregistre une requête pour la clé ; renvoie le délai d'attente en secondes si la limite est dépassée
+    fn hit(&self, key: String, limit: usize) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if hits.len() > PRUNE_THRESHOLD {
+            let window = self.window;
+            hits.retain(|_, entries| entries.back().is_some_and(|last| now.duration_since(*last) < window));
+        }
+
+        let entries = hits.entry(key).or_default();
+        while entries.front().is_some_and(|first| now.duration_since(*first) >= self.window) {
+            entries.pop_front();
+        }
+
+        if entries.len() >= limit {
+            let retry_after = entries.front()
+                .map(|first| self.window.saturating_sub(now.duration_since(*first)).as_secs().max(1))
+                .unwrap_or(1);
+            return Err(retry_after);
+        }
+
+        entries.push_back(now);
+        Ok(())
+    }
+}
+
+/// Adresse de la connexion, non falsifiable par un header `X-Forwarded-For` :
+/// clé de la limitation et du verrouillage des connexions
+pub fn client_ip(req: &HttpRequest) -> String {
+    req.connection_info()
+        .peer_addr()
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Middleware des routes publiques de `/api/auth` et des vérifications de code 2FA : limite le nombre
+/// de requêtes par adresse IP et par compte visé (utilisateur du jeton, ou champ `email` ou `phone` du corps JSON)
+pub async fn rate_limit(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let limiter = match req.app_data::<web::Data<RateLimiter>>().cloned() {
+        Some(limiter) => limiter,
+        None => return next.call(req).await,
+    };
+
+    let ip = client_ip(req.request());
+    limiter.hit(format!("ip:{}", ip), limiter.per_ip).map_err(too_many_requests)?;
+
+    // Derrière l'authentification, le compte est celui du jeton ; sinon il est lu dans le corps,
+    // remis ensuite en place pour le handler
+    let body = req.extract::<web::Bytes>().await?;
+    let account = match req.extensions().get::<Claims>() {
+        Some(claims) => Some(claims.sub.to_string()),
+        None => account_key(&body),
+    };
+    if let Some(account) = account {
+        limiter.hit(format!("account:{}", account), limiter.per_account).map_err(too_many_requests)?;
+    }
+    req.set_payload(body.into());
+
+    next.call(req).await
+}
+
+fn account_key(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+
+    ["email", "phone"].iter()
+        .find_map(|field| value.get(field).and_then(|v| v.as_str()))
+        .map(|account| account.trim().to_lowercase())
+}
+
+fn too_many_requests(retry_after: u64) -> Error {
+    AppError::TooManyRequests(format!(
+        "Trop de tentatives, veuillez réessayer dans {} secondes",
+        retry_after
+    ))
+    .into()
+}
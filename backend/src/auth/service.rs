@@ -1,18 +1,41 @@
 use jsonwebtoken::{encode, decode, Header, Validation};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Utc, Duration};
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore};
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
 
 
-use crate::auth::models::{Claims, AuthResponse, UserAuthResponse};
+use crate::auth::models::{Claims, AuthResponse, UserAuthResponse, LoginOutcome, TwoFactorChallengeResponse};
 use crate::auth::config::JWT_CONFIG;
+use crate::auth::extractor::AuthenticatedUser;
+use crate::auth::totp;
 use crate::model::users::User;
+use crate::model::sessions::DeviceInfo;
+use crate::model::two_factor::TwoFactorSettings;
+use crate::repositories::user_repository::UserRepository;
+use crate::repositories::session_repository::SessionRepository;
+use crate::repositories::login_attempt_repository::LoginAttemptRepository;
+use crate::repositories::two_factor_repository::TwoFactorRepository;
 use crate::errors::AppError;
 
+type HmacSha256 = Hmac<Sha256>;
+
+/// Durée de validité du défi émis quand la 2FA est activée
+const TWO_FACTOR_CHALLENGE_TTL_SECONDS: i64 = 300;
+/// Ancienneté maximale de la vérification 2FA pour les actions sensibles
+const TWO_FACTOR_RECENT_MINUTES: i64 = 15;
+/// Nombre de codes de secours générés à l'activation
+const BACKUP_CODE_COUNT: usize = 10;
+
 pub struct AuthService;
 
 impl AuthService {
 
-    pub fn generate_token(user: &User) -> Result<String, AppError> {
+    /// Jeton d'accès de courte durée rattaché à une session
+    pub fn generate_token(user: &User, session_id: Uuid) -> Result<String, AppError> {
         let now = Utc::now();
         let expiration = now + Duration::seconds(JWT_CONFIG.expiration);
 
@@ -21,6 +44,7 @@ impl AuthService {
             email: user.email.clone(),
             exp: expiration.timestamp(),
             iat: now.timestamp(),
+            sid: session_id,
         };
 
         encode(&Header::default(), &claims, &JWT_CONFIG.encoding_key)
@@ -35,6 +59,172 @@ impl AuthService {
             .map_err(|e| AppError::AuthenticationError(e.to_string()))
     }
 
+    /// Rejette les jetons dont la session a été révoquée ou a expiré
+    pub async fn ensure_session_active(pool: &PgPool, claims: &Claims) -> Result<(), AppError> {
+        if !SessionRepository::is_active(pool, claims.sid, claims.sub).await? {
+            return Err(AppError::AuthenticationError("Session révoquée, veuillez vous reconnecter".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Refuse la connexion tant que le verrouillage progressif du compte est en cours
+    pub async fn ensure_not_locked(pool: &PgPool, user_id: Uuid, ip_address: &str) -> Result<(), AppError> {
+        if let Some(locked_until) = LoginAttemptRepository::locked_until(pool, user_id, ip_address).await? {
+            let remaining = (locked_until - Utc::now()).num_seconds().max(1);
+            return Err(AppError::TooManyRequests(format!(
+                "Compte temporairement verrouillé après plusieurs échecs, réessayez dans {} secondes",
+                remaining
+            )));
+        }
+        Ok(())
+    }
+
+    /// Seuls les comptes vérifiés et actifs peuvent se connecter
+    pub async fn ensure_can_login(pool: &PgPool, user: &User) -> Result<(), AppError> {
+        if !UserRepository::find_verification_status(pool, user.id).await?.is_complete() {
+            return Err(AppError::AuthenticationError(
+                "Compte en attente de vérification de l'email et du téléphone".to_string(),
+            ));
+        }
+
+        if !user.is_active {
+            return Err(AppError::AuthenticationError("Votre compte est désactivé".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Ouvre une nouvelle session pour l'utilisateur et renvoie la paire de jetons
+    pub async fn open_session(
+        pool: &PgPool,
+        user: User,
+        device: &DeviceInfo,
+        two_factor_verified: bool,
+    ) -> Result<AuthResponse, AppError> {
+        let refresh_token = Self::generate_secure_token();
+        let expires_at = Utc::now() + Duration::seconds(JWT_CONFIG.refresh_expiration);
+        let two_factor_verified_at = two_factor_verified.then(Utc::now);
+        let session = SessionRepository::create(
+            pool,
+            user.id,
+            &Self::hash_token(&refresh_token),
+            device,
+            expires_at,
+            two_factor_verified_at,
+        ).await?;
+
+        let token = Self::generate_token(&user, session.id)?;
+        Ok(Self::create_auth_response(user, token, refresh_token))
+    }
+
+    /// Suite du premier facteur : ouvre la session, ou émet un défi si la 2FA est activée
+    pub async fn complete_first_factor(pool: &PgPool, user: User, device: &DeviceInfo) -> Result<LoginOutcome, AppError> {
+        let settings = TwoFactorRepository::find_settings(pool, user.id).await?;
+        if !settings.is_enabled() {
+            let auth_response = Self::open_session(pool, user, device, false).await?;
+            return Ok(LoginOutcome::Authenticated(auth_response));
+        }
+
+        let challenge_token = Self::generate_secure_token();
+        let expires_at = Utc::now() + Duration::seconds(TWO_FACTOR_CHALLENGE_TTL_SECONDS);
+        TwoFactorRepository::create_challenge(pool, user.id, &Self::hash_token(&challenge_token), expires_at).await?;
+
+        Ok(LoginOutcome::TwoFactorRequired(TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge_token,
+            expires_in: TWO_FACTOR_CHALLENGE_TTL_SECONDS,
+        }))
+    }
+
+    /// Vérifie un code TOTP (non réutilisable) ou, à défaut, consomme un code de secours
+    pub async fn verify_second_factor(
+        pool: &PgPool,
+        user_id: Uuid,
+        settings: &TwoFactorSettings,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        if let Some(secret) = &settings.totp_secret
+            && let Some(step) = totp::verify(secret, code, Utc::now().timestamp())
+        {
+            return TwoFactorRepository::record_totp_step(pool, user_id, step).await;
+        }
+
+        let backup_hash = Self::hash_token(&Self::normalize_backup_code(code));
+        TwoFactorRepository::consume_backup_code(pool, user_id, &backup_hash).await
+    }
+
+    /// Les paiements et la clôture des tours exigent une vérification 2FA récente sur la session
+    pub async fn require_recent_two_factor(pool: &PgPool, user: &AuthenticatedUser) -> Result<(), AppError> {
+        let verified_at = SessionRepository::two_factor_verified_at(pool, user.session_id).await?;
+
+        match verified_at {
+            Some(verified_at) if verified_at > Utc::now() - Duration::minutes(TWO_FACTOR_RECENT_MINUTES) => Ok(()),
+            _ => Err(AppError::Forbidden(format!(
+                "Cette action exige une double authentification vérifiée depuis moins de {} minutes",
+                TWO_FACTOR_RECENT_MINUTES
+            ))),
+        }
+    }
+
+    /// Codes de secours à usage unique, au format `xxxx-xxxx`
+    pub fn generate_backup_codes() -> Vec<String> {
+        (0..BACKUP_CODE_COUNT)
+            .map(|_| {
+                let mut bytes = [0u8; 4];
+                rand::thread_rng().fill_bytes(&mut bytes);
+                let code = hex::encode(bytes);
+                format!("{}-{}", &code[..4], &code[4..])
+            })
+            .collect()
+    }
+
+    pub fn normalize_backup_code(code: &str) -> String {
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    }
+
+    /// Échange un jeton de rafraîchissement contre une nouvelle paire de jetons
+    pub async fn rotate_session(pool: &PgPool, refresh_token: &str) -> Result<AuthResponse, AppError> {
+        let new_refresh_token = Self::generate_secure_token();
+        let expires_at = Utc::now() + Duration::seconds(JWT_CONFIG.refresh_expiration);
+        let session = SessionRepository::rotate(
+            pool,
+            &Self::hash_token(refresh_token),
+            &Self::hash_token(&new_refresh_token),
+            expires_at,
+        ).await?;
+
+        let user = UserRepository::find_by_id_with_password(pool, session.user_id).await?;
+        if !user.is_active {
+            return Err(AppError::AuthenticationError("Votre compte est désactivé".to_string()));
+        }
+
+        let token = Self::generate_token(&user, session.id)?;
+        Ok(Self::create_auth_response(user, token, new_refresh_token))
+    }
+
+    /// Génère un jeton aléatoire (32 octets, encodé en hexadécimal) à transmettre à l'utilisateur
+    pub fn generate_secure_token() -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    /// Code numérique à 6 chiffres envoyé par SMS
+    pub fn generate_otp_code() -> String {
+        format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+    }
+
+    /// Empreinte HMAC-SHA256 d'un jeton : seule cette valeur est stockée en base
+    pub fn hash_token(token: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(JWT_CONFIG.secret.as_bytes())
+            .expect("HMAC accepte des clés de toute taille");
+        mac.update(token.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
     pub fn hash_password(password: &str) -> Result<String, AppError> {
         hash(password, DEFAULT_COST)
             .map_err(|e| AppError::InternalServerError(e.to_string()))
@@ -46,9 +236,10 @@ impl AuthService {
     }
 
     
-    pub fn create_auth_response(user: User, token: String) -> AuthResponse {
+    pub fn create_auth_response(user: User, token: String, refresh_token: String) -> AuthResponse {
         AuthResponse {
             access_token: token,
+            refresh_token,
             token_type: "Bearer".to_string(),
             expires_in: JWT_CONFIG.expiration,
             user: UserAuthResponse {
@@ -0,0 +1,172 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// Nom affiché dans l'application d'authentification
+const ISSUER: &str = "Tontine";
+/// Durée d'un pas TOTP en secondes (RFC 6238)
+const STEP_SECONDS: i64 = 30;
+/// Nombre de chiffres du code
+const DIGITS: u32 = 6;
+/// Pas acceptés avant et après le pas courant (décalage d'horloge du téléphone)
+const ALLOWED_DRIFT: i64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Génère un secret de 160 bits encodé en base32, format attendu par les applications TOTP
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+/// URI `otpauth://` à afficher sous forme de QR code lors de l'enrôlement
+pub fn provisioning_uri(secret: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = ISSUER,
+        account = url_encode(account),
+        secret = secret,
+        digits = DIGITS,
+        period = STEP_SECONDS,
+    )
+}
+
+/// Vérifie le code pour l'instant `unix_time` ; renvoie le pas correspondant
+/// afin que l'appelant puisse refuser la réutilisation d'un même code
+pub fn verify(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let key = base32_decode(secret)?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current_step = unix_time / STEP_SECONDS;
+    (current_step - ALLOWED_DRIFT..=current_step + ALLOWED_DRIFT)
+        .find(|step| code_at(&key, *step) == code)
+}
+
+fn code_at(key: &[u8], step: i64) -> String {
+    let mut mac = HmacSha1::new_from_slice(key).expect("HMAC accepte des clés de toute taille");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Troncature dynamique (RFC 4226, section 5.3)
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+
+    format!("{:0width$}", binary % 10_u32.pow(DIGITS), width = DIGITS as usize)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    output
+}
+
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in input.trim_end_matches('=').chars().filter(|c| !c.is_whitespace()) {
+        let value = BASE32_ALPHABET.iter().position(|a| *a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+fn url_encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Secret des vecteurs de test SHA1 de la RFC 6238 (annexe B)
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    /// (instant, code à 8 chiffres de la RFC) : le code à 6 chiffres en est la fin
+    const RFC_VECTORS: [(i64, &str); 6] = [
+        (59, "94287082"),
+        (1_111_111_109, "07081804"),
+        (1_111_111_111, "14050471"),
+        (1_234_567_890, "89005924"),
+        (2_000_000_000, "69279037"),
+        (20_000_000_000, "65353130"),
+    ];
+
+    #[test]
+    fn code_at_matches_rfc_6238_vectors() {
+        for (time, expected) in RFC_VECTORS {
+            assert_eq!(code_at(RFC_SECRET, time / STEP_SECONDS), expected[2..], "instant {}", time);
+        }
+    }
+
+    #[test]
+    fn verify_accepts_rfc_codes_and_returns_their_step() {
+        let secret = base32_encode(RFC_SECRET);
+        for (time, expected) in RFC_VECTORS {
+            assert_eq!(verify(&secret, &expected[2..], time), Some(time / STEP_SECONDS), "instant {}", time);
+        }
+    }
+
+    #[test]
+    fn verify_tolerates_one_step_of_drift_only() {
+        let secret = base32_encode(RFC_SECRET);
+        let (time, expected) = RFC_VECTORS[3];
+        let code = &expected[2..];
+
+        assert_eq!(verify(&secret, code, time + STEP_SECONDS), Some(time / STEP_SECONDS));
+        assert_eq!(verify(&secret, code, time - STEP_SECONDS), Some(time / STEP_SECONDS));
+        assert_eq!(verify(&secret, code, time + 2 * STEP_SECONDS), None);
+    }
+
+    #[test]
+    fn verify_rejects_malformed_codes() {
+        let secret = base32_encode(RFC_SECRET);
+        let (time, expected) = RFC_VECTORS[0];
+
+        assert_eq!(verify(&secret, expected, time), None);
+        assert_eq!(verify(&secret, "28708a", time), None);
+        assert_eq!(verify(&secret, "", time), None);
+        assert_eq!(verify("not base32!", &expected[2..], time), None);
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        let encoded = base32_encode(RFC_SECRET);
+        assert_eq!(encoded, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(base32_decode(&encoded).as_deref(), Some(RFC_SECRET));
+        assert_eq!(base32_decode(&encoded.to_lowercase()).as_deref(), Some(RFC_SECRET));
+        assert_eq!(generate_secret().len(), 32);
+    }
+}
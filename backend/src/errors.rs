@@ -8,7 +8,15 @@ pub enum AppError {
     DatabaseError(String),
     NotFound(String),
     ValidationError(String),
+    /// Erreurs de validation rattachées aux champs de la requête
+    InvalidFields(Vec<FieldError>),
     AuthenticationError(String),
+    Forbidden(String),
+    TooManyRequests(String),
+    /// Requête en concurrence avec une autre, ou incompatible avec une requête précédente
+    Conflict(String),
+    /// Échec ou réponse inattendue de l'opérateur de paiement
+    PaymentProviderError(String),
     InternalServerError(String),
 }
 
@@ -18,13 +26,35 @@ struct ErrorResponse {
     message: String,
 }
 
+/// Champ refusé et motif du refus
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct FieldErrorsResponse<'a> {
+    error: String,
+    message: String,
+    fields: &'a [FieldError],
+}
+
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AppError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::InvalidFields(fields) => {
+                let fields: Vec<String> = fields.iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+                write!(f, "Validation error: {}", fields.join(", "))
+            }
             AppError::AuthenticationError(msg) => write!(f, "Authentication error: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::PaymentProviderError(msg) => write!(f, "Payment provider error: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "Internal server error: {}", msg),
         }
     }
@@ -51,12 +81,43 @@ impl ResponseError for AppError {
                     message: msg.to_string(),
                 })
             }
+            AppError::InvalidFields(fields) => {
+                HttpResponse::BadRequest().json(FieldErrorsResponse {
+                    error: "VALIDATION_ERROR".to_string(),
+                    message: "Certains champs sont invalides".to_string(),
+                    fields,
+                })
+            }
             AppError::AuthenticationError(msg) => {
                 HttpResponse::Unauthorized().json(ErrorResponse {
                     error: "AUTHENTICATION_ERROR".to_string(),
                     message: msg.to_string(),
                 })
             }
+            AppError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(ErrorResponse {
+                    error: "FORBIDDEN".to_string(),
+                    message: msg.to_string(),
+                })
+            }
+            AppError::TooManyRequests(msg) => {
+                HttpResponse::TooManyRequests().json(ErrorResponse {
+                    error: "TOO_MANY_REQUESTS".to_string(),
+                    message: msg.to_string(),
+                })
+            }
+            AppError::Conflict(msg) => {
+                HttpResponse::Conflict().json(ErrorResponse {
+                    error: "CONFLICT".to_string(),
+                    message: msg.to_string(),
+                })
+            }
+            AppError::PaymentProviderError(msg) => {
+                HttpResponse::BadGateway().json(ErrorResponse {
+                    error: "PAYMENT_PROVIDER_ERROR".to_string(),
+                    message: msg.to_string(),
+                })
+            }
             AppError::InternalServerError(msg) => {
                 HttpResponse::InternalServerError().json(ErrorResponse {
                     error: "INTERNAL_ERROR".to_string(),
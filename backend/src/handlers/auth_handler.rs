@@ -1,68 +1,263 @@
-use actix_web::{web, HttpResponse, HttpRequest};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
 use serde_json::json;
-use actix_web::HttpMessage;
+use uuid::Uuid;
+use chrono::{Duration, Utc};
 
 
-use crate::auth::models::{LoginRequest, ChangePasswordRequest, ResetPasswordRequest, ConfirmResetPasswordRequest};
+use crate::auth::models::{LoginRequest, ChangePasswordRequest, ResetPasswordRequest, ConfirmResetPasswordRequest, RefreshTokenRequest, OtpRequest, OtpLoginRequest, VerifyEmailRequest, VerifyPhoneRequest, ResendVerificationRequest};
 use crate::auth::service::AuthService;
+use crate::auth::rate_limit::client_ip;
+use crate::auth::extractor::AuthenticatedUser;
 use crate::repositories::user_repository::UserRepository;
+use crate::repositories::password_reset_repository::PasswordResetRepository;
+use crate::repositories::session_repository::SessionRepository;
+use crate::repositories::otp_repository::OtpRepository;
+use crate::repositories::login_attempt_repository::LoginAttemptRepository;
+use crate::model::sessions::DeviceInfo;
+use crate::model::otp_codes::OtpPurpose;
+use crate::model::users::{CreateUser, UserResponse, VerificationStatus};
+use crate::notifications::{Channel, Notification, Notifier};
 use crate::errors::AppError;
 
+/// Durée de validité d'un jeton de réinitialisation de mot de passe
+const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+/// Durée de validité d'un code de connexion envoyé par SMS
+const OTP_TTL_MINUTES: i64 = 5;
+/// Durée de validité des codes de vérification envoyés à l'inscription
+const VERIFICATION_CODE_TTL_MINUTES: i64 = 30;
+/// Nombre de codes erronés tolérés avant d'invalider le code
+const OTP_MAX_ATTEMPTS: i32 = 5;
+
 pub struct AuthHandler;
 
 impl AuthHandler {
+    pub async fn register(
+        pool: web::Data<sqlx::PgPool>,
+        notifier: web::Data<dyn Notifier>,
+        user_data: web::Json<CreateUser>,
+    ) -> Result<HttpResponse, AppError> {
+        // Le compte est créé en attente de vérification
+        let user = UserRepository::create(&pool, &user_data.into_inner()).await?;
+
+        let status = VerificationStatus { email_verified: false, phone_verified: false };
+        send_verification_codes(&pool, notifier.get_ref(), &user, status).await?;
+
+        Ok(HttpResponse::Created().json(json!({
+            "message": "Compte créé, saisissez les codes reçus par email et par SMS pour l'activer",
+            "user": user
+        })))
+    }
+
+    pub async fn verify_email(
+        pool: web::Data<sqlx::PgPool>,
+        verify_data: web::Json<VerifyEmailRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let user = UserRepository::find_by_email(&pool, &verify_data.email).await
+            .map_err(|_| AppError::AuthenticationError("Code invalide ou expiré".to_string()))?;
+
+        let code_hash = AuthService::hash_token(&verify_data.code);
+        OtpRepository::verify(&pool, user.id, OtpPurpose::VerifyEmail, &code_hash, OTP_MAX_ATTEMPTS).await?;
+
+        let user = UserRepository::mark_email_verified(&pool, user.id).await?;
+        Ok(HttpResponse::Ok().json(verification_response(&pool, user).await?))
+    }
+
+    pub async fn verify_phone(
+        pool: web::Data<sqlx::PgPool>,
+        verify_data: web::Json<VerifyPhoneRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let user = UserRepository::find_by_phone(&pool, &verify_data.phone).await
+            .map_err(|_| AppError::AuthenticationError("Code invalide ou expiré".to_string()))?;
+
+        let code_hash = AuthService::hash_token(&verify_data.code);
+        OtpRepository::verify(&pool, user.id, OtpPurpose::VerifyPhone, &code_hash, OTP_MAX_ATTEMPTS).await?;
+
+        let user = UserRepository::mark_phone_verified(&pool, user.id).await?;
+        Ok(HttpResponse::Ok().json(verification_response(&pool, user).await?))
+    }
+
+    pub async fn resend_verification(
+        pool: web::Data<sqlx::PgPool>,
+        notifier: web::Data<dyn Notifier>,
+        resend_data: web::Json<ResendVerificationRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let response = HttpResponse::Ok().json(json!({
+            "message": "Si le compte est en attente de vérification, de nouveaux codes ont été envoyés"
+        }));
+
+        let user = match UserRepository::find_by_email(&pool, &resend_data.email).await {
+            Ok(user) => user,
+            // On ne révèle pas si l'email existe ou pas
+            Err(AppError::NotFound(_)) => return Ok(response),
+            Err(e) => return Err(e),
+        };
+
+        let status = UserRepository::find_verification_status(&pool, user.id).await?;
+        if !status.is_complete() {
+            send_verification_codes(&pool, notifier.get_ref(), &user.into(), status).await?;
+        }
+
+        Ok(response)
+    }
+
     pub async fn login(
+        req: HttpRequest,
         pool: web::Data<sqlx::PgPool>,
         login_data: web::Json<LoginRequest>,
     ) -> Result<HttpResponse, AppError> {
+        let device = device_info(&req);
+        let ip = client_ip(&req);
+
         // Trouver l'utilisateur par email
         let user = match UserRepository::find_by_email(&pool, &login_data.email).await {
             Ok(user) => user,
             Err(_) => {
+                LoginAttemptRepository::record_failure(&pool, None, &login_data.email, "password", "unknown_account", &device, &ip).await?;
                 return Err(AppError::AuthenticationError("Email ou mot de passe incorrect".to_string()));
             }
         };
 
-        // Vérifier si l'utilisateur est actif
-        if !user.is_active {
-            return Err(AppError::AuthenticationError("Votre compte est désactivé".to_string()));
-        }
+        // Refuser avant toute vérification coûteuse si le compte est verrouillé
+        AuthService::ensure_not_locked(&pool, user.id, &ip).await?;
+
+        // Vérifier que le compte est vérifié et actif
+        AuthService::ensure_can_login(&pool, &user).await?;
 
         // Vérifier le mot de passe
         let is_valid = AuthService::verify_password(&login_data.password, &user.password_hash)?;
         if !is_valid {
+            LoginAttemptRepository::record_failure(&pool, Some(user.id), &login_data.email, "password", "invalid_password", &device, &ip).await?;
             return Err(AppError::AuthenticationError("Email ou mot de passe incorrect".to_string()));
         }
 
-        // Générer le token JWT
-        let token = AuthService::generate_token(&user)?;
+        LoginAttemptRepository::reset(&pool, user.id, &ip).await?;
 
-        // Créer la réponse
-        let auth_response = AuthService::create_auth_response(user, token);
+        // Ouvrir une session, ou demander le second facteur si la 2FA est activée
+        let outcome = AuthService::complete_first_factor(&pool, user, &device).await?;
 
-        Ok(HttpResponse::Ok().json(auth_response))
+        Ok(HttpResponse::Ok().json(outcome))
+    }
+
+    pub async fn request_otp(
+        pool: web::Data<sqlx::PgPool>,
+        notifier: web::Data<dyn Notifier>,
+        otp_data: web::Json<OtpRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let response = HttpResponse::Ok().json(json!({
+            "message": "Si le numéro est enregistré, un code de connexion a été envoyé"
+        }));
+
+        let user = match UserRepository::find_by_phone(&pool, &otp_data.phone).await {
+            Ok(user) => user,
+            // On ne révèle pas si le numéro existe ou pas
+            Err(AppError::NotFound(_)) => return Ok(response),
+            Err(e) => return Err(e),
+        };
+
+        if !user.is_active {
+            return Ok(response);
+        }
+
+        let code = AuthService::generate_otp_code();
+        let expires_at = Utc::now() + Duration::minutes(OTP_TTL_MINUTES);
+        OtpRepository::create(&pool, user.id, OtpPurpose::Login, &AuthService::hash_token(&code), expires_at).await?;
+
+        let notification = Notification {
+            channel: Channel::Sms,
+            recipient: user.phone.clone(),
+            subject: "Code de connexion Tontine".to_string(),
+            body: format!("Votre code de connexion Tontine : {} (valable {} minutes)", code, OTP_TTL_MINUTES),
+        };
+        notifier.send(&notification).await?;
+
+        Ok(response)
+    }
+
+    pub async fn login_with_otp(
+        req: HttpRequest,
+        pool: web::Data<sqlx::PgPool>,
+        login_data: web::Json<OtpLoginRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let device = device_info(&req);
+        let ip = client_ip(&req);
+
+        let user = match UserRepository::find_by_phone(&pool, &login_data.phone).await {
+            Ok(user) => user,
+            Err(_) => {
+                LoginAttemptRepository::record_failure(&pool, None, &login_data.phone, "otp", "unknown_account", &device, &ip).await?;
+                return Err(AppError::AuthenticationError("Code invalide ou expiré".to_string()));
+            }
+        };
+
+        AuthService::ensure_not_locked(&pool, user.id, &ip).await?;
+        AuthService::ensure_can_login(&pool, &user).await?;
+
+        // Vérifier le code (expiration et nombre de tentatives)
+        let code_hash = AuthService::hash_token(&login_data.code);
+        if let Err(e) = OtpRepository::verify(&pool, user.id, OtpPurpose::Login, &code_hash, OTP_MAX_ATTEMPTS).await {
+            if matches!(e, AppError::AuthenticationError(_)) {
+                LoginAttemptRepository::record_failure(&pool, Some(user.id), &login_data.phone, "otp", "invalid_code", &device, &ip).await?;
+            }
+            return Err(e);
+        }
+
+        LoginAttemptRepository::reset(&pool, user.id, &ip).await?;
+
+        // Même réponse que la connexion par email
+        let outcome = AuthService::complete_first_factor(&pool, user, &device).await?;
+
+        Ok(HttpResponse::Ok().json(outcome))
     }
 
-    pub async fn logout() -> Result<HttpResponse, AppError> {
-        // Pour un logout simple, le client doit simplement supprimer le token
-        // Pour un logout plus avancé, on pourrait utiliser une blacklist de tokens
+    pub async fn logout(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        // Révoquer la session courante : le jeton d'accès et le jeton de rafraîchissement deviennent inutilisables
+        SessionRepository::revoke(&pool, user.session_id, user.id).await?;
+
         Ok(HttpResponse::Ok().json(json!({
             "message": "Déconnexion réussie"
         })))
     }
 
+    pub async fn logout_all(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let mut conn = pool.acquire().await?;
+        let revoked = SessionRepository::revoke_all_for_user(&mut conn, user.id, None).await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Toutes les sessions ont été fermées",
+            "revoked_sessions": revoked
+        })))
+    }
+
+    pub async fn get_sessions(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let sessions = SessionRepository::find_active_by_user(&pool, user.id).await?;
+        Ok(HttpResponse::Ok().json(sessions))
+    }
+
+    pub async fn revoke_session(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        session_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        SessionRepository::revoke(&pool, session_id.into_inner(), user.id).await?;
+        Ok(HttpResponse::NoContent().finish())
+    }
+
     pub async fn change_password(
         pool: web::Data<sqlx::PgPool>,
-        req: HttpRequest,
+        user: AuthenticatedUser,
         password_data: web::Json<ChangePasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
-        // Récupérer l'utilisateur depuis le token - CORRECTION ICI
-        let extensions = req.extensions();
-        let claims = extensions
-            .get::<crate::auth::models::Claims>()
-            .ok_or_else(|| AppError::AuthenticationError("Token invalide".to_string()))?;
-
-        let user_id = claims.sub;
+        let user_id = user.id;
+        let current_session_id = user.session_id;
 
         // Récupérer l'utilisateur COMPLET avec le mot de passe - CORRECTION ICI
         let user = UserRepository::find_by_id_with_password(&pool, user_id).await?;
@@ -73,15 +268,8 @@ impl AuthHandler {
             return Err(AppError::AuthenticationError("Mot de passe actuel incorrect".to_string()));
         }
 
-        // Hasher le nouveau mot de passe
-        let new_password_hash = AuthService::hash_password(&password_data.new_password)?;
-
-        // Mettre à jour le mot de passe
-        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
-            .bind(&new_password_hash)
-            .bind(user_id)
-            .execute(&**pool)
-            .await?;
+        // Mettre à jour le mot de passe et fermer les autres sessions
+        UserRepository::change_password(&pool, user_id, &password_data.new_password, Some(current_session_id)).await?;
 
         Ok(HttpResponse::Ok().json(json!({
             "message": "Mot de passe modifié avec succès"
@@ -90,15 +278,9 @@ impl AuthHandler {
 
     pub async fn get_me(
         pool: web::Data<sqlx::PgPool>,
-        req: HttpRequest,
+        user: AuthenticatedUser,
     ) -> Result<HttpResponse, AppError> {
-        // Récupérer l'utilisateur depuis le token - CORRECTION ICI
-        let extensions = req.extensions_mut();
-        let claims = extensions
-            .get::<crate::auth::models::Claims>()
-            .ok_or_else(|| AppError::AuthenticationError("Token invalide".to_string()))?;
-
-        let user_id = claims.sub;
+        let user_id = user.id;
 
         // Récupérer l'utilisateur (version publique sans mot de passe)
         let user = UserRepository::find_by_id(&pool, user_id).await?;
@@ -108,63 +290,124 @@ impl AuthHandler {
 
     pub async fn refresh_token(
         pool: web::Data<sqlx::PgPool>,
-        req: HttpRequest,
+        refresh_data: web::Json<RefreshTokenRequest>,
     ) -> Result<HttpResponse, AppError> {
-        // Récupérer l'utilisateur depuis le token - CORRECTION ICI
-        let extensions = req.extensions();
-        let claims = extensions
-            .get::<crate::auth::models::Claims>()
-            .ok_or_else(|| AppError::AuthenticationError("Token invalide".to_string()))?;
-
-        let user_id = claims.sub;
-
-        // Récupérer l'utilisateur COMPLET pour générer le token - CORRECTION ICI
-        let user = UserRepository::find_by_id_with_password(&pool, user_id).await?;
-
-        // Générer un nouveau token
-        let new_token = AuthService::generate_token(&user)?;
-
-        // Créer la réponse
-        let auth_response = AuthService::create_auth_response(user, new_token);
+        // Le jeton présenté est consommé et remplacé par un nouveau
+        let auth_response = AuthService::rotate_session(&pool, &refresh_data.refresh_token).await?;
 
         Ok(HttpResponse::Ok().json(auth_response))
     }
 
     pub async fn request_password_reset(
         pool: web::Data<sqlx::PgPool>,
+        notifier: web::Data<dyn Notifier>,
         reset_data: web::Json<ResetPasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
-        // Dans une vraie application, on enverrait un email avec un lien de réinitialisation
-        // Pour l'instant, on simule juste l'envoi
-        let _user = match UserRepository::find_by_email(&pool, &reset_data.email).await {
+        let response = HttpResponse::Ok().json(json!({
+            "message": "Si l'email existe, un lien de réinitialisation a été envoyé"
+        }));
+
+        let user = match UserRepository::find_by_email(&pool, &reset_data.email).await {
             Ok(user) => user,
-            Err(_) => {
-                // On ne révèle pas si l'email existe ou pas
-                return Ok(HttpResponse::Ok().json(json!({
-                    "message": "Si l'email existe, un lien de réinitialisation a été envoyé"
-                })));
-            }
+            // On ne révèle pas si l'email existe ou pas
+            Err(AppError::NotFound(_)) => return Ok(response),
+            Err(e) => return Err(e),
         };
 
-        // Ici, on générerait un token de réinitialisation et on enverrait un email
-        // Pour la démo, on retourne juste un message
+        // Générer un jeton à usage unique, seul son hash est conservé
+        let token = AuthService::generate_secure_token();
+        let expires_at = Utc::now() + Duration::minutes(RESET_TOKEN_TTL_MINUTES);
+        PasswordResetRepository::create(&pool, user.id, &AuthService::hash_token(&token), expires_at).await?;
+
+        let notification = Notification {
+            channel: Channel::Email,
+            recipient: user.email,
+            subject: "Réinitialisation de votre mot de passe".to_string(),
+            body: format!(
+                "Utilisez ce code pour réinitialiser votre mot de passe : {} (valable {} minutes)",
+                token, RESET_TOKEN_TTL_MINUTES
+            ),
+        };
+        notifier.send(&notification).await?;
 
-        Ok(HttpResponse::Ok().json(json!({
-            "message": "Si l'email existe, un lien de réinitialisation a été envoyé"
-        })))
+        Ok(response)
     }
 
     pub async fn confirm_password_reset(
         pool: web::Data<sqlx::PgPool>,
         confirm_data: web::Json<ConfirmResetPasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
-        // Dans une vraie application, on vérifierait le token de réinitialisation
-        // Pour la démo, on simule la réinitialisation
+        let new_password_hash = AuthService::hash_password(&confirm_data.new_password)?;
+
+        // Invalide le jeton et révoque toutes les sessions ouvertes
+        PasswordResetRepository::consume(&pool, &AuthService::hash_token(&confirm_data.token), &new_password_hash).await?;
 
-        // Ici, on devrait avoir une table pour stocker les tokens de réinitialisation
-        // et vérifier leur validité
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Mot de passe réinitialisé avec succès, veuillez vous reconnecter"
+        })))
+    }
+}
+
+/// Appareil à l'origine de la requête, enregistré avec la session
+pub fn device_info(req: &HttpRequest) -> DeviceInfo {
+    DeviceInfo {
+        user_agent: req.headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        ip_address: req.connection_info()
+            .realip_remote_addr()
+            .map(|addr| addr.to_string()),
+    }
+}
+
+/// Envoie un code de vérification pour chaque moyen de contact non encore vérifié
+pub(crate) async fn send_verification_codes(
+    pool: &sqlx::PgPool,
+    notifier: &dyn Notifier,
+    user: &UserResponse,
+    status: VerificationStatus,
+) -> Result<(), AppError> {
+    let pending = [
+        (status.email_verified, OtpPurpose::VerifyEmail, Channel::Email, &user.email),
+        (status.phone_verified, OtpPurpose::VerifyPhone, Channel::Sms, &user.phone),
+    ];
+
+    for (verified, purpose, channel, recipient) in pending {
+        if verified {
+            continue;
+        }
 
-        // Pour l'instant, on retourne une erreur indiquant que c'est une démo
-        Err(AppError::ValidationError("Fonctionnalité de réinitialisation en cours de développement".to_string()))
+        let code = AuthService::generate_otp_code();
+        let expires_at = Utc::now() + Duration::minutes(VERIFICATION_CODE_TTL_MINUTES);
+        OtpRepository::create(pool, user.id, purpose, &AuthService::hash_token(&code), expires_at).await?;
+
+        let notification = Notification {
+            channel,
+            recipient: recipient.clone(),
+            subject: "Vérification de votre compte Tontine".to_string(),
+            body: format!(
+                "Votre code de vérification : {} (valable {} minutes)",
+                code, VERIFICATION_CODE_TTL_MINUTES
+            ),
+        };
+        notifier.send(&notification).await?;
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+async fn verification_response(pool: &sqlx::PgPool, user: UserResponse) -> Result<serde_json::Value, AppError> {
+    let status = UserRepository::find_verification_status(pool, user.id).await?;
+    let message = if status.is_complete() {
+        "Vérification terminée, vous pouvez vous connecter"
+    } else {
+        "Vérification enregistrée, il reste à vérifier l'autre moyen de contact"
+    };
+
+    Ok(json!({
+        "message": message,
+        "verification": status,
+        "user": user
+    }))
+}
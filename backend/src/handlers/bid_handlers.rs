@@ -0,0 +1,95 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+use crate::model::bids::{OpenBidding, SubmitBid};
+use crate::repositories::bid_repository::BidRepository;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::errors::AppError;
+
+pub struct BidHandler;
+
+impl BidHandler {
+    /// Fenêtre d'enchères du tour ; un simple membre ne voit que ses propres offres
+    pub async fn get_bidding(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        let role = AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let only_user = if Permission::ViewAllRecords.is_granted_to(role) { None } else { Some(user.id) };
+        let state = BidRepository::find_state(&pool, round_id, only_user).await?;
+        Ok(HttpResponse::Ok().json(state))
+    }
+
+    pub async fn open_bidding(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+        bidding_data: web::Json<OpenBidding>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let window = BidRepository::open_window(&pool, round_id, user.id, &bidding_data).await?;
+        Ok(HttpResponse::Ok().json(window))
+    }
+
+    pub async fn close_bidding(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let window = BidRepository::close_window(&pool, round_id).await?;
+        Ok(HttpResponse::Ok().json(window))
+    }
+
+    pub async fn submit_bid(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+        bid_data: web::Json<SubmitBid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::Contribute).await?;
+
+        let bid = BidRepository::submit(&pool, round_id, user.id, bid_data.amount).await?;
+        Ok(HttpResponse::Ok().json(bid))
+    }
+
+    pub async fn withdraw_bid(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::Contribute).await?;
+
+        let bid = BidRepository::withdraw(&pool, round_id, user.id).await?;
+        Ok(HttpResponse::Ok().json(bid))
+    }
+
+    /// Désigne le gagnant une fois la fenêtre close et enregistre la remise
+    pub async fn resolve_bidding(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let resolution = BidRepository::resolve(&pool, round_id).await?;
+        Ok(HttpResponse::Ok().json(resolution))
+    }
+}
@@ -1,96 +1,174 @@
 use actix_web::{web, HttpResponse};
 use uuid::Uuid;
 
-use crate::model::contributions::{CreateContribution, UpdateContribution};
+use crate::model::contributions::{CreateContribution, PaymentMethod, PaymentStatus, UpdateContribution};
 use crate::repositories::contributions_repository::{ContributionRepository};
+use crate::payments::PaymentProviders;
+use crate::payments::service::PaymentService;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
 use crate::errors::AppError;
 
 pub struct ContributionHandler;
 
 impl ContributionHandler {
-    pub async fn get_contributions(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let contributions = ContributionRepository::find_all(&pool).await?;
+    pub async fn get_contributions(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let contributions = ContributionRepository::find_all(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(contributions))
     }
 
     pub async fn get_contribution(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         contribution_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let contribution = ContributionRepository::find_by_id(&pool, contribution_id.into_inner()).await?;
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, member_user_id) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &[member_user_id], Permission::ViewAllRecords).await?;
+
+        let contribution = ContributionRepository::find_by_id(&pool, contribution_id).await?;
         Ok(HttpResponse::Ok().json(contribution))
     }
 
     pub async fn get_round_contributions(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let contributions = ContributionRepository::find_by_round(&pool, round_id.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let contributions = ContributionRepository::find_by_round(&pool, round_id).await?;
         Ok(HttpResponse::Ok().json(contributions))
     }
 
     pub async fn get_member_contributions(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let contributions = ContributionRepository::find_by_member(&pool, member_id.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, member_user_id) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &[member_user_id], Permission::ViewAllRecords).await?;
+
+        let contributions = ContributionRepository::find_by_member(&pool, member_id).await?;
         Ok(HttpResponse::Ok().json(contributions))
     }
 
     pub async fn create_contribution(
         pool: web::Data<sqlx::PgPool>,
+        providers: web::Data<PaymentProviders>,
+        user: AuthenticatedUser,
         contribution_data: web::Json<CreateContribution>,
     ) -> Result<HttpResponse, AppError> {
-        let contribution = ContributionRepository::create(&pool, &contribution_data.into_inner()).await?;
+        let contribution_data = contribution_data.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, contribution_data.tontine_round_id).await?;
+        let (_, member_user_id) = AccessControl::member_context(&pool, contribution_data.member_id).await?;
+
+        // Cotiser pour soi-même ou enregistrer la cotisation d'un autre membre
+        let permission = if member_user_id == user.id {
+            Permission::Contribute
+        } else {
+            Permission::RecordContributions
+        };
+        let role = AccessControl::require(&pool, tontine_id, user.id, permission).await?;
+
+        // Mobile money : la cotisation reste en attente jusqu'à la confirmation de l'opérateur
+        if matches!(contribution_data.payment_method, PaymentMethod::MobileMoney) {
+            let payment = PaymentService::collect_contribution(&pool, &providers, &contribution_data, member_user_id).await?;
+            return Ok(HttpResponse::Created().json(payment));
+        }
+
+        // Une cotisation déclarée par le membre attend la confirmation du trésorier (mark-paid)
+        let payment_status = if Permission::RecordContributions.is_granted_to(role) {
+            PaymentStatus::Paid
+        } else {
+            PaymentStatus::Pending
+        };
+        let contribution = ContributionRepository::create(&pool, &contribution_data, payment_status).await?;
         Ok(HttpResponse::Created().json(contribution))
     }
 
     pub async fn update_contribution(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         contribution_id: web::Path<Uuid>,
         contribution_data: web::Json<UpdateContribution>,
     ) -> Result<HttpResponse, AppError> {
-        let contribution = ContributionRepository::update(&pool, contribution_id.into_inner(), &contribution_data.into_inner()).await?;
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, _) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let contribution = ContributionRepository::update(&pool, contribution_id, &contribution_data.into_inner()).await?;
         Ok(HttpResponse::Ok().json(contribution))
     }
 
     pub async fn delete_contribution(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         contribution_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        ContributionRepository::delete(&pool, contribution_id.into_inner()).await?;
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, _) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        ContributionRepository::delete(&pool, contribution_id).await?;
         Ok(HttpResponse::NoContent().finish())
     }
 
     pub async fn mark_as_paid(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         contribution_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let contribution = ContributionRepository::mark_as_paid(&pool, contribution_id.into_inner()).await?;
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, _) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let contribution = ContributionRepository::mark_as_paid(&pool, contribution_id).await?;
         Ok(HttpResponse::Ok().json(contribution))
     }
 
     pub async fn mark_as_failed(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         contribution_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let contribution = ContributionRepository::mark_as_failed(&pool, contribution_id.into_inner()).await?;
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, _) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let contribution = ContributionRepository::mark_as_failed(&pool, contribution_id).await?;
         Ok(HttpResponse::Ok().json(contribution))
     }
 
     pub async fn get_round_summary(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let summary = ContributionRepository::get_round_summary(&pool, round_id.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewSummaries).await?;
+
+        let summary = ContributionRepository::get_round_summary(&pool, round_id).await?;
         Ok(HttpResponse::Ok().json(summary))
     }
 
     pub async fn get_member_contributions_summary(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let summary = ContributionRepository::get_member_contributions_summary(&pool, member_id.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, member_user_id) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &[member_user_id], Permission::ViewSummaries).await?;
+
+        let summary = ContributionRepository::get_member_contributions_summary(&pool, member_id).await?;
         Ok(HttpResponse::Ok().json(summary))
     }
-}
\ No newline at end of file
+}
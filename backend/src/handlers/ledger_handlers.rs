@@ -0,0 +1,48 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+use crate::repositories::ledger_repository::LedgerRepository;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::errors::AppError;
+
+pub struct LedgerHandler;
+
+impl LedgerHandler {
+    pub async fn get_balances(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewSummaries).await?;
+
+        let balances = LedgerRepository::find_balances(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(balances))
+    }
+
+    pub async fn get_journal(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let entries = LedgerRepository::find_entries(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(entries))
+    }
+
+    /// Vérifie que toutes les écritures de la tontine s'équilibrent
+    pub async fn check(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let check = LedgerRepository::check(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(check))
+    }
+}
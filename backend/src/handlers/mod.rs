@@ -4,4 +4,10 @@ pub mod  tontine_member_handlers;
 pub mod tontine_round_handlers;
 pub mod  contribution_handlers;
 pub mod transaction_handlers;
-pub mod auth_handler;
\ No newline at end of file
+pub mod auth_handler;
+pub mod two_factor_handler;
+pub mod bid_handlers;
+pub mod penalty_handlers;
+pub mod ledger_handlers;
+pub mod payment_handlers;
+pub mod reconciliation_handlers;
\ No newline at end of file
@@ -0,0 +1,111 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use crate::model::payment_transactions::{PaymentProviderKind, PaymentTransaction};
+use crate::repositories::payment_transaction_repository::PaymentTransactionRepository;
+use crate::payments::PaymentProviders;
+use crate::payments::service::PaymentService;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::errors::AppError;
+
+pub struct PaymentHandler;
+
+impl PaymentHandler {
+    pub async fn get_payment(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        payment_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let payment = PaymentTransactionRepository::find_by_id(&pool, payment_id.into_inner()).await?;
+        Self::authorize(&pool, user.id, &payment, Permission::ViewAllRecords).await?;
+
+        Ok(HttpResponse::Ok().json(payment))
+    }
+
+    pub async fn get_contribution_payments(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        contribution_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let contribution_id = contribution_id.into_inner();
+        let (tontine_id, member_user_id) = AccessControl::contribution_context(&pool, contribution_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &[member_user_id], Permission::ViewAllRecords).await?;
+
+        let payments = PaymentTransactionRepository::find_by_contribution(&pool, contribution_id).await?;
+        Ok(HttpResponse::Ok().json(payments))
+    }
+
+    /// Interroge l'opérateur si le paiement est encore en attente
+    pub async fn refresh_payment(
+        pool: web::Data<sqlx::PgPool>,
+        providers: web::Data<PaymentProviders>,
+        user: AuthenticatedUser,
+        payment_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let payment = PaymentTransactionRepository::find_by_id(&pool, payment_id.into_inner()).await?;
+        // Réservé à la collecte : un membre ne déclenche pas lui-même le règlement de sa cotisation
+        let (tontine_id, permission) = match (payment.contribution_id, payment.tontine_round_id) {
+            (Some(contribution_id), _) => {
+                (AccessControl::contribution_context(&pool, contribution_id).await?.0, Permission::RecordContributions)
+            }
+            (None, Some(round_id)) => (AccessControl::tontine_id_of_round(&pool, round_id).await?, Permission::IssuePayouts),
+            (None, None) => return Err(AppError::NotFound("Paiement sans cotisation ni tour".to_string())),
+        };
+        AccessControl::require(&pool, tontine_id, user.id, permission).await?;
+
+        let payment = PaymentService::refresh(&pool, &providers, payment.id).await?;
+        if payment.contribution_id.is_some() {
+            let result = PaymentService::with_contribution(&pool, payment).await?;
+            return Ok(HttpResponse::Ok().json(result));
+        }
+        Ok(HttpResponse::Ok().json(payment))
+    }
+
+    /// Notification d'un opérateur (sans jeton) : le corps doit être signé avec la clé partagée.
+    /// Une notification répétée est acceptée sans nouvel effet.
+    pub async fn callback(
+        pool: web::Data<sqlx::PgPool>,
+        providers: web::Data<PaymentProviders>,
+        provider: web::Path<String>,
+        req: HttpRequest,
+        body: web::Bytes,
+    ) -> Result<HttpResponse, AppError> {
+        let kind = PaymentProviderKind::from_slug(&provider)
+            .ok_or_else(|| AppError::NotFound(format!("Opérateur de paiement inconnu : {}", provider)))?;
+
+        let signature = req.headers().get("X-Signature").and_then(|value| value.to_str().ok());
+        providers.verify_signature(&body, signature)?;
+
+        let payload: serde_json::Value = serde_json::from_slice(&body)
+            .map_err(|e| AppError::ValidationError(format!("Notification illisible : {}", e)))?;
+        let event = providers.get(kind)?.handle_callback(&payload)?;
+
+        let result = PaymentService::handle_callback(&pool, kind, event).await?;
+        Ok(HttpResponse::Ok().json(serde_json::json!({
+            "payment_reference": result.payment_reference,
+            "status": result.status,
+        })))
+    }
+
+    /// Le paiement d'une cotisation est visible par son membre ou avec `permission`,
+    /// le versement d'un tour avec `permission` seulement
+    async fn authorize(
+        pool: &sqlx::PgPool,
+        user_id: Uuid,
+        payment: &PaymentTransaction,
+        permission: Permission,
+    ) -> Result<(), AppError> {
+        if let Some(contribution_id) = payment.contribution_id {
+            let (tontine_id, member_user_id) = AccessControl::contribution_context(pool, contribution_id).await?;
+            AccessControl::require_own_or(pool, tontine_id, user_id, &[member_user_id], permission).await?;
+            return Ok(());
+        }
+
+        let round_id = payment.tontine_round_id
+            .ok_or_else(|| AppError::NotFound("Paiement sans cotisation ni tour".to_string()))?;
+        let tontine_id = AccessControl::tontine_id_of_round(pool, round_id).await?;
+        AccessControl::require(pool, tontine_id, user_id, permission).await?;
+        Ok(())
+    }
+}
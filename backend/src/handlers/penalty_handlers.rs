@@ -0,0 +1,107 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+use serde_json::json;
+
+use crate::model::penalties::{PenaltyStatus, SetPenaltyRule};
+use crate::repositories::penalty_repository::PenaltyRepository;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::errors::AppError;
+
+pub struct PenaltyHandler;
+
+impl PenaltyHandler {
+    pub async fn get_rule(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let rule = PenaltyRepository::find_rule(&pool, tontine_id).await?
+            .ok_or_else(|| AppError::NotFound("Aucune règle d'amende pour cette tontine".to_string()))?;
+        Ok(HttpResponse::Ok().json(rule))
+    }
+
+    pub async fn set_rule(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+        rule_data: web::Json<SetPenaltyRule>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let rule = PenaltyRepository::set_rule(&pool, tontine_id, &rule_data).await?;
+        Ok(HttpResponse::Ok().json(rule))
+    }
+
+    pub async fn delete_rule(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        PenaltyRepository::delete_rule(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Règle d'amende supprimée"
+        })))
+    }
+
+    pub async fn get_tontine_penalties(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let penalties = PenaltyRepository::find_by_tontine(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(penalties))
+    }
+
+    pub async fn get_member_penalties(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        member_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let member_id = member_id.into_inner();
+        let (tontine_id, member_user_id) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &[member_user_id], Permission::ViewAllRecords).await?;
+
+        let penalties = PenaltyRepository::find_by_member(&pool, member_id).await?;
+        Ok(HttpResponse::Ok().json(penalties))
+    }
+
+    pub async fn mark_as_paid(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        penalty_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        Self::settle(pool, user, penalty_id.into_inner(), PenaltyStatus::Paid).await
+    }
+
+    pub async fn waive(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        penalty_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        Self::settle(pool, user, penalty_id.into_inner(), PenaltyStatus::Waived).await
+    }
+
+    async fn settle(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        penalty_id: Uuid,
+        status: PenaltyStatus,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = PenaltyRepository::find_tontine_id(&pool, penalty_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let penalty = PenaltyRepository::settle(&pool, penalty_id, status).await?;
+        Ok(HttpResponse::Ok().json(penalty))
+    }
+}
@@ -0,0 +1,85 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use crate::model::payment_transactions::PaymentProviderKind;
+use crate::model::reconciliation::{ImportStatement, ResolveReconciliationItem, StatementFormat};
+use crate::repositories::reconciliation_repository::ReconciliationRepository;
+use crate::payments::reconciliation::ReconciliationService;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::errors::AppError;
+
+pub struct ReconciliationHandler;
+
+impl ReconciliationHandler {
+    /// Le relevé est le corps brut de la requête (`text/csv` ou `application/json`)
+    pub async fn import_statement(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+        params: web::Query<ImportStatement>,
+        req: HttpRequest,
+        body: web::Bytes,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let provider = PaymentProviderKind::from_slug(&params.provider)
+            .ok_or_else(|| AppError::ValidationError(format!("Opérateur de paiement inconnu : {}", params.provider)))?;
+        let format = params.format.unwrap_or_else(|| {
+            let content_type = req.headers()
+                .get(actix_web::http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if content_type.contains("json") { StatementFormat::Json } else { StatementFormat::Csv }
+        });
+
+        let report = ReconciliationService::import(&pool, tontine_id, provider, format, &body, user.id).await?;
+        Ok(HttpResponse::Created().json(report))
+    }
+
+    pub async fn get_runs(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let runs = ReconciliationRepository::find_runs(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(runs))
+    }
+
+    pub async fn get_report(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        run_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let run = ReconciliationRepository::find_run(&pool, run_id.into_inner()).await?;
+        AccessControl::require(&pool, run.tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let report = ReconciliationRepository::get_report(&pool, run.id).await?;
+        Ok(HttpResponse::Ok().json(report))
+    }
+
+    pub async fn resolve_item(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        item_id: web::Path<Uuid>,
+        resolution: web::Json<ResolveReconciliationItem>,
+    ) -> Result<HttpResponse, AppError> {
+        let item = ReconciliationRepository::find_item(&pool, item_id.into_inner()).await?;
+        let run = ReconciliationRepository::find_run(&pool, item.run_id).await?;
+        AccessControl::require(&pool, run.tontine_id, user.id, Permission::RecordContributions).await?;
+
+        let resolution = resolution.into_inner();
+        let item = ReconciliationRepository::resolve(
+            &pool,
+            item.id,
+            resolution.resolution,
+            resolution.note.as_deref(),
+            user.id,
+        ).await?;
+        Ok(HttpResponse::Ok().json(item))
+    }
+}
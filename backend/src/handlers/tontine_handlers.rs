@@ -1,81 +1,232 @@
 use actix_web::{web, HttpResponse};
 use uuid::Uuid;
+use chrono::Utc;
 
-
-use crate::model::tontine::{CreateTontine, UpdateTontine};
+use crate::model::tontine::{CreateTontine, UpdateTontine, StartTontine, CancelTontine};
 use crate::repositories::tontine_repository::{TontineRepository};
+use crate::repositories::tontine_round_repository::TontineRoundRepository;
+use crate::repositories::scheduled_job_repository::ScheduledJobRepository;
+use crate::repositories::payout_repository::PayoutRepository;
+use crate::repositories::beneficiary_draw_repository::BeneficiaryDrawRepository;
+use crate::ordering;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
 use crate::errors::AppError;
 
 pub struct TontineHandler;
 
 impl TontineHandler {
-    pub async fn get_tontines(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let tontines = TontineRepository::find_all(&pool).await?;
+    pub async fn get_tontines(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let tontines = TontineRepository::find_all(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(tontines))
     }
 
     pub async fn get_tontine(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let tontine = TontineRepository::find_by_id(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let tontine = TontineRepository::find_by_id(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(tontine))
     }
 
     pub async fn get_tontine_with_creator(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let tontine = TontineRepository::get_tontine_with_creator(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let tontine = TontineRepository::get_tontine_with_creator(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(tontine))
     }
 
     pub async fn create_tontine(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_data: web::Json<CreateTontine>,
-        user_id : String,
     ) -> Result<HttpResponse, AppError> {
-        let tontine = TontineRepository::create(&pool, &tontine_data.into_inner(), user_id).await?;
+        let tontine = TontineRepository::create(&pool, &tontine_data.into_inner(), user.id).await?;
         Ok(HttpResponse::Created().json(tontine))
     }
 
     pub async fn update_tontine(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
         tontine_data: web::Json<UpdateTontine>,
     ) -> Result<HttpResponse, AppError> {
-        let tontine = TontineRepository::update(&pool, tontine_id.into_inner(), &tontine_data.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let tontine = TontineRepository::update(&pool, tontine_id, &tontine_data.into_inner()).await?;
         Ok(HttpResponse::Ok().json(tontine))
     }
 
     pub async fn delete_tontine(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        TontineRepository::delete(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        TontineRepository::delete(&pool, tontine_id).await?;
         Ok(HttpResponse::NoContent().finish())
     }
 
     pub async fn get_user_tontines(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let tontines = TontineRepository::find_by_creator(&pool, user_id.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        let tontines = TontineRepository::find_by_creator(&pool, user_id).await?;
         Ok(HttpResponse::Ok().json(tontines))
     }
 
     pub async fn get_active_tontines(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
     ) -> Result<HttpResponse, AppError> {
-        let tontines = TontineRepository::get_active_tontines(&pool).await?;
+        let tontines = TontineRepository::get_active_tontines(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(tontines))
     }
 
+    /// Ouvre les inscriptions d'une tontine en brouillon
+    pub async fn open_recruitment(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let tontine = TontineRepository::open_recruitment(&pool, tontine_id, user.id).await?;
+        Ok(HttpResponse::Ok().json(tontine))
+    }
+
+    pub async fn complete_tontine(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let tontine = TontineRepository::complete(&pool, tontine_id, user.id).await?;
+        Ok(HttpResponse::Ok().json(tontine))
+    }
+
+    pub async fn cancel_tontine(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+        cancel_data: web::Json<CancelTontine>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let cancellation = TontineRepository::cancel(&pool, tontine_id, user.id, cancel_data.reason.as_deref()).await?;
+        Ok(HttpResponse::Ok().json(cancellation))
+    }
+
+    pub async fn get_status_history(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let history = TontineRepository::find_status_history(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(history))
+    }
+
+    /// Tirages au sort des bénéficiaires, avec leur graine
+    pub async fn get_draws(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let draws = BeneficiaryDrawRepository::find_by_tontine(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(draws))
+    }
+
+    /// Refait le tirage à partir de la graine publiée pour en vérifier le résultat
+    pub async fn verify_draw(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        path: web::Path<(Uuid, Uuid)>,
+    ) -> Result<HttpResponse, AppError> {
+        let (tontine_id, draw_id) = path.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let draw = BeneficiaryDrawRepository::find_by_id(&pool, tontine_id, draw_id).await?;
+        Ok(HttpResponse::Ok().json(ordering::verify(draw)))
+    }
+
+    /// Tâches planifiées de la tontine (ouverture des tours, retards de cotisation)
+    pub async fn get_scheduled_jobs(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let jobs = ScheduledJobRepository::find_by_tontine(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(jobs))
+    }
+
+    pub async fn get_payouts(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let payouts = PayoutRepository::find_by_tontine(&pool, tontine_id).await?;
+        Ok(HttpResponse::Ok().json(payouts))
+    }
+
+    /// Démarre la tontine complète en générant tous les tours du cycle
+    pub async fn start_tontine(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        tontine_id: web::Path<Uuid>,
+        start_data: web::Json<StartTontine>,
+    ) -> Result<HttpResponse, AppError> {
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let start_date = start_data.start_date.unwrap_or_else(Utc::now);
+        let rounds = TontineRoundRepository::generate_schedule(&pool, tontine_id, start_date, user.id).await?;
+        Ok(HttpResponse::Created().json(rounds))
+    }
+
     pub async fn increment_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let tontine = TontineRepository::increment_round(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageTontine).await?;
+
+        let tontine = TontineRepository::increment_round(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(tontine))
     }
-}
\ No newline at end of file
+}
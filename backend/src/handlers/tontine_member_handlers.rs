@@ -4,78 +4,119 @@ use serde_json::json;
 
 use crate::model::tontine_members::{CreateTontineMember, UpdateTontineMember};
 use crate::repositories::tontine_member_repository::TontineMemberRepository;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
 use crate::errors::AppError;
 
 pub struct TontineMemberHandler;
 
 impl TontineMemberHandler {
-    pub async fn get_members(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let members = TontineMemberRepository::find_all(&pool).await?;
+    pub async fn get_members(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let members = TontineMemberRepository::find_all(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(members))
     }
 
     pub async fn get_member(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let member = TontineMemberRepository::find_by_id(&pool, member_id.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, _) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let member = TontineMemberRepository::find_by_id(&pool, member_id).await?;
         Ok(HttpResponse::Ok().json(member))
     }
 
     pub async fn get_tontine_members(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let members = TontineMemberRepository::find_by_tontine(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let members = TontineMemberRepository::find_by_tontine(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(members))
     }
 
     pub async fn get_user_members(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let members = TontineMemberRepository::find_by_user(&pool, user_id.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        let members = TontineMemberRepository::find_by_user(&pool, user_id).await?;
         Ok(HttpResponse::Ok().json(members))
     }
 
     pub async fn create_member(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_data: web::Json<CreateTontineMember>,
     ) -> Result<HttpResponse, AppError> {
-        let member = TontineMemberRepository::create(&pool, &member_data.into_inner()).await?;
+        let member_data = member_data.into_inner();
+        AccessControl::require(&pool, member_data.tontine_id, user.id, Permission::ManageMembers).await?;
+
+        let member = TontineMemberRepository::create(&pool, &member_data).await?;
         Ok(HttpResponse::Created().json(member))
     }
 
     pub async fn update_member(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
         member_data: web::Json<UpdateTontineMember>,
     ) -> Result<HttpResponse, AppError> {
-        let member = TontineMemberRepository::update(&pool, member_id.into_inner(), &member_data.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, _) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageMembers).await?;
+
+        let member = TontineMemberRepository::update(&pool, member_id, &member_data.into_inner()).await?;
         Ok(HttpResponse::Ok().json(member))
     }
 
     pub async fn delete_member(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        TontineMemberRepository::delete(&pool, member_id.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, _) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageMembers).await?;
+
+        TontineMemberRepository::delete(&pool, member_id).await?;
         Ok(HttpResponse::NoContent().finish())
     }
 
     pub async fn deactivate_member(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         member_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let member = TontineMemberRepository::deactivate_member(&pool, member_id.into_inner()).await?;
+        let member_id = member_id.into_inner();
+        let (tontine_id, _) = AccessControl::member_context(&pool, member_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageMembers).await?;
+
+        let member = TontineMemberRepository::deactivate_member(&pool, member_id).await?;
         Ok(HttpResponse::Ok().json(member))
     }
 
     pub async fn get_member_count(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let count = TontineMemberRepository::get_tontine_member_count(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let count = TontineMemberRepository::get_tontine_member_count(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(json!({ "member_count": count })))
     }
-}
\ No newline at end of file
+}
@@ -2,99 +2,191 @@ use actix_web::{web, HttpResponse};
 use uuid::Uuid;
 use serde_json::json;
 
+use crate::model::payouts::{PayRound, PayoutMethod};
 use crate::model::tontine_rounds::{CreateTontineRound, UpdateTontineRound, RoundStatus};
 use crate::repositories::tontine_round_repository::TontineRoundRepository;
+use crate::repositories::payout_repository::PayoutRepository;
+use crate::payments::PaymentProviders;
+use crate::payments::service::PaymentService;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::auth::service::AuthService;
 use crate::errors::AppError;
 
 pub struct TontineRoundHandler;
 
 impl TontineRoundHandler {
-    pub async fn get_rounds(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let rounds = TontineRoundRepository::find_all(&pool).await?;
+    pub async fn get_rounds(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let rounds = TontineRoundRepository::find_all(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(rounds))
     }
 
     pub async fn get_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::find_by_id(&pool, round_id.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let round = TontineRoundRepository::find_by_id(&pool, round_id).await?;
         Ok(HttpResponse::Ok().json(round))
     }
 
     pub async fn get_tontine_rounds(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let rounds = TontineRoundRepository::find_by_tontine(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let rounds = TontineRoundRepository::find_by_tontine(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(rounds))
     }
 
     pub async fn get_current_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::find_current_round(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let round = TontineRoundRepository::find_current_round(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(round))
     }
 
     pub async fn create_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_data: web::Json<CreateTontineRound>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::create(&pool, &round_data.into_inner()).await?;
+        let round_data = round_data.into_inner();
+        AccessControl::require(&pool, round_data.tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let round = TontineRoundRepository::create(&pool, &round_data).await?;
         Ok(HttpResponse::Created().json(round))
     }
 
     pub async fn update_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
         round_data: web::Json<UpdateTontineRound>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::update(&pool, round_id.into_inner(), &round_data.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let round = TontineRoundRepository::update(&pool, round_id, &round_data.into_inner()).await?;
         Ok(HttpResponse::Ok().json(round))
     }
 
     pub async fn delete_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        TontineRoundRepository::delete(&pool, round_id.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        TontineRoundRepository::delete(&pool, round_id).await?;
         Ok(HttpResponse::NoContent().finish())
     }
 
-    pub async fn complete_round(
+    /// Verse le pot du tour à son bénéficiaire : clôture du tour, transaction de versement
+    /// et avancement de la tontine dans une même transaction. En mobile money, le tour reste
+    /// `disbursing` jusqu'à la confirmation de l'opérateur (202).
+    pub async fn pay_round(
         pool: web::Data<sqlx::PgPool>,
+        providers: web::Data<PaymentProviders>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
+        options: web::Json<PayRound>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::complete_round(&pool, round_id.into_inner()).await?;
-        Ok(HttpResponse::Ok().json(round))
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::CompleteRounds).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::IssuePayouts).await?;
+        AuthService::require_recent_two_factor(&pool, &user).await?;
+
+        let options = options.into_inner();
+        if options.payout_method == Some(PayoutMethod::MobileMoney) {
+            let disbursement = PaymentService::disburse_round(&pool, &providers, round_id, user.id, options.provider).await?;
+            return Ok(HttpResponse::Accepted().json(disbursement));
+        }
+
+        let payout = PayoutRepository::pay_round(&pool, round_id, user.id).await?;
+        Ok(HttpResponse::Ok().json(payout))
+    }
+
+    pub async fn get_round_payout(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let payout = PayoutRepository::find_by_round(&pool, round_id).await?;
+        Ok(HttpResponse::Ok().json(payout))
+    }
+
+    /// Tire au sort le bénéficiaire du tour (stratégie `per_round_draw`)
+    pub async fn draw_beneficiary(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        round_id: web::Path<Uuid>,
+    ) -> Result<HttpResponse, AppError> {
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ManageRounds).await?;
+
+        let draw = TontineRoundRepository::draw_round_beneficiary(&pool, round_id, user.id).await?;
+        Ok(HttpResponse::Ok().json(draw))
     }
 
     pub async fn cancel_round(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         round_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let round = TontineRoundRepository::cancel_round(&pool, round_id.into_inner()).await?;
+        let round_id = round_id.into_inner();
+        let tontine_id = AccessControl::tontine_id_of_round(&pool, round_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::CompleteRounds).await?;
+
+        let round = TontineRoundRepository::cancel_round(&pool, round_id).await?;
         Ok(HttpResponse::Ok().json(round))
     }
 
     pub async fn get_rounds_by_status(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         status: web::Path<String>,
     ) -> Result<HttpResponse, AppError> {
         let round_status = RoundStatus::try_from(status.into_inner())
-            .map_err(|e| AppError::ValidationError(e))?;
-            
-        let rounds = TontineRoundRepository::get_rounds_by_status(&pool, round_status).await?;
+            .map_err(AppError::ValidationError)?;
+
+        let rounds = TontineRoundRepository::get_rounds_by_status(&pool, round_status, user.id).await?;
         Ok(HttpResponse::Ok().json(rounds))
     }
 
     pub async fn get_next_round_number(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let next_round = TontineRoundRepository::get_next_round_number(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewTontine).await?;
+
+        let next_round = TontineRoundRepository::get_next_round_number(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(json!({ "next_round_number": next_round })))
     }
-}
\ No newline at end of file
+}
@@ -3,69 +3,90 @@ use uuid::Uuid;
 
 
 use crate::model::transactions::{CreateTransaction, TransactionType, TransactionStatus};
-use crate::repositories::transaction_repository::{TransactionRepository, TontineFinancialSummary, UserFinancialSummary};
+use crate::repositories::transaction_repository::TransactionRepository;
+use crate::auth::access::{AccessControl, Permission};
+use crate::auth::extractor::AuthenticatedUser;
+use crate::auth::service::AuthService;
 use crate::errors::AppError;
 
 pub struct TransactionHandler;
 
 impl TransactionHandler {
-    pub async fn get_transactions(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let transactions = TransactionRepository::find_all(&pool).await?;
+    pub async fn get_transactions(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let transactions = TransactionRepository::find_all(&pool, user.id).await?;
         Ok(HttpResponse::Ok().json(transactions))
     }
 
     pub async fn get_transaction(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         transaction_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let transaction = TransactionRepository::find_by_id(&pool, transaction_id.into_inner()).await?;
+        let transaction_id = transaction_id.into_inner();
+        let (tontine_id, parties) = AccessControl::transaction_context(&pool, transaction_id).await?;
+        AccessControl::require_own_or(&pool, tontine_id, user.id, &parties, Permission::ViewAllRecords).await?;
+
+        let transaction = TransactionRepository::find_by_id(&pool, transaction_id).await?;
         Ok(HttpResponse::Ok().json(transaction))
     }
 
     pub async fn get_tontine_transactions(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let transactions = TransactionRepository::find_by_tontine(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewAllRecords).await?;
+
+        let transactions = TransactionRepository::find_by_tontine(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(transactions))
     }
 
     pub async fn get_user_transactions(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let transactions = TransactionRepository::find_by_user(&pool, user_id.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        let transactions = TransactionRepository::find_by_user(&pool, user_id).await?;
         Ok(HttpResponse::Ok().json(transactions))
     }
 
     pub async fn create_transaction(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         transaction_data: web::Json<CreateTransaction>,
     ) -> Result<HttpResponse, AppError> {
-        let transaction = TransactionRepository::create(&pool, &transaction_data.into_inner()).await?;
-        Ok(HttpResponse::Created().json(transaction))
-    }
-
-    pub async fn create_contribution_transaction(
-        pool: web::Data<sqlx::PgPool>,
-        transaction_data: web::Json<CreateContributionTransaction>,
-    ) -> Result<HttpResponse, AppError> {
-        let data = transaction_data.into_inner();
-        let transaction = TransactionRepository::create_contribution_transaction(
-            &pool, 
-            data.tontine_id, 
-            data.from_user_id, 
-            data.amount, 
-            data.description
-        ).await?;
+        let transaction_data = transaction_data.into_inner();
+        AccessControl::require(&pool, transaction_data.tontine_id, user.id, Permission::IssuePayouts).await?;
+        // Une cotisation n'entre dans la caisse qu'avec sa contribution, confirmée par un trésorier
+        if matches!(transaction_data.transaction_type, TransactionType::Contribution) {
+            return Err(AppError::ValidationError(
+                "Les cotisations s'enregistrent via /api/contributions".to_string()
+            ));
+        }
+        // Toutes les transactions restantes (versement, remboursement, remise et dividende d'enchère)
+        // font sortir ou déplacent l'argent de la caisse
+        AuthService::require_recent_two_factor(&pool, &user).await?;
+
+        let transaction = TransactionRepository::create(&pool, &transaction_data).await?;
         Ok(HttpResponse::Created().json(transaction))
     }
 
     pub async fn create_payout_transaction(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         transaction_data: web::Json<CreatePayoutTransaction>,
     ) -> Result<HttpResponse, AppError> {
         let data = transaction_data.into_inner();
+        AccessControl::require(&pool, data.tontine_id, user.id, Permission::IssuePayouts).await?;
+        AuthService::require_recent_two_factor(&pool, &user).await?;
+
         let transaction = TransactionRepository::create_payout_transaction(
             &pool, 
             data.tontine_id, 
@@ -78,12 +99,15 @@ impl TransactionHandler {
 
     pub async fn update_transaction_status(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         path: web::Path<(Uuid, String)>,
     ) -> Result<HttpResponse, AppError> {
         let (transaction_id, status_str) = path.into_inner();
-        
+        let (tontine_id, _) = AccessControl::transaction_context(&pool, transaction_id).await?;
+        AccessControl::require(&pool, tontine_id, user.id, Permission::IssuePayouts).await?;
+
         let status = TransactionStatus::try_from(status_str)
-            .map_err(|e| AppError::ValidationError(e))?;
+            .map_err(AppError::ValidationError)?;
 
         let transaction = TransactionRepository::update_status(&pool, transaction_id, status).await?;
         Ok(HttpResponse::Ok().json(transaction))
@@ -91,40 +115,41 @@ impl TransactionHandler {
 
     pub async fn get_transactions_by_type(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         transaction_type: web::Path<String>,
     ) -> Result<HttpResponse, AppError> {
         let t_type = TransactionType::try_from(transaction_type.into_inner())
-            .map_err(|e| AppError::ValidationError(e))?;
+            .map_err(AppError::ValidationError)?;
 
-        let transactions = TransactionRepository::get_transactions_by_type(&pool, t_type).await?;
+        let transactions = TransactionRepository::get_transactions_by_type(&pool, t_type, user.id).await?;
         Ok(HttpResponse::Ok().json(transactions))
     }
 
     pub async fn get_tontine_financial_summary(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         tontine_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let summary = TransactionRepository::get_tontine_financial_summary(&pool, tontine_id.into_inner()).await?;
+        let tontine_id = tontine_id.into_inner();
+        AccessControl::require(&pool, tontine_id, user.id, Permission::ViewSummaries).await?;
+
+        let summary = TransactionRepository::get_tontine_financial_summary(&pool, tontine_id).await?;
         Ok(HttpResponse::Ok().json(summary))
     }
 
     pub async fn get_user_financial_summary(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let summary = TransactionRepository::get_user_financial_summary(&pool, user_id.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        let summary = TransactionRepository::get_user_financial_summary(&pool, user_id).await?;
         Ok(HttpResponse::Ok().json(summary))
     }
 }
 
-#[derive(serde::Deserialize)]
-pub struct CreateContributionTransaction {
-    pub tontine_id: Uuid,
-    pub from_user_id: Uuid,
-    pub amount: rust_decimal::Decimal,
-    pub description: Option<String>,
-}
-
 #[derive(serde::Deserialize)]
 pub struct CreatePayoutTransaction {
     pub tontine_id: Uuid,
@@ -0,0 +1,167 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+use chrono::Utc;
+
+use crate::auth::models::{TwoFactorCodeRequest, TwoFactorLoginRequest, TwoFactorSetupResponse};
+use crate::auth::service::AuthService;
+use crate::auth::rate_limit::client_ip;
+use crate::auth::extractor::AuthenticatedUser;
+use crate::auth::totp;
+use crate::model::two_factor::TwoFactorSettings;
+use crate::handlers::auth_handler::device_info;
+use crate::repositories::user_repository::UserRepository;
+use crate::repositories::two_factor_repository::TwoFactorRepository;
+use crate::repositories::session_repository::SessionRepository;
+use crate::repositories::login_attempt_repository::LoginAttemptRepository;
+use crate::errors::AppError;
+
+/// Nombre de codes erronés tolérés sur un défi de connexion
+const CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+
+pub struct TwoFactorHandler;
+
+impl TwoFactorHandler {
+    /// Génère un secret TOTP en attente ; la 2FA ne sera active qu'après `enable`
+    pub async fn setup(
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+    ) -> Result<HttpResponse, AppError> {
+        let secret = totp::generate_secret();
+        TwoFactorRepository::set_pending_secret(&pool, user.id, &secret).await?;
+
+        Ok(HttpResponse::Ok().json(TwoFactorSetupResponse {
+            provisioning_uri: totp::provisioning_uri(&secret, &user.email),
+            secret,
+        }))
+    }
+
+    /// Confirme l'enrôlement avec un premier code et renvoie les codes de secours (affichés une seule fois)
+    pub async fn enable(
+        req: HttpRequest,
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        code_data: web::Json<TwoFactorCodeRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let ip = client_ip(&req);
+        AuthService::ensure_not_locked(&pool, user.id, &ip).await?;
+
+        let settings = TwoFactorRepository::find_settings(&pool, user.id).await?;
+        if settings.is_enabled() {
+            return Err(AppError::ValidationError("La double authentification est déjà activée".to_string()));
+        }
+
+        let secret = settings.totp_secret
+            .ok_or_else(|| AppError::ValidationError("Aucun enrôlement en cours, appelez d'abord /api/auth/2fa/setup".to_string()))?;
+
+        let Some(step) = totp::verify(&secret, &code_data.code, Utc::now().timestamp()) else {
+            LoginAttemptRepository::record_failure(&pool, Some(user.id), &user.email, "totp", "invalid_code", &device_info(&req), &ip).await?;
+            return Err(AppError::ValidationError("Code de vérification incorrect".to_string()));
+        };
+        TwoFactorRepository::record_totp_step(&pool, user.id, step).await?;
+        LoginAttemptRepository::reset(&pool, user.id, &ip).await?;
+
+        let backup_codes = AuthService::generate_backup_codes();
+        let backup_hashes: Vec<String> = backup_codes.iter()
+            .map(|code| AuthService::hash_token(&AuthService::normalize_backup_code(code)))
+            .collect();
+        TwoFactorRepository::enable(&pool, user.id, &backup_hashes).await?;
+        SessionRepository::mark_two_factor_verified(&pool, user.session_id).await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Double authentification activée",
+            "backup_codes": backup_codes
+        })))
+    }
+
+    pub async fn disable(
+        req: HttpRequest,
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        code_data: web::Json<TwoFactorCodeRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let settings = Self::enabled_settings(&pool, &user).await?;
+        Self::check_code(&req, &pool, &user, &settings, &code_data.code).await?;
+
+        TwoFactorRepository::disable(&pool, user.id).await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Double authentification désactivée"
+        })))
+    }
+
+    /// Nouvelle vérification du second facteur pour la session courante (avant une action sensible)
+    pub async fn verify(
+        req: HttpRequest,
+        pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
+        code_data: web::Json<TwoFactorCodeRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let settings = Self::enabled_settings(&pool, &user).await?;
+        Self::check_code(&req, &pool, &user, &settings, &code_data.code).await?;
+
+        SessionRepository::mark_two_factor_verified(&pool, user.session_id).await?;
+
+        Ok(HttpResponse::Ok().json(json!({
+            "message": "Double authentification vérifiée pour cette session"
+        })))
+    }
+
+    /// Seconde étape de la connexion : échange le défi et un code contre une session
+    pub async fn login(
+        req: HttpRequest,
+        pool: web::Data<sqlx::PgPool>,
+        login_data: web::Json<TwoFactorLoginRequest>,
+    ) -> Result<HttpResponse, AppError> {
+        let device = device_info(&req);
+        let ip = client_ip(&req);
+        let challenge = TwoFactorRepository::find_pending_challenge(&pool, &AuthService::hash_token(&login_data.challenge_token)).await?;
+
+        let user = UserRepository::find_by_id_with_password(&pool, challenge.user_id).await?;
+        AuthService::ensure_not_locked(&pool, user.id, &ip).await?;
+        AuthService::ensure_can_login(&pool, &user).await?;
+
+        let settings = TwoFactorRepository::find_settings(&pool, user.id).await?;
+        if !AuthService::verify_second_factor(&pool, user.id, &settings, &login_data.code).await? {
+            TwoFactorRepository::record_challenge_failure(&pool, challenge.id, CHALLENGE_MAX_ATTEMPTS).await?;
+            LoginAttemptRepository::record_failure(&pool, Some(user.id), &user.email, "totp", "invalid_code", &device, &ip).await?;
+            return Err(AppError::AuthenticationError("Code de vérification incorrect".to_string()));
+        }
+
+        TwoFactorRepository::consume_challenge(&pool, challenge.id).await?;
+        LoginAttemptRepository::reset(&pool, user.id, &ip).await?;
+
+        let auth_response = AuthService::open_session(&pool, user, &device, true).await?;
+        Ok(HttpResponse::Ok().json(auth_response))
+    }
+
+    /// Vérifie le second facteur d'un utilisateur connecté avec le même verrouillage progressif
+    /// que la connexion, pour qu'un jeton volé ne permette pas de deviner les codes
+    async fn check_code(
+        req: &HttpRequest,
+        pool: &sqlx::PgPool,
+        user: &AuthenticatedUser,
+        settings: &TwoFactorSettings,
+        code: &str,
+    ) -> Result<(), AppError> {
+        let ip = client_ip(req);
+        AuthService::ensure_not_locked(pool, user.id, &ip).await?;
+
+        if !AuthService::verify_second_factor(pool, user.id, settings, code).await? {
+            LoginAttemptRepository::record_failure(pool, Some(user.id), &user.email, "totp", "invalid_code", &device_info(req), &ip).await?;
+            return Err(AppError::AuthenticationError("Code de vérification incorrect".to_string()));
+        }
+
+        LoginAttemptRepository::reset(pool, user.id, &ip).await
+    }
+
+    async fn enabled_settings(
+        pool: &sqlx::PgPool,
+        user: &AuthenticatedUser,
+    ) -> Result<TwoFactorSettings, AppError> {
+        let settings = TwoFactorRepository::find_settings(pool, user.id).await?;
+        if !settings.is_enabled() {
+            return Err(AppError::ValidationError("La double authentification n'est pas activée".to_string()));
+        }
+        Ok(settings)
+    }
+}
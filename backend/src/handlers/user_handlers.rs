@@ -2,57 +2,76 @@ use actix_web::{web, HttpResponse};
 use uuid::Uuid;
 use serde_json::json;
 
-use crate::model::users::{CreateUser, UpdateUser};
+use crate::model::users::{UpdateUser, VerificationStatus};
 use crate::repositories::user_repository::UserRepository;
+use crate::auth::access::AccessControl;
+use crate::auth::extractor::AuthenticatedUser;
+use crate::handlers::auth_handler::send_verification_codes;
+use crate::notifications::Notifier;
 use crate::errors::AppError;
 
 pub struct UserHandler;
 
 impl UserHandler {
-    pub async fn get_users(pool: web::Data<sqlx::PgPool>) -> Result<HttpResponse, AppError> {
-        let users = UserRepository::find_all(&pool).await?;
-        Ok(HttpResponse::Ok().json(users))
-    }
-
     pub async fn get_user(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        let user = UserRepository::find_by_id(&pool, user_id.into_inner()).await?;
-        Ok(HttpResponse::Ok().json(user))
-    }
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_can_view_user(&pool, user.id, user_id).await?;
 
-    pub async fn create_user(
-        pool: web::Data<sqlx::PgPool>,
-        user_data: web::Json<CreateUser>,
-    ) -> Result<HttpResponse, AppError> {
-        let user = UserRepository::create(&pool, &user_data.into_inner()).await?;
-        Ok(HttpResponse::Created().json(user))
+        let user = UserRepository::find_by_id(&pool, user_id).await?;
+        Ok(HttpResponse::Ok().json(user))
     }
 
     pub async fn update_user(
         pool: web::Data<sqlx::PgPool>,
+        notifier: web::Data<dyn Notifier>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
         user_data: web::Json<UpdateUser>,
     ) -> Result<HttpResponse, AppError> {
-        let user = UserRepository::update(&pool, user_id.into_inner(), &user_data.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        let previous = UserRepository::find_by_id(&pool, user_id).await?;
+        let user = UserRepository::update(&pool, user_id, &user_data.into_inner()).await?;
+
+        // Un code est envoyé au nouvel email ou au nouveau numéro, à vérifier avant la prochaine connexion
+        let status = VerificationStatus {
+            email_verified: user.email == previous.email,
+            phone_verified: user.phone == previous.phone,
+        };
+        if !status.is_complete() {
+            send_verification_codes(&pool, notifier.get_ref(), &user, status).await?;
+        }
+
         Ok(HttpResponse::Ok().json(user))
     }
 
     pub async fn delete_user(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
     ) -> Result<HttpResponse, AppError> {
-        UserRepository::delete(&pool, user_id.into_inner()).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        UserRepository::delete(&pool, user_id).await?;
         Ok(HttpResponse::NoContent().finish())
     }
 
     pub async fn change_password(
         pool: web::Data<sqlx::PgPool>,
+        user: AuthenticatedUser,
         user_id: web::Path<Uuid>,
         password_data: web::Json<ChangePasswordRequest>,
     ) -> Result<HttpResponse, AppError> {
-        UserRepository::change_password(&pool, user_id.into_inner(), &password_data.new_password).await?;
+        let user_id = user_id.into_inner();
+        AccessControl::ensure_same_user(user.id, user_id)?;
+
+        UserRepository::change_password(&pool, user_id, &password_data.new_password, Some(user.session_id)).await?;
         Ok(HttpResponse::Ok().json(json!({"message": "Mot de passe modifié avec succès"})))
     }
 }
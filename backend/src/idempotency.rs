@@ -0,0 +1,158 @@
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    web, Error, HttpMessage, HttpResponse,
+};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::models::Claims;
+use crate::errors::AppError;
+use crate::model::idempotency_keys::{IdempotencyKey, IdempotencyKeyStatus, IdempotencyReservation};
+use crate::repositories::idempotency_key_repository::IdempotencyKeyRepository;
+use crate::settings::IdempotencySettings;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// Présent sur une réponse rejouée depuis le stockage
+const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+const MAX_KEY_LENGTH: usize = 255;
+/// Une clé en cours depuis plus longtemps est considérée comme abandonnée (arrêt du serveur)
+const STALE_PROCESSING_MINUTES: i64 = 5;
+
+/// Middleware des POST qui déplacent de l'argent, placé derrière l'authentification :
+/// avec un header `Idempotency-Key`, la réponse est conservée par clé et par utilisateur
+/// et rejouée si la requête est renvoyée. Une clé réutilisée pour une autre requête est refusée.
+/// Les erreurs serveur ne sont pas conservées, pour que la requête puisse être retentée.
+pub async fn idempotency(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(key) = idempotency_key(&req)? else {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    };
+
+    // Sans utilisateur, base ou réglages, la clé ne pourrait pas être honorée : refuser plutôt
+    // que de traiter la requête sans protection contre les doublons
+    let user_id = req.extensions().get::<Claims>().map(|claims| claims.sub);
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+    let settings = req.app_data::<web::Data<IdempotencySettings>>().cloned();
+    let (Some(user_id), Some(pool), Some(settings)) = (user_id, pool, settings) else {
+        return Err(AppError::InternalServerError(
+            "Middleware d'idempotence mal configuré (authentification, base ou réglages absents)".to_string()
+        ).into());
+    };
+
+    // Lire le corps pour l'empreinte, puis le remettre en place pour le handler
+    let body = req.extract::<web::Bytes>().await?;
+    let uri = req.uri().path_and_query().map_or_else(|| req.path().to_string(), |uri| uri.as_str().to_string());
+    let request_hash = request_hash(req.method(), &uri, &body);
+    req.set_payload(body.into());
+
+    let now = Utc::now();
+    let reservation = IdempotencyKeyRepository::reserve(
+        &pool,
+        user_id,
+        &key,
+        &request_hash,
+        now + Duration::hours(settings.retention_hours),
+        now - Duration::minutes(STALE_PROCESSING_MINUTES),
+    ).await?;
+
+    let id = match reservation {
+        IdempotencyReservation::Reserved(id) => id,
+        IdempotencyReservation::Existing(existing) => return replay(req, existing, &request_hash),
+    };
+
+    let res = match next.call(req).await {
+        Ok(res) => res,
+        Err(e) => {
+            release(&pool, id).await;
+            return Err(e);
+        }
+    };
+
+    if res.status().is_server_error() {
+        release(&pool, id).await;
+        return Ok(res.map_into_boxed_body());
+    }
+
+    let (http_req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let body = body::to_bytes(body).await.map_err(|e| {
+        let e: Box<dyn std::error::Error> = e.into();
+        AppError::InternalServerError(format!("Lecture de la réponse impossible : {}", e))
+    })?;
+
+    let content_type = res.headers().get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    if let Err(e) = IdempotencyKeyRepository::complete(&pool, id, res.status().as_u16(), content_type, &body).await {
+        eprintln!("[idempotency] Réponse de la clé {} non enregistrée : {}", id, e);
+    }
+
+    Ok(ServiceResponse::new(http_req, res.set_body(body).map_into_boxed_body()))
+}
+
+fn idempotency_key(req: &ServiceRequest) -> Result<Option<String>, AppError> {
+    if req.method() != Method::POST {
+        return Ok(None);
+    }
+    let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    value.to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+        .map(|key| Some(key.to_string()))
+        .ok_or_else(|| AppError::ValidationError(format!(
+            "{} doit être une chaîne de 1 à {} caractères", IDEMPOTENCY_KEY_HEADER, MAX_KEY_LENGTH
+        )))
+}
+
+/// Empreinte SHA-256 de la méthode, du chemin avec sa query string et du corps
+fn request_hash(method: &Method, uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b" ");
+    hasher.update(uri.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn replay(req: ServiceRequest, existing: IdempotencyKey, request_hash: &str) -> Result<ServiceResponse<BoxBody>, Error> {
+    if existing.request_hash != request_hash {
+        return Err(AppError::ValidationError(format!(
+            "Cette {} a déjà été utilisée pour une autre requête", IDEMPOTENCY_KEY_HEADER
+        )).into());
+    }
+    if existing.status != String::from(IdempotencyKeyStatus::Completed) {
+        return Err(AppError::Conflict(
+            "Une requête avec cette clé est en cours de traitement, réessayez plus tard".to_string()
+        ).into());
+    }
+
+    let status = existing.response_status
+        .and_then(|status| u16::try_from(status).ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut response = HttpResponse::build(status);
+    response.insert_header((REPLAYED_HEADER, "true"));
+    if let Some(content_type) = existing.response_content_type {
+        response.insert_header((header::CONTENT_TYPE, content_type));
+    }
+    let response = response.body(existing.response_body.unwrap_or_default());
+
+    Ok(ServiceResponse::new(req.into_parts().0, response))
+}
+
+async fn release(pool: &PgPool, id: Uuid) {
+    if let Err(e) = IdempotencyKeyRepository::release(pool, id).await {
+        eprintln!("[idempotency] Clé {} non libérée : {}", id, e);
+    }
+}
@@ -1,9 +1,7 @@
 use actix_cors::Cors;
 use actix_web::{App, HttpServer, web, middleware};
-use actix_files::Files;
 use sqlx::PgPool;
-use dotenvy::dotenv;
-use std::env;
+use sqlx::postgres::PgPoolOptions;
 
 pub mod model;
 pub mod errors;
@@ -11,33 +9,63 @@ pub mod handlers;
 pub mod repositories;
 pub mod routes;
 pub mod auth;
+pub mod notifications;
+pub mod settings;
+pub mod scheduler;
+pub mod ordering;
+pub mod validation;
+pub mod payments;
+pub mod idempotency;
 
+use settings::{DatabaseSettings, Settings};
 
-async fn create_pool() -> PgPool {
-    dotenv().ok();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    PgPool::connect(&database_url).await.expect("Failed to connect to DB")
+
+async fn create_pool(settings: &DatabaseSettings) -> std::io::Result<PgPool> {
+    PgPoolOptions::new()
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .connect(&settings.url)
+        .await
+        .map_err(|e| std::io::Error::other(format!("Connexion à la base de données impossible : {}", e)))
 }
 
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let pool = web::Data::new(create_pool().await);
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    env_logger::Builder::new().parse_filters(&settings.log_level).init();
+    auth::config::init(&settings.jwt);
+
+    let pool = web::Data::new(create_pool(&settings.database).await?);
+    let notifier: web::Data<dyn notifications::Notifier> = web::Data::from(notifications::notifier_from_settings(&settings.notifier));
+    let rate_limiter = web::Data::new(auth::rate_limit::RateLimiter::from_settings(&settings.rate_limit));
+    let payment_providers = web::Data::new(payments::PaymentProviders::from_settings(&settings.payments));
+    let idempotency_settings = web::Data::new(settings.idempotency.clone());
+
+    scheduler::Scheduler::new(pool.get_ref().clone(), settings.scheduler.clone(), payment_providers.clone().into_inner()).spawn();
+
+    let bind_address = (settings.server.host.clone(), settings.server.port);
+    let allowed_origins = settings.allowed_origins.clone();
 
-    println!(" Backend TONTINE démarré sur http://127.0.0.1:8080");
+    println!(" Backend TONTINE démarré sur http://{}:{}", bind_address.0, bind_address.1);
     
     HttpServer::new(move || {
 
-        let cors = Cors::default()
-            .allowed_origin("http://localhost:8080")  
-            .allowed_origin("http://127.0.0.1:8080")  
-            .allowed_origin("http://localhost:3000")
-            .allowed_origin("http://127.0.0.1:3000")
+        let cors = allowed_origins.iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "PATCH"])
             .allowed_headers(vec![
                 actix_web::http::header::CONTENT_TYPE,
                 actix_web::http::header::AUTHORIZATION,
                 actix_web::http::header::ACCEPT,
+                actix_web::http::header::HeaderName::from_static("idempotency-key"),
             ])
             .supports_credentials()
             .max_age(3600);
@@ -47,9 +75,13 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             .wrap(middleware::Logger::default())
             .app_data(pool.clone())
+            .app_data(notifier.clone())
+            .app_data(payment_providers.clone())
+            .app_data(idempotency_settings.clone())
+            .app_data(rate_limiter.clone())
             .configure(routes::config)
     })
-    .bind(("127.0.0.1", 8080))?
+    .bind(bind_address)?
     .run()
     .await
 }
\ No newline at end of file
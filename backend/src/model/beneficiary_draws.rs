@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// Tirage au sort des bénéficiaires, publié avec sa graine
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BeneficiaryDraw {
+    pub id: Uuid,
+    pub tontine_id: Uuid,
+    /// Tour concerné pour un tirage par tour, `None` pour l'ordre complet tiré au démarrage
+    pub tontine_round_id: Option<Uuid>,
+    pub strategy: String,
+    pub seed: String,
+    /// Identifiants utilisateur des candidats, dans l'ordre utilisé pour le mélange
+    pub candidates: Vec<Uuid>,
+    pub result: Vec<Uuid>,
+    pub drawn_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DrawVerification {
+    pub draw: BeneficiaryDraw,
+    pub recomputed: Vec<Uuid>,
+    pub valid: bool,
+}
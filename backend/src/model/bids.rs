@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::tontine_rounds::TontineRound;
+use crate::model::transactions::Transaction;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BidWindow {
+    pub tontine_round_id: Uuid,
+    pub opened_by: Option<Uuid>,
+    pub min_bid: Decimal,
+    pub closes_at: DateTime<Utc>,
+    pub status: String,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Bid {
+    pub id: Uuid,
+    pub tontine_round_id: Uuid,
+    pub member_id: Uuid,
+    pub user_id: Uuid,
+    /// Remise proposée sur le pot du tour
+    pub amount: Decimal,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenBidding {
+    pub closes_at: DateTime<Utc>,
+    pub min_bid: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitBid {
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BidStatus {
+    Active,
+    Withdrawn,
+    Won,
+    Lost,
+}
+
+impl From<BidStatus> for String {
+    fn from(status: BidStatus) -> String {
+        match status {
+            BidStatus::Active => "active".to_string(),
+            BidStatus::Withdrawn => "withdrawn".to_string(),
+            BidStatus::Won => "won".to_string(),
+            BidStatus::Lost => "lost".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for BidStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "active" => Ok(BidStatus::Active),
+            "withdrawn" => Ok(BidStatus::Withdrawn),
+            "won" => Ok(BidStatus::Won),
+            "lost" => Ok(BidStatus::Lost),
+            _ => Err(format!("Statut d'enchère invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BiddingState {
+    pub window: Option<BidWindow>,
+    /// Toutes les offres pour les gestionnaires, seulement les siennes pour un membre
+    pub bids: Vec<Bid>,
+}
+
+/// Résultat de l'enchère : gagnant, remise et versement net attendu
+#[derive(Debug, Clone, Serialize)]
+pub struct BidResolution {
+    pub round: TontineRound,
+    pub winning_bid: Option<Bid>,
+    pub discount: Decimal,
+    pub net_payout: Decimal,
+    pub discount_transaction: Option<Transaction>,
+    pub dividends: Vec<Transaction>,
+}
@@ -5,6 +5,8 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
+use crate::model::payment_transactions::PaymentProviderKind;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Contribution {
     pub id: Uuid,
@@ -23,6 +25,10 @@ pub struct CreateContribution {
     pub member_id: Uuid,
     pub amount: Decimal,
     pub payment_method: PaymentMethod,
+    /// Mobile money : numéro débité, celui du membre par défaut
+    pub phone_number: Option<String>,
+    /// Mobile money : opérateur, celui de la configuration par défaut
+    pub provider: Option<PaymentProviderKind>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +73,8 @@ pub enum PaymentStatus {
     Paid,
     Pending,
     Failed,
+    /// Toujours impayée après le délai de grâce du tour
+    Late,
 }
 
 impl From<PaymentStatus> for String {
@@ -75,6 +83,7 @@ impl From<PaymentStatus> for String {
             PaymentStatus::Paid => "paid".to_string(),
             PaymentStatus::Pending => "pending".to_string(),
             PaymentStatus::Failed => "failed".to_string(),
+            PaymentStatus::Late => "late".to_string(),
         }
     }
 }
@@ -87,6 +96,7 @@ impl TryFrom<String> for PaymentStatus {
             "paid" => Ok(PaymentStatus::Paid),
             "pending" => Ok(PaymentStatus::Pending),
             "failed" => Ok(PaymentStatus::Failed),
+            "late" => Ok(PaymentStatus::Late),
             _ => Err(format!("Statut de paiement invalide: {}", value)),
         }
     }
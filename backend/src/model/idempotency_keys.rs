@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// Requête protégée par une clé `Idempotency-Key` et réponse à rejouer
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IdempotencyKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub status: String,
+    pub response_status: Option<i32>,
+    pub response_content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdempotencyKeyStatus {
+    /// La requête est en cours d'exécution
+    Processing,
+    /// La réponse est enregistrée et sera rejouée
+    Completed,
+}
+
+impl From<IdempotencyKeyStatus> for String {
+    fn from(status: IdempotencyKeyStatus) -> String {
+        match status {
+            IdempotencyKeyStatus::Processing => "processing".to_string(),
+            IdempotencyKeyStatus::Completed => "completed".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for IdempotencyKeyStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "processing" => Ok(IdempotencyKeyStatus::Processing),
+            "completed" => Ok(IdempotencyKeyStatus::Completed),
+            _ => Err(format!("Statut de clé d'idempotence invalide: {}", value)),
+        }
+    }
+}
+
+/// Issue de la réservation d'une clé
+#[derive(Debug, Clone)]
+pub enum IdempotencyReservation {
+    /// Clé nouvelle (ou expirée) : la requête doit être exécutée
+    Reserved(Uuid),
+    /// Clé déjà utilisée pendant la durée de conservation
+    Existing(IdempotencyKey),
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AccountKind {
+    /// Position d'un membre dans la tontine : débité de ses cotisations et amendes,
+    /// crédité de ce qu'il reçoit
+    MemberWallet,
+    /// Cotisations encaissées et non encore versées
+    TontinePot,
+    /// Remises d'enchères conservées par la tontine ou en attente de redistribution
+    TontineReserve,
+    Penalties,
+    Fees,
+}
+
+impl From<AccountKind> for String {
+    fn from(kind: AccountKind) -> String {
+        match kind {
+            AccountKind::MemberWallet => "member_wallet".to_string(),
+            AccountKind::TontinePot => "tontine_pot".to_string(),
+            AccountKind::TontineReserve => "tontine_reserve".to_string(),
+            AccountKind::Penalties => "penalties".to_string(),
+            AccountKind::Fees => "fees".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for AccountKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "member_wallet" => Ok(AccountKind::MemberWallet),
+            "tontine_pot" => Ok(AccountKind::TontinePot),
+            "tontine_reserve" => Ok(AccountKind::TontineReserve),
+            "penalties" => Ok(AccountKind::Penalties),
+            "fees" => Ok(AccountKind::Fees),
+            _ => Err(format!("Type de compte invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub tontine_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub penalty_id: Option<Uuid>,
+    /// Écriture contre-passée par celle-ci
+    pub reversal_of: Option<Uuid>,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LedgerPosting {
+    pub id: Uuid,
+    pub entry_id: Uuid,
+    pub account_id: Uuid,
+    pub account_kind: String,
+    pub user_id: Option<Uuid>,
+    /// Positif au crédit du compte, négatif au débit
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntryWithPostings {
+    #[serde(flatten)]
+    pub entry: JournalEntry,
+    pub postings: Vec<LedgerPosting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountBalance {
+    pub account_id: Uuid,
+    pub kind: String,
+    pub tontine_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UnbalancedEntry {
+    pub entry_id: Uuid,
+    pub total: Decimal,
+}
+
+/// Vérification de l'invariant : chaque écriture et le grand livre s'équilibrent
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerCheck {
+    pub tontine_id: Uuid,
+    pub entries_checked: i64,
+    pub unbalanced_entries: Vec<UnbalancedEntry>,
+    /// Somme de tous les soldes de la tontine, nulle si le grand livre est équilibré
+    pub ledger_total: Decimal,
+    pub balanced: bool,
+}
@@ -3,4 +3,17 @@ pub mod tontine_members;
 pub mod tontine_rounds;
 pub mod transactions;
 pub mod users;
-pub mod tontine;
\ No newline at end of file
+pub mod tontine;
+pub mod password_reset_tokens;
+pub mod sessions;
+pub mod otp_codes;
+pub mod two_factor;
+pub mod scheduled_jobs;
+pub mod beneficiary_draws;
+pub mod bids;
+pub mod penalties;
+pub mod payouts;
+pub mod ledger;
+pub mod payment_transactions;
+pub mod reconciliation;
+pub mod idempotency_keys;
\ No newline at end of file
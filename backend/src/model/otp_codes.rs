@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OtpCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: String,
+    pub code_hash: String,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpPurpose {
+    Login,
+    VerifyEmail,
+    VerifyPhone,
+}
+
+impl From<OtpPurpose> for String {
+    fn from(purpose: OtpPurpose) -> String {
+        match purpose {
+            OtpPurpose::Login => "login".to_string(),
+            OtpPurpose::VerifyEmail => "verify_email".to_string(),
+            OtpPurpose::VerifyPhone => "verify_phone".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for OtpPurpose {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "login" => Ok(OtpPurpose::Login),
+            "verify_email" => Ok(OtpPurpose::VerifyEmail),
+            "verify_phone" => Ok(OtpPurpose::VerifyPhone),
+            _ => Err(format!("Usage de code invalide: {}", value)),
+        }
+    }
+}
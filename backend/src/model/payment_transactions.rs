@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::contributions::Contribution;
+
+/// Paiement mobile money d'une cotisation ou versement d'un tour, suivi auprès de l'opérateur
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaymentTransaction {
+    pub id: Uuid,
+    pub contribution_id: Option<Uuid>,
+    /// Tour versé, pour un paiement sortant
+    pub tontine_round_id: Option<Uuid>,
+    /// `collection` (cotisation encaissée) ou `disbursement` (versement d'un tour)
+    pub direction: String,
+    /// Transaction `contribution` ou `payout` réglée à la confirmation du paiement
+    pub transaction_id: Option<Uuid>,
+    /// Référence générée par l'application et transmise à l'opérateur
+    pub payment_reference: String,
+    pub provider: String,
+    pub amount: Decimal,
+    pub currency: Option<String>,
+    pub status: String,
+    /// Référence attribuée par l'opérateur
+    pub provider_reference: Option<String>,
+    /// Numéro débité et dernières réponses de l'opérateur
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PaymentProviderKind {
+    MtnMobileMoney,
+    OrangeMoney,
+    /// Opérateur simulé pour le développement local
+    Mock,
+}
+
+impl PaymentProviderKind {
+    /// Identifiant court utilisé dans la configuration et les URL
+    pub fn slug(self) -> &'static str {
+        match self {
+            PaymentProviderKind::MtnMobileMoney => "mtn",
+            PaymentProviderKind::OrangeMoney => "orange",
+            PaymentProviderKind::Mock => "mock",
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        match slug {
+            "mtn" => Some(PaymentProviderKind::MtnMobileMoney),
+            "orange" => Some(PaymentProviderKind::OrangeMoney),
+            "mock" => Some(PaymentProviderKind::Mock),
+            _ => None,
+        }
+    }
+}
+
+impl From<PaymentProviderKind> for String {
+    fn from(kind: PaymentProviderKind) -> String {
+        match kind {
+            PaymentProviderKind::MtnMobileMoney => "MTNMobileMoney".to_string(),
+            PaymentProviderKind::OrangeMoney => "OrangeMoney".to_string(),
+            PaymentProviderKind::Mock => "Mock".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PaymentProviderKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "MTNMobileMoney" => Ok(PaymentProviderKind::MtnMobileMoney),
+            "OrangeMoney" => Ok(PaymentProviderKind::OrangeMoney),
+            "Mock" => Ok(PaymentProviderKind::Mock),
+            _ => Err(format!("Opérateur de paiement invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PaymentDirection {
+    Collection,
+    Disbursement,
+}
+
+impl From<PaymentDirection> for String {
+    fn from(direction: PaymentDirection) -> String {
+        match direction {
+            PaymentDirection::Collection => "collection".to_string(),
+            PaymentDirection::Disbursement => "disbursement".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PaymentDirection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "collection" => Ok(PaymentDirection::Collection),
+            "disbursement" => Ok(PaymentDirection::Disbursement),
+            _ => Err(format!("Sens de paiement invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PaymentTransactionStatus {
+    /// En attente de la validation du payeur sur son téléphone
+    Pending,
+    Successful,
+    Failed,
+}
+
+impl From<PaymentTransactionStatus> for String {
+    fn from(status: PaymentTransactionStatus) -> String {
+        match status {
+            PaymentTransactionStatus::Pending => "pending".to_string(),
+            PaymentTransactionStatus::Successful => "successful".to_string(),
+            PaymentTransactionStatus::Failed => "failed".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PaymentTransactionStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(PaymentTransactionStatus::Pending),
+            "successful" => Ok(PaymentTransactionStatus::Successful),
+            "failed" => Ok(PaymentTransactionStatus::Failed),
+            _ => Err(format!("Statut de paiement invalide: {}", value)),
+        }
+    }
+}
+
+/// Cotisation payée par mobile money et paiement correspondant
+#[derive(Debug, Clone, Serialize)]
+pub struct ContributionPayment {
+    #[serde(flatten)]
+    pub contribution: Contribution,
+    pub payment: PaymentTransaction,
+}
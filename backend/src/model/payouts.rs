@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::payment_transactions::{PaymentProviderKind, PaymentTransaction};
+use crate::model::tontine::Tontine;
+use crate::model::tontine_rounds::TontineRound;
+use crate::model::transactions::Transaction;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RoundPayout {
+    pub tontine_round_id: Uuid,
+    pub tontine_id: Uuid,
+    pub transaction_id: Uuid,
+    pub beneficiary_user_id: Uuid,
+    /// Cotisations payées au moment du versement
+    pub collected_amount: Decimal,
+    /// Remise d'enchère retenue sur le pot
+    pub discount: Decimal,
+    pub amount: Decimal,
+    /// Cotisations manquantes, non nulles uniquement avec la politique `pay_collected`
+    pub shortfall: Decimal,
+    pub paid_by: Option<Uuid>,
+    pub paid_at: DateTime<Utc>,
+    /// `pending` tant que l'opérateur n'a pas confirmé un versement mobile money
+    pub status: String,
+    pub payment_method: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutMethod {
+    /// Remis hors application, le versement est effectué immédiatement
+    Manual,
+    MobileMoney,
+}
+
+impl From<PayoutMethod> for String {
+    fn from(method: PayoutMethod) -> String {
+        match method {
+            PayoutMethod::Manual => "manual".to_string(),
+            PayoutMethod::MobileMoney => "mobile_money".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PayoutStatus {
+    Pending,
+    Completed,
+}
+
+impl From<PayoutStatus> for String {
+    fn from(status: PayoutStatus) -> String {
+        match status {
+            PayoutStatus::Pending => "pending".to_string(),
+            PayoutStatus::Completed => "completed".to_string(),
+        }
+    }
+}
+
+/// Options du versement d'un tour ; `{}` pour un versement manuel. Un champ inconnu est refusé,
+/// pour qu'une faute de frappe ne se transforme pas en versement manuel.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PayRound {
+    pub payout_method: Option<PayoutMethod>,
+    /// Mobile money : opérateur, celui de la configuration par défaut
+    pub provider: Option<PaymentProviderKind>,
+}
+
+/// Versement mobile money demandé : le tour reste `disbursing` jusqu'à la confirmation
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundDisbursement {
+    pub payout: RoundPayout,
+    pub round: TontineRound,
+    pub transaction: Transaction,
+    pub payment: PaymentTransaction,
+}
+
+/// Résultat du versement d'un tour : tour clôturé, transaction et tontine avancée
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundPayoutResult {
+    pub payout: RoundPayout,
+    pub round: TontineRound,
+    pub transaction: Transaction,
+    pub tontine: Tontine,
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PenaltyRule {
+    pub tontine_id: Uuid,
+    pub kind: String,
+    /// Montant fixe, ou pourcentage de la cotisation
+    pub amount: Decimal,
+    pub per_day: bool,
+    pub grace_days: i32,
+    pub cap: Option<Decimal>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PenaltyRule {
+    /// Jours de retard facturables : jours entamés après l'échéance, moins les jours de grâce
+    pub fn days_late(&self, due_date: DateTime<Utc>, paid_at: DateTime<Utc>) -> i64 {
+        let seconds = (paid_at - due_date).num_seconds();
+        if seconds <= 0 {
+            return 0;
+        }
+        let days = (seconds + SECONDS_PER_DAY - 1) / SECONDS_PER_DAY;
+        (days - self.grace_days as i64).max(0)
+    }
+
+    /// Amende pour une cotisation de `contribution_amount` réglée avec `days_late` jours de retard
+    pub fn fine_for(&self, contribution_amount: Decimal, days_late: i64) -> Decimal {
+        if days_late <= 0 {
+            return Decimal::ZERO;
+        }
+
+        let unit = match PenaltyKind::try_from(self.kind.clone()) {
+            Ok(PenaltyKind::Percentage) => contribution_amount * self.amount / Decimal::from(100),
+            _ => self.amount,
+        };
+        let fine = if self.per_day { unit * Decimal::from(days_late) } else { unit };
+        let fine = match self.cap {
+            Some(cap) => fine.min(cap),
+            None => fine,
+        };
+
+        fine.round_dp(2)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPenaltyRule {
+    pub kind: PenaltyKind,
+    pub amount: Decimal,
+    pub per_day: Option<bool>,
+    pub grace_days: Option<i32>,
+    pub cap: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PenaltyKind {
+    Flat,
+    Percentage,
+}
+
+impl From<PenaltyKind> for String {
+    fn from(kind: PenaltyKind) -> String {
+        match kind {
+            PenaltyKind::Flat => "flat".to_string(),
+            PenaltyKind::Percentage => "percentage".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PenaltyKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "flat" => Ok(PenaltyKind::Flat),
+            "percentage" => Ok(PenaltyKind::Percentage),
+            _ => Err(format!("Type d'amende invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Penalty {
+    pub id: Uuid,
+    pub tontine_id: Uuid,
+    pub contribution_id: Uuid,
+    pub member_id: Uuid,
+    pub amount: Decimal,
+    pub days_late: i32,
+    pub status: String,
+    pub assessed_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PenaltyStatus {
+    Unpaid,
+    Paid,
+    Waived,
+}
+
+impl From<PenaltyStatus> for String {
+    fn from(status: PenaltyStatus) -> String {
+        match status {
+            PenaltyStatus::Unpaid => "unpaid".to_string(),
+            PenaltyStatus::Paid => "paid".to_string(),
+            PenaltyStatus::Waived => "waived".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PenaltyStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "unpaid" => Ok(PenaltyStatus::Unpaid),
+            "paid" => Ok(PenaltyStatus::Paid),
+            "waived" => Ok(PenaltyStatus::Waived),
+            _ => Err(format!("Statut d'amende invalide: {}", value)),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::payment_transactions::PaymentProviderKind;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReconciliationRun {
+    pub id: Uuid,
+    pub tontine_id: Uuid,
+    pub provider: String,
+    pub source_format: String,
+    /// Période des cotisations comparées, déduite des dates du relevé
+    pub period_start: Option<DateTime<Utc>>,
+    pub period_end: Option<DateTime<Utc>>,
+    pub lines_count: i32,
+    pub imported_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReconciliationItem {
+    pub id: Uuid,
+    pub run_id: Uuid,
+    pub kind: String,
+    pub statement_reference: Option<String>,
+    pub statement_amount: Option<Decimal>,
+    pub statement_phone: Option<String>,
+    pub statement_date: Option<DateTime<Utc>>,
+    pub payment_transaction_id: Option<Uuid>,
+    pub contribution_id: Option<Uuid>,
+    pub recorded_amount: Option<Decimal>,
+    pub resolution: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReconciliationItemKind {
+    Matched,
+    /// Cotisation payée absente du relevé : l'argent n'est peut-être jamais arrivé
+    MissingInStatement,
+    /// Paiement du relevé sans cotisation correspondante
+    MissingInRecords,
+    /// Ligne répétée dans le relevé ou rapprochée d'une cotisation déjà rapprochée
+    Duplicate,
+    AmountMismatch,
+}
+
+impl From<ReconciliationItemKind> for String {
+    fn from(kind: ReconciliationItemKind) -> String {
+        match kind {
+            ReconciliationItemKind::Matched => "matched".to_string(),
+            ReconciliationItemKind::MissingInStatement => "missing_in_statement".to_string(),
+            ReconciliationItemKind::MissingInRecords => "missing_in_records".to_string(),
+            ReconciliationItemKind::Duplicate => "duplicate".to_string(),
+            ReconciliationItemKind::AmountMismatch => "amount_mismatch".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for ReconciliationItemKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "matched" => Ok(ReconciliationItemKind::Matched),
+            "missing_in_statement" => Ok(ReconciliationItemKind::MissingInStatement),
+            "missing_in_records" => Ok(ReconciliationItemKind::MissingInRecords),
+            "duplicate" => Ok(ReconciliationItemKind::Duplicate),
+            "amount_mismatch" => Ok(ReconciliationItemKind::AmountMismatch),
+            _ => Err(format!("Type d'écart invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReconciliationResolution {
+    /// Écart justifié, aucune correction
+    Accepted,
+    /// Cotisation jamais arrivée : elle passe en échec avec son paiement et sa transaction
+    ContributionFailed,
+}
+
+impl From<ReconciliationResolution> for String {
+    fn from(resolution: ReconciliationResolution) -> String {
+        match resolution {
+            ReconciliationResolution::Accepted => "accepted".to_string(),
+            ReconciliationResolution::ContributionFailed => "contribution_failed".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    Csv,
+    Json,
+}
+
+impl From<StatementFormat> for String {
+    fn from(format: StatementFormat) -> String {
+        match format {
+            StatementFormat::Csv => "csv".to_string(),
+            StatementFormat::Json => "json".to_string(),
+        }
+    }
+}
+
+/// Paramètres d'import : opérateur (`mtn`, `orange`, `mock`) et format, sinon déduit du Content-Type
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportStatement {
+    pub provider: String,
+    pub format: Option<StatementFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveReconciliationItem {
+    pub resolution: ReconciliationResolution,
+    pub note: Option<String>,
+}
+
+/// Ligne de relevé après lecture du fichier
+#[derive(Debug, Clone)]
+pub struct StatementLine {
+    pub reference: Option<String>,
+    pub amount: Decimal,
+    pub phone: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Cotisation payée susceptible de figurer dans le relevé, avec son paiement mobile money éventuel
+#[derive(Debug, Clone, FromRow)]
+pub struct RecordedPayment {
+    pub contribution_id: Uuid,
+    pub amount: Decimal,
+    pub payment_date: DateTime<Utc>,
+    pub member_phone: String,
+    pub payment_transaction_id: Option<Uuid>,
+    pub payment_reference: Option<String>,
+    pub provider_reference: Option<String>,
+}
+
+/// Import à enregistrer avec ses résultats
+#[derive(Debug, Clone)]
+pub struct NewReconciliationRun {
+    pub tontine_id: Uuid,
+    pub provider: PaymentProviderKind,
+    pub format: StatementFormat,
+    pub period: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub lines_count: usize,
+    pub imported_by: Uuid,
+}
+
+/// Résultat du rapprochement d'une ligne de relevé ou d'une cotisation, avant enregistrement
+#[derive(Debug, Clone)]
+pub struct NewReconciliationItem {
+    pub kind: ReconciliationItemKind,
+    pub line: Option<StatementLine>,
+    pub record: Option<RecordedPayment>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconciliationSummary {
+    pub matched: i64,
+    pub missing_in_statement: i64,
+    pub missing_in_records: i64,
+    pub duplicate: i64,
+    pub amount_mismatch: i64,
+    /// Écarts restant à résoudre
+    pub open: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationReport {
+    pub run: ReconciliationRun,
+    pub summary: ReconciliationSummary,
+    pub items: Vec<ReconciliationItem>,
+}
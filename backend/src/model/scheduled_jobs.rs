@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub tontine_id: Uuid,
+    pub tontine_round_id: Option<Uuid>,
+    pub run_at: DateTime<Utc>,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobType {
+    /// Le tour devient le tour courant de la tontine à sa date d'échéance
+    OpenRound,
+    /// Les cotisations encore impayées passent en retard après le délai de grâce
+    MarkLateContributions,
+    /// Envoi du versement mobile money du tour, rejoué en cas d'échec temporaire
+    DisbursePayout,
+}
+
+impl From<JobType> for String {
+    fn from(job_type: JobType) -> String {
+        match job_type {
+            JobType::OpenRound => "open_round".to_string(),
+            JobType::MarkLateContributions => "mark_late_contributions".to_string(),
+            JobType::DisbursePayout => "disburse_payout".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for JobType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "open_round" => Ok(JobType::OpenRound),
+            "mark_late_contributions" => Ok(JobType::MarkLateContributions),
+            "disburse_payout" => Ok(JobType::DisbursePayout),
+            _ => Err(format!("Type de tâche invalide: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl From<JobStatus> for String {
+    fn from(status: JobStatus) -> String {
+        match status {
+            JobStatus::Pending => "pending".to_string(),
+            JobStatus::Running => "running".to_string(),
+            JobStatus::Done => "done".to_string(),
+            JobStatus::Failed => "failed".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for JobStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(JobStatus::Pending),
+            "running" => Ok(JobStatus::Running),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(format!("Statut de tâche invalide: {}", value)),
+        }
+    }
+}
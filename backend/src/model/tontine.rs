@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Months, Utc};
 use rust_decimal::Decimal;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq, Eq)]
@@ -14,6 +14,11 @@ pub struct Tontine {
     pub max_members: i32,
     pub current_round: i32,
     pub status: String,
+    pub payout_strategy: String,
+    pub bid_discount_policy: String,
+    /// Cotisation réglable en plusieurs versements
+    pub allow_partial_payments: bool,
+    pub payout_shortfall_policy: String,
     pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -26,6 +31,14 @@ pub struct CreateTontine {
     pub amount_per_member: Decimal,
     pub frequency: TontineFrequency,
     pub max_members: i32,
+    /// Ordre fixe (`position_order`) par défaut
+    pub payout_strategy: Option<PayoutStrategy>,
+    /// Remise des enchères conservée comme intérêt par défaut
+    pub bid_discount_policy: Option<BidDiscountPolicy>,
+    /// Cotisation exigée en un seul versement par défaut
+    pub allow_partial_payments: Option<bool>,
+    /// Versement refusé tant que des cotisations manquent par défaut
+    pub payout_shortfall_policy: Option<ShortfallPolicy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,7 +48,16 @@ pub struct UpdateTontine {
     pub amount_per_member: Option<Decimal>,
     pub frequency: Option<TontineFrequency>,
     pub max_members: Option<i32>,
-    pub status: Option<TontineStatus>,
+    pub payout_strategy: Option<PayoutStrategy>,
+    pub bid_discount_policy: Option<BidDiscountPolicy>,
+    pub allow_partial_payments: Option<bool>,
+    pub payout_shortfall_policy: Option<ShortfallPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartTontine {
+    /// Date d'échéance du premier tour (maintenant par défaut)
+    pub start_date: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +67,20 @@ pub enum TontineFrequency {
     Monthly,
 }
 
+impl TontineFrequency {
+    /// Échéance du tour d'indice `index` (0 pour le premier) à partir de la date de départ
+    pub fn due_date(&self, start_date: DateTime<Utc>, index: u32) -> DateTime<Utc> {
+        match self {
+            TontineFrequency::Daily => start_date + Duration::days(index as i64),
+            TontineFrequency::Weekly => start_date + Duration::weeks(index as i64),
+            // En fin de mois, l'échéance est ramenée au dernier jour du mois (31 janvier -> 28/29 février)
+            TontineFrequency::Monthly => start_date
+                .checked_add_months(Months::new(index))
+                .unwrap_or(start_date),
+        }
+    }
+}
+
 impl From<TontineFrequency> for String {
     fn from(freq: TontineFrequency) -> String {
         match freq {
@@ -68,17 +104,140 @@ impl TryFrom<String> for TontineFrequency {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Règle de désignation des bénéficiaires, choisie à la création de la tontine
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PayoutStrategy {
+    /// Ordre des `position_order` des membres
+    Fixed,
+    /// Ordre complet tiré au sort au démarrage
+    RandomDraw,
+    /// Tirage au sort à l'ouverture de chaque tour parmi ceux qui n'ont pas encore reçu
+    PerRoundDraw,
+    /// Les membres enchérissent (remise sur le pot) pour recevoir plus tôt
+    Auction,
+}
+
+impl From<PayoutStrategy> for String {
+    fn from(strategy: PayoutStrategy) -> String {
+        match strategy {
+            PayoutStrategy::Fixed => "fixed".to_string(),
+            PayoutStrategy::RandomDraw => "random_draw".to_string(),
+            PayoutStrategy::PerRoundDraw => "per_round_draw".to_string(),
+            PayoutStrategy::Auction => "auction".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for PayoutStrategy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "fixed" => Ok(PayoutStrategy::Fixed),
+            "random_draw" => Ok(PayoutStrategy::RandomDraw),
+            "per_round_draw" => Ok(PayoutStrategy::PerRoundDraw),
+            "auction" => Ok(PayoutStrategy::Auction),
+            _ => Err(format!("Stratégie de versement invalide: {}", value)),
+        }
+    }
+}
+
+/// Sort de la remise payée par le gagnant d'une enchère
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BidDiscountPolicy {
+    /// Conservée par la tontine comme intérêt
+    KeepAsInterest,
+    /// Partagée à parts égales entre les autres membres actifs
+    Redistribute,
+}
+
+impl From<BidDiscountPolicy> for String {
+    fn from(policy: BidDiscountPolicy) -> String {
+        match policy {
+            BidDiscountPolicy::KeepAsInterest => "interest".to_string(),
+            BidDiscountPolicy::Redistribute => "redistribute".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for BidDiscountPolicy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "interest" => Ok(BidDiscountPolicy::KeepAsInterest),
+            "redistribute" => Ok(BidDiscountPolicy::Redistribute),
+            _ => Err(format!("Politique de remise invalide: {}", value)),
+        }
+    }
+}
+
+/// Versement d'un tour dont toutes les cotisations ne sont pas réglées
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ShortfallPolicy {
+    /// Versement refusé tant qu'une cotisation reste due
+    Reject,
+    /// Le bénéficiaire reçoit les cotisations effectivement payées
+    PayCollected,
+}
+
+impl From<ShortfallPolicy> for String {
+    fn from(policy: ShortfallPolicy) -> String {
+        match policy {
+            ShortfallPolicy::Reject => "reject".to_string(),
+            ShortfallPolicy::PayCollected => "pay_collected".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for ShortfallPolicy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "reject" => Ok(ShortfallPolicy::Reject),
+            "pay_collected" => Ok(ShortfallPolicy::PayCollected),
+            _ => Err(format!("Politique de versement incomplet invalide: {}", value)),
+        }
+    }
+}
+
+/// Cycle de vie d'une tontine :
+/// draft -> recruiting -> running -> completed, annulation possible avant la fin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TontineStatus {
-    Active,
+    Draft,
+    Recruiting,
+    Running,
     Completed,
     Cancelled,
 }
 
+impl TontineStatus {
+    pub fn can_transition_to(&self, next: TontineStatus) -> bool {
+        matches!(
+            (self, next),
+            (TontineStatus::Draft, TontineStatus::Recruiting)
+                | (TontineStatus::Recruiting, TontineStatus::Running)
+                | (TontineStatus::Running, TontineStatus::Completed)
+                | (TontineStatus::Draft, TontineStatus::Cancelled)
+                | (TontineStatus::Recruiting, TontineStatus::Cancelled)
+                | (TontineStatus::Running, TontineStatus::Cancelled)
+        )
+    }
+
+    /// Les paramètres financiers (montant, fréquence, nombre de membres) ne sont modifiables qu'avant le démarrage
+    pub fn allows_terms_change(&self) -> bool {
+        matches!(self, TontineStatus::Draft | TontineStatus::Recruiting)
+    }
+}
+
 impl From<TontineStatus> for String {
     fn from(status: TontineStatus) -> String {
         match status {
-            TontineStatus::Active => "active".to_string(),
+            TontineStatus::Draft => "draft".to_string(),
+            TontineStatus::Recruiting => "recruiting".to_string(),
+            TontineStatus::Running => "running".to_string(),
             TontineStatus::Completed => "completed".to_string(),
             TontineStatus::Cancelled => "cancelled".to_string(),
         }
@@ -90,10 +249,48 @@ impl TryFrom<String> for TontineStatus {
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.as_str() {
-            "active" => Ok(TontineStatus::Active),
+            "draft" => Ok(TontineStatus::Draft),
+            "recruiting" => Ok(TontineStatus::Recruiting),
+            "running" => Ok(TontineStatus::Running),
             "completed" => Ok(TontineStatus::Completed),
             "cancelled" => Ok(TontineStatus::Cancelled),
             _ => Err(format!("Statut invalide: {}", value)),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Changement de statut journalisé
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TontineStatusChange {
+    pub id: Uuid,
+    pub tontine_id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub changed_by: Option<Uuid>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelTontine {
+    pub reason: Option<String>,
+}
+
+/// Remboursement dû à un membre lors de l'annulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberRefund {
+    pub member_id: Uuid,
+    pub user_id: Uuid,
+    pub total_contributed: Decimal,
+    pub total_received: Decimal,
+    pub refund_amount: Decimal,
+    pub transaction_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TontineCancellation {
+    pub tontine: Tontine,
+    pub cancelled_rounds: u64,
+    pub refunds: Vec<MemberRefund>,
+    pub total_refunds: Decimal,
+}
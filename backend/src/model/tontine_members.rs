@@ -11,6 +11,7 @@ pub struct TontineMember {
     pub join_date: DateTime<Utc>,
     pub is_active: bool,
     pub position_order: Option<i32>,
+    pub role: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,12 +19,47 @@ pub struct CreateTontineMember {
     pub tontine_id: Uuid,
     pub user_id: Uuid,
     pub position_order: Option<i32>,
+    pub role: Option<TontineRole>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateTontineMember {
     pub is_active: Option<bool>,
     pub position_order: Option<i32>,
+    pub role: Option<TontineRole>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TontineRole {
+    Owner,
+    Treasurer,
+    Member,
+    Auditor,
+}
+
+impl From<TontineRole> for String {
+    fn from(role: TontineRole) -> String {
+        match role {
+            TontineRole::Owner => "owner".to_string(),
+            TontineRole::Treasurer => "treasurer".to_string(),
+            TontineRole::Member => "member".to_string(),
+            TontineRole::Auditor => "auditor".to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for TontineRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "owner" => Ok(TontineRole::Owner),
+            "treasurer" => Ok(TontineRole::Treasurer),
+            "member" => Ok(TontineRole::Member),
+            "auditor" => Ok(TontineRole::Auditor),
+            _ => Err(format!("Rôle invalide: {}", value)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -34,6 +70,7 @@ pub struct TontineMemberWithUser {
     pub join_date: DateTime<Utc>,
     pub is_active: bool,
     pub position_order: Option<i32>,
+    pub role: String,
     pub user_email: String,
     pub user_phone: String,
     pub user_full_name: String,
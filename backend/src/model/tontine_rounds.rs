@@ -30,20 +30,30 @@ pub struct UpdateTontineRound {
     pub beneficiary_user_id: Option<Uuid>,
     pub amount: Option<Decimal>,
     pub round_date: Option<DateTime<Utc>>,
-    pub status: Option<RoundStatus>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RoundStatus {
     Pending,
+    /// Versement mobile money envoyé, en attente de la confirmation de l'opérateur
+    Disbursing,
     Completed,
     Cancelled,
 }
 
+impl RoundStatus {
+    /// Seul un tour en attente peut être annulé : un tour versé (ou en cours de versement)
+    /// reste dans l'historique, et son bénéficiaire n'est pas tiré à nouveau
+    pub fn is_cancellable(&self) -> bool {
+        matches!(self, RoundStatus::Pending)
+    }
+}
+
 impl From<RoundStatus> for String {
     fn from(status: RoundStatus) -> String {
         match status {
             RoundStatus::Pending => "pending".to_string(),
+            RoundStatus::Disbursing => "disbursing".to_string(),
             RoundStatus::Completed => "completed".to_string(),
             RoundStatus::Cancelled => "cancelled".to_string(),
         }
@@ -56,6 +66,7 @@ impl TryFrom<String> for RoundStatus {
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.as_str() {
             "pending" => Ok(RoundStatus::Pending),
+            "disbursing" => Ok(RoundStatus::Disbursing),
             "completed" => Ok(RoundStatus::Completed),
             "cancelled" => Ok(RoundStatus::Cancelled),
             _ => Err(format!("Statut de tour invalide: {}", value)),
@@ -75,4 +86,17 @@ pub struct TontineRoundWithBeneficiary {
     pub created_at: DateTime<Utc>,
     pub beneficiary_name: Option<String>,
     pub beneficiary_email: Option<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_pending_rounds_can_be_cancelled() {
+        assert!(RoundStatus::Pending.is_cancellable());
+        assert!(!RoundStatus::Disbursing.is_cancellable());
+        assert!(!RoundStatus::Completed.is_cancellable());
+        assert!(!RoundStatus::Cancelled.is_cancellable());
+    }
+}
@@ -32,6 +32,10 @@ pub enum TransactionType {
     Contribution,
     Payout,
     Refund,
+    /// Remise payée par le gagnant d'une enchère, retenue sur son versement
+    BidDiscount,
+    /// Part de remise d'enchère reversée à un membre
+    BidDividend,
 }
 
 impl From<TransactionType> for String {
@@ -40,6 +44,8 @@ impl From<TransactionType> for String {
             TransactionType::Contribution => "contribution".to_string(),
             TransactionType::Payout => "payout".to_string(),
             TransactionType::Refund => "refund".to_string(),
+            TransactionType::BidDiscount => "bid_discount".to_string(),
+            TransactionType::BidDividend => "bid_dividend".to_string(),
         }
     }
 }
@@ -52,6 +58,8 @@ impl TryFrom<String> for TransactionType {
             "contribution" => Ok(TransactionType::Contribution),
             "payout" => Ok(TransactionType::Payout),
             "refund" => Ok(TransactionType::Refund),
+            "bid_discount" => Ok(TransactionType::BidDiscount),
+            "bid_dividend" => Ok(TransactionType::BidDividend),
             _ => Err(format!("Type de transaction invalide: {}", value)),
         }
     }
@@ -31,6 +31,19 @@ pub struct UpdateUser {
     pub is_active: Option<bool>,
 }
 
+/// État de vérification des moyens de contact d'un compte
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct VerificationStatus {
+    pub email_verified: bool,
+    pub phone_verified: bool,
+}
+
+impl VerificationStatus {
+    pub fn is_complete(&self) -> bool {
+        self.email_verified && self.phone_verified
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
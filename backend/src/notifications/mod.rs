@@ -0,0 +1,93 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Arc;
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+
+use crate::errors::AppError;
+use crate::settings::NotifierSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Email,
+    Sms,
+}
+
+impl From<Channel> for String {
+    fn from(channel: Channel) -> String {
+        match channel {
+            Channel::Email => "email".to_string(),
+            Channel::Sms => "sms".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub channel: Channel,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Canal d'envoi des messages (email, SMS) vers les utilisateurs
+pub trait Notifier: Send + Sync {
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), AppError>>;
+}
+
+/// Affiche les messages sur la sortie standard (développement local)
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            println!(
+                "[{}] à {} - {} : {}",
+                String::from(notification.channel),
+                notification.recipient,
+                notification.subject,
+                notification.body
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Ajoute les messages à la fin d'un fichier (tests locaux)
+pub struct FileNotifier {
+    pub path: String,
+}
+
+impl Notifier for FileNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}\t{}",
+                Utc::now().to_rfc3339(),
+                String::from(notification.channel),
+                notification.recipient,
+                notification.subject,
+                notification.body
+            )
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+        })
+    }
+}
+
+/// Sélectionne l'implémentation selon `NOTIFIER` (`log`, ou `file` avec `NOTIFIER_FILE`)
+pub fn notifier_from_settings(settings: &NotifierSettings) -> Arc<dyn Notifier> {
+    match settings.kind.as_str() {
+        "file" => Arc::new(FileNotifier {
+            path: settings.file.clone(),
+        }),
+        _ => Arc::new(LogNotifier),
+    }
+}
@@ -0,0 +1,210 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::model::beneficiary_draws::{BeneficiaryDraw, DrawVerification};
+use crate::model::tontine::PayoutStrategy;
+
+/// Bénéficiaires fixés au démarrage de la tontine
+pub enum InitialOrder {
+    /// Ordre imposé (positions des membres)
+    Fixed(Vec<Uuid>),
+    /// Ordre tiré au sort : le tirage est enregistré pour pouvoir être vérifié
+    Drawn(Vec<Uuid>),
+    /// Aucun bénéficiaire attribué d'avance, ils sont désignés tour par tour
+    Deferred,
+}
+
+/// Règle de désignation des bénéficiaires des tours
+pub trait BeneficiaryOrdering: Send + Sync {
+    /// `candidates` : membres actifs dans l'ordre de leurs positions
+    fn initial_order(&self, candidates: &[Uuid], seed: &str) -> InitialOrder;
+
+    /// Bénéficiaire tiré à l'ouverture d'un tour parmi ceux qui n'ont pas encore reçu,
+    /// `None` si la stratégie ne procède pas à un tirage par tour
+    fn draw_round(&self, candidates: &[Uuid], seed: &str) -> Option<Uuid>;
+}
+
+pub struct FixedOrder;
+
+impl BeneficiaryOrdering for FixedOrder {
+    fn initial_order(&self, candidates: &[Uuid], _seed: &str) -> InitialOrder {
+        InitialOrder::Fixed(candidates.to_vec())
+    }
+
+    fn draw_round(&self, _candidates: &[Uuid], _seed: &str) -> Option<Uuid> {
+        None
+    }
+}
+
+pub struct RandomDraw;
+
+impl BeneficiaryOrdering for RandomDraw {
+    fn initial_order(&self, candidates: &[Uuid], seed: &str) -> InitialOrder {
+        InitialOrder::Drawn(shuffle(seed, candidates))
+    }
+
+    fn draw_round(&self, _candidates: &[Uuid], _seed: &str) -> Option<Uuid> {
+        None
+    }
+}
+
+pub struct PerRoundDraw;
+
+impl BeneficiaryOrdering for PerRoundDraw {
+    fn initial_order(&self, _candidates: &[Uuid], _seed: &str) -> InitialOrder {
+        InitialOrder::Deferred
+    }
+
+    fn draw_round(&self, candidates: &[Uuid], seed: &str) -> Option<Uuid> {
+        shuffle(seed, candidates).first().copied()
+    }
+}
+
+/// Le bénéficiaire de chaque tour est le gagnant de l'enchère du tour
+pub struct Auction;
+
+impl BeneficiaryOrdering for Auction {
+    fn initial_order(&self, _candidates: &[Uuid], _seed: &str) -> InitialOrder {
+        InitialOrder::Deferred
+    }
+
+    fn draw_round(&self, _candidates: &[Uuid], _seed: &str) -> Option<Uuid> {
+        None
+    }
+}
+
+pub fn ordering_for(strategy: PayoutStrategy) -> Box<dyn BeneficiaryOrdering> {
+    match strategy {
+        PayoutStrategy::Fixed => Box::new(FixedOrder),
+        PayoutStrategy::RandomDraw => Box::new(RandomDraw),
+        PayoutStrategy::PerRoundDraw => Box::new(PerRoundDraw),
+        PayoutStrategy::Auction => Box::new(Auction),
+    }
+}
+
+/// Graine aléatoire de 256 bits (hexadécimal), publiée avec le résultat du tirage
+pub fn generate_seed() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Mélange de Fisher-Yates déterministe : pour i de n-1 à 1, l'élément i est échangé avec
+/// l'élément j = (8 premiers octets de SHA-256("{seed}:{i}") en big-endian) mod (i + 1).
+/// Avec la graine et la liste des candidats, n'importe quel membre peut refaire le calcul.
+pub fn shuffle(seed: &str, candidates: &[Uuid]) -> Vec<Uuid> {
+    let mut order = candidates.to_vec();
+
+    for i in (1..order.len()).rev() {
+        let digest = Sha256::digest(format!("{}:{}", seed, i).as_bytes());
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest[..8]);
+        let j = (u64::from_be_bytes(prefix) % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+
+    order
+}
+
+/// Refait le tirage à partir de sa graine et de ses candidats et le compare au résultat enregistré
+pub fn verify(draw: BeneficiaryDraw) -> DrawVerification {
+    let mut recomputed = shuffle(&draw.seed, &draw.candidates);
+    if draw.tontine_round_id.is_some() {
+        // Tirage d'un tour : seul le premier du mélange est retenu
+        recomputed.truncate(1);
+    }
+
+    DrawVerification {
+        valid: recomputed == draw.result,
+        recomputed,
+        draw,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    const SEED: &str = "5f2b9c0e7a1d4386b2e0c9f17d3a6b84e1c2d3f4a5b6c7d8e9f0011223344556";
+
+    fn candidates(count: u128) -> Vec<Uuid> {
+        (1..=count).map(Uuid::from_u128).collect()
+    }
+
+    fn draw(tontine_round_id: Option<Uuid>, candidates: Vec<Uuid>, result: Vec<Uuid>) -> BeneficiaryDraw {
+        BeneficiaryDraw {
+            id: Uuid::new_v4(),
+            tontine_id: Uuid::new_v4(),
+            tontine_round_id,
+            strategy: "random_draw".to_string(),
+            seed: SEED.to_string(),
+            candidates,
+            result,
+            drawn_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn shuffle_is_reproducible_from_its_seed() {
+        let candidates = candidates(10);
+        let order = shuffle(SEED, &candidates);
+
+        assert_eq!(shuffle(SEED, &candidates), order);
+        assert_ne!(shuffle("autre graine", &candidates), order);
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation_of_the_candidates() {
+        let candidates = candidates(25);
+        let mut order = shuffle(SEED, &candidates);
+        order.sort();
+
+        assert_eq!(order, candidates);
+        assert!(shuffle(SEED, &[]).is_empty());
+        assert_eq!(shuffle(SEED, &candidates[..1]), candidates[..1]);
+    }
+
+    #[test]
+    fn generate_seed_returns_256_random_bits_in_hex() {
+        let seed = generate_seed();
+
+        assert_eq!(seed.len(), 64);
+        assert!(seed.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(generate_seed(), seed);
+    }
+
+    #[test]
+    fn verify_accepts_a_recorded_initial_draw() {
+        let candidates = candidates(8);
+        let InitialOrder::Drawn(result) = RandomDraw.initial_order(&candidates, SEED) else {
+            panic!("le tirage au sort doit produire un ordre tiré");
+        };
+
+        let verification = verify(draw(None, candidates, result.clone()));
+        assert!(verification.valid);
+        assert_eq!(verification.recomputed, result);
+    }
+
+    #[test]
+    fn verify_accepts_a_recorded_round_draw() {
+        let candidates = candidates(8);
+        let winner = PerRoundDraw.draw_round(&candidates, SEED).expect("un bénéficiaire doit être tiré");
+
+        let verification = verify(draw(Some(Uuid::new_v4()), candidates, vec![winner]));
+        assert!(verification.valid);
+        assert_eq!(verification.recomputed, vec![winner]);
+    }
+
+    #[test]
+    fn verify_rejects_a_tampered_result() {
+        let candidates = candidates(8);
+        let mut result = shuffle(SEED, &candidates);
+        result.swap(0, 1);
+
+        assert!(!verify(draw(None, candidates, result)).valid);
+    }
+}
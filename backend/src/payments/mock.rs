@@ -0,0 +1,105 @@
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+
+use crate::errors::AppError;
+use crate::model::payment_transactions::{PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+
+use super::{CallbackEvent, PaymentRequest, PaymentProvider, ProviderResponse};
+
+/// Opérateur simulé pour le développement local : la demande ou le versement reste en attente
+/// et la consultation du statut renvoie l'issue configurée (`MOCK_PAYMENT_OUTCOME`)
+pub struct MockProvider {
+    outcome: PaymentTransactionStatus,
+}
+
+impl MockProvider {
+    pub fn new(outcome: &str) -> Self {
+        let outcome = match outcome {
+            "failure" => PaymentTransactionStatus::Failed,
+            "pending" => PaymentTransactionStatus::Pending,
+            _ => PaymentTransactionStatus::Successful,
+        };
+        MockProvider { outcome }
+    }
+}
+
+impl PaymentProvider for MockProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Mock
+    }
+
+    fn initiate_collection<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(async move {
+            println!(
+                "[paiement simulé] demande de {} {} au {} ({})",
+                request.amount, request.currency, request.phone_number, request.payment_reference
+            );
+            Ok(ProviderResponse {
+                status: PaymentTransactionStatus::Pending,
+                provider_reference: Some(format!("MOCK-{}", request.payment_reference)),
+                raw: json!({ "status": "pending" }),
+            })
+        })
+    }
+
+    fn reserve_disbursement<'a>(&'a self, _request: &'a PaymentRequest) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn initiate_disbursement<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(async move {
+            println!(
+                "[paiement simulé] versement de {} {} au {} ({})",
+                request.amount, request.currency, request.phone_number, request.payment_reference
+            );
+            Ok(ProviderResponse {
+                status: PaymentTransactionStatus::Pending,
+                provider_reference: Some(format!("MOCK-{}", request.payment_reference)),
+                raw: json!({ "status": "pending" }),
+            })
+        })
+    }
+
+    fn query_status<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(async move {
+            Ok(ProviderResponse {
+                status: self.outcome,
+                provider_reference: payment.provider_reference.clone(),
+                raw: json!({ "status": String::from(self.outcome) }),
+            })
+        })
+    }
+
+    /// Un versement est connu dès qu'il a reçu sa référence simulée
+    fn lookup_disbursement<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<Option<ProviderResponse>, AppError>> {
+        Box::pin(async move {
+            if payment.provider_reference.is_none() {
+                return Ok(None);
+            }
+            self.query_status(payment).await.map(Some)
+        })
+    }
+
+    /// Attend `{"payment_reference": "...", "status": "successful" | "failed" | "pending"}`
+    fn handle_callback(&self, payload: &Value) -> Result<CallbackEvent, AppError> {
+        let payment_reference = payload.get("payment_reference")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| AppError::ValidationError("Notification sans payment_reference".to_string()))?;
+        let status = payload.get("status")
+            .and_then(Value::as_str)
+            .map(|status| PaymentTransactionStatus::try_from(status.to_string()))
+            .transpose()
+            .map_err(AppError::ValidationError)?
+            .unwrap_or(self.outcome);
+
+        Ok(CallbackEvent {
+            payment_reference: Some(payment_reference),
+            response: ProviderResponse {
+                status,
+                provider_reference: None,
+                raw: payload.clone(),
+            },
+        })
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use sha2::Sha256;
+
+use crate::errors::AppError;
+use crate::model::payment_transactions::{PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+use crate::settings::PaymentSettings;
+
+pub mod mock;
+pub mod mtn;
+pub mod orange;
+pub mod reconciliation;
+pub mod service;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Demande de débit du compte mobile money d'un payeur, ou de versement sur celui d'un bénéficiaire
+#[derive(Debug, Clone)]
+pub struct PaymentRequest {
+    pub payment_reference: String,
+    pub amount: Decimal,
+    pub currency: String,
+    pub phone_number: String,
+    pub description: String,
+    /// URL à laquelle l'opérateur notifie l'issue du paiement
+    pub callback_url: Option<String>,
+    /// Référence déjà réservée auprès de l'opérateur (`payToken` Orange), réutilisée par un nouvel essai
+    pub provider_reference: Option<String>,
+}
+
+/// Statut d'un paiement tel que rapporté par l'opérateur
+#[derive(Debug, Clone)]
+pub struct ProviderResponse {
+    pub status: PaymentTransactionStatus,
+    pub provider_reference: Option<String>,
+    /// Réponse brute, conservée pour le suivi
+    pub raw: serde_json::Value,
+}
+
+/// Notification de l'opérateur : le paiement est identifié par notre référence
+/// ou par la sienne selon l'opérateur
+#[derive(Debug, Clone)]
+pub struct CallbackEvent {
+    pub payment_reference: Option<String>,
+    pub response: ProviderResponse,
+}
+
+/// Opérateur de paiement mobile money
+pub trait PaymentProvider: Send + Sync {
+    fn kind(&self) -> PaymentProviderKind;
+
+    /// Demande au payeur de valider le débit ; le paiement reste en général en attente
+    fn initiate_collection<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>>;
+
+    /// Réserve auprès de l'opérateur la référence d'un versement avant son envoi, pour qu'elle soit
+    /// enregistrée et réutilisée par un nouvel essai. `None` si notre `payment_reference` suffit.
+    fn reserve_disbursement<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<Option<String>, AppError>>;
+
+    /// Envoie le montant sur le compte du bénéficiaire ; le versement reste en général en attente
+    fn initiate_disbursement<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>>;
+
+    /// Recherche un versement chez l'opérateur : `None` s'il ne l'a jamais reçu et peut être envoyé
+    /// sans risque de double versement, une erreur si son sort est inconnu
+    fn lookup_disbursement<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<Option<ProviderResponse>, AppError>>;
+
+    /// Consulte un encaissement ou un versement selon le sens du paiement
+    fn query_status<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<ProviderResponse, AppError>>;
+
+    fn supports_disbursement(&self) -> bool {
+        true
+    }
+
+    /// Interprète la notification envoyée par l'opérateur à l'issue du paiement
+    fn handle_callback(&self, payload: &serde_json::Value) -> Result<CallbackEvent, AppError>;
+}
+
+/// Opérateurs configurés, partagés par les handlers
+pub struct PaymentProviders {
+    providers: HashMap<PaymentProviderKind, Arc<dyn PaymentProvider>>,
+    default_kind: PaymentProviderKind,
+    callback_base_url: String,
+    webhook_secret: String,
+    currency: String,
+}
+
+impl PaymentProviders {
+    /// MTN et Orange ne sont disponibles qu'avec leurs identifiants ; l'opérateur simulé
+    /// uniquement s'il est l'opérateur par défaut, pour ne jamais confirmer de faux paiements en production
+    pub fn from_settings(settings: &PaymentSettings) -> Self {
+        let mut providers: HashMap<PaymentProviderKind, Arc<dyn PaymentProvider>> = HashMap::new();
+
+        if settings.mtn.is_configured() {
+            providers.insert(PaymentProviderKind::MtnMobileMoney, Arc::new(mtn::MtnMomoProvider::new(settings.mtn.clone())));
+        }
+        if settings.orange.is_configured() {
+            providers.insert(PaymentProviderKind::OrangeMoney, Arc::new(orange::OrangeMoneyProvider::new(settings.orange.clone())));
+        }
+
+        let default_kind = PaymentProviderKind::from_slug(&settings.default_provider).unwrap_or(PaymentProviderKind::Mock);
+        if default_kind == PaymentProviderKind::Mock {
+            providers.insert(PaymentProviderKind::Mock, Arc::new(mock::MockProvider::new(&settings.mock_outcome)));
+        }
+
+        PaymentProviders {
+            providers,
+            default_kind,
+            callback_base_url: settings.callback_base_url.trim_end_matches('/').to_string(),
+            webhook_secret: settings.webhook_secret.clone(),
+            currency: settings.currency.clone(),
+        }
+    }
+
+    pub fn get(&self, kind: PaymentProviderKind) -> Result<Arc<dyn PaymentProvider>, AppError> {
+        self.providers.get(&kind).cloned().ok_or_else(|| {
+            AppError::ValidationError(format!("L'opérateur de paiement {} n'est pas configuré", kind.slug()))
+        })
+    }
+
+    /// Opérateur autorisé à verser les tours
+    pub fn disburser(&self, kind: PaymentProviderKind) -> Result<Arc<dyn PaymentProvider>, AppError> {
+        let provider = self.get(kind)?;
+        if !provider.supports_disbursement() {
+            return Err(AppError::ValidationError(format!(
+                "Les versements ne sont pas configurés pour l'opérateur {}", kind.slug()
+            )));
+        }
+        Ok(provider)
+    }
+
+    pub fn default_kind(&self) -> PaymentProviderKind {
+        self.default_kind
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    pub fn callback_url(&self, kind: PaymentProviderKind) -> Option<String> {
+        if self.callback_base_url.is_empty() {
+            return None;
+        }
+        Some(format!("{}/api/payments/callback/{}", self.callback_base_url, kind.slug()))
+    }
+
+    /// Vérifie `X-Signature` : HMAC-SHA256 du corps brut avec la clé partagée, en hexadécimal,
+    /// éventuellement préfixé de `sha256=`. La comparaison se fait en temps constant.
+    pub fn verify_signature(&self, body: &[u8], signature: Option<&str>) -> Result<(), AppError> {
+        if self.webhook_secret.is_empty() {
+            return Err(AppError::AuthenticationError("Notifications de paiement non configurées".to_string()));
+        }
+
+        let signature = signature
+            .map(|value| value.trim())
+            .map(|value| value.strip_prefix("sha256=").unwrap_or(value))
+            .and_then(|value| hex::decode(value).ok())
+            .ok_or_else(|| AppError::AuthenticationError("Signature de la notification absente ou illisible".to_string()))?;
+
+        let mut mac = HmacSha256::new_from_slice(self.webhook_secret.as_bytes())
+            .expect("HMAC accepte des clés de toute taille");
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| AppError::AuthenticationError("Signature de la notification invalide".to_string()))
+    }
+}
+
+/// Chiffres du numéro au format international camerounais (237XXXXXXXXX)
+pub(crate) fn international_msisdn(phone: &str) -> String {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let digits = digits.strip_prefix("00").map(str::to_string).unwrap_or(digits);
+    if digits.len() == 9 {
+        format!("237{}", digits)
+    } else {
+        digits
+    }
+}
+
+/// Numéro sans indicatif pays (XXXXXXXXX)
+pub(crate) fn local_msisdn(phone: &str) -> String {
+    let international = international_msisdn(phone);
+    match international.strip_prefix("237") {
+        Some(local) if local.len() == 9 => local.to_string(),
+        _ => international,
+    }
+}
+
+/// Montant transmis aux opérateurs, sans décimales superflues
+pub(crate) fn format_amount(amount: Decimal) -> String {
+    amount.normalize().to_string()
+}
+
+pub(crate) fn provider_error(provider: &str, error: impl std::fmt::Display) -> AppError {
+    AppError::PaymentProviderError(format!("{} : {}", provider, error))
+}
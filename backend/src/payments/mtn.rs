@@ -0,0 +1,229 @@
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+
+use crate::errors::AppError;
+use crate::model::payment_transactions::{PaymentDirection, PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+use crate::settings::MtnSettings;
+
+use super::{CallbackEvent, PaymentRequest, PaymentProvider, ProviderResponse, format_amount, international_msisdn, provider_error};
+
+const PROVIDER_NAME: &str = "MTN Mobile Money";
+
+/// Produits Collection et Disbursement de la MoMo Open API : le payeur valide la demande
+/// `requesttopay` sur son téléphone, le bénéficiaire reçoit un `transfer`. La référence de
+/// la demande est notre `payment_reference`.
+pub struct MtnMomoProvider {
+    client: reqwest::Client,
+    settings: MtnSettings,
+}
+
+impl MtnMomoProvider {
+    pub fn new(settings: MtnSettings) -> Self {
+        MtnMomoProvider {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.settings.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Chaque produit a sa clé d'abonnement et son utilisateur API
+    fn product(&self, direction: PaymentDirection) -> Product<'_> {
+        match direction {
+            PaymentDirection::Collection => Product {
+                path: "collection",
+                resource: "requesttopay",
+                subscription_key: &self.settings.subscription_key,
+                api_user: &self.settings.api_user,
+                api_key: &self.settings.api_key,
+            },
+            PaymentDirection::Disbursement => Product {
+                path: "disbursement",
+                resource: "transfer",
+                subscription_key: &self.settings.disbursement_subscription_key,
+                api_user: &self.settings.disbursement_api_user,
+                api_key: &self.settings.disbursement_api_key,
+            },
+        }
+    }
+
+    async fn access_token(&self, product: &Product<'_>) -> Result<String, AppError> {
+        let response = self.client
+            .post(self.url(&format!("/{}/token/", product.path)))
+            .basic_auth(product.api_user, Some(product.api_key))
+            .header("Ocp-Apim-Subscription-Key", product.subscription_key)
+            .header(reqwest::header::CONTENT_LENGTH, 0)
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        let body = read_json(response).await?;
+        body.get("access_token")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| provider_error(PROVIDER_NAME, "jeton d'accès absent de la réponse"))
+    }
+
+    async fn submit(&self, direction: PaymentDirection, request: &PaymentRequest) -> Result<ProviderResponse, AppError> {
+        let product = self.product(direction);
+        let token = self.access_token(&product).await?;
+
+        let mut builder = self.client
+            .post(self.url(&format!("/{}/v1_0/{}", product.path, product.resource)))
+            .bearer_auth(token)
+            .header("X-Reference-Id", &request.payment_reference)
+            .header("X-Target-Environment", &self.settings.target_environment)
+            .header("Ocp-Apim-Subscription-Key", product.subscription_key);
+        if let Some(callback_url) = &request.callback_url {
+            builder = builder.header("X-Callback-Url", callback_url);
+        }
+
+        let party = json!({
+            "partyIdType": "MSISDN",
+            "partyId": international_msisdn(&request.phone_number),
+        });
+        let party_field = match direction {
+            PaymentDirection::Collection => "payer",
+            PaymentDirection::Disbursement => "payee",
+        };
+
+        let response = builder
+            .json(&json!({
+                "amount": format_amount(request.amount),
+                "currency": request.currency,
+                "externalId": request.payment_reference,
+                (party_field): party,
+                "payerMessage": request.description,
+                "payeeNote": request.description,
+            }))
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        // 202 Accepted sans corps : la demande attend la validation du payeur ou le traitement
+        // du versement. 409 : la référence a déjà été reçue lors d'un essai précédent.
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::CONFLICT {
+            let body = response.text().await.unwrap_or_default();
+            return Err(provider_error(PROVIDER_NAME, format!("demande refusée ({}) {}", status, body)));
+        }
+
+        Ok(ProviderResponse {
+            status: PaymentTransactionStatus::Pending,
+            provider_reference: Some(request.payment_reference.clone()),
+            raw: json!({ "http_status": status.as_u16() }),
+        })
+    }
+
+    async fn request_status(&self, direction: PaymentDirection, reference: &str) -> Result<ProviderResponse, AppError> {
+        self.find_status(direction, reference).await?
+            .ok_or_else(|| provider_error(PROVIDER_NAME, format!("opération {} inconnue", reference)))
+    }
+
+    /// Statut de l'opération, `None` si MTN ne l'a jamais reçue (404)
+    async fn find_status(&self, direction: PaymentDirection, reference: &str) -> Result<Option<ProviderResponse>, AppError> {
+        let product = self.product(direction);
+        let token = self.access_token(&product).await?;
+
+        let response = self.client
+            .get(self.url(&format!("/{}/v1_0/{}/{}", product.path, product.resource, reference)))
+            .bearer_auth(token)
+            .header("X-Target-Environment", &self.settings.target_environment)
+            .header("Ocp-Apim-Subscription-Key", product.subscription_key)
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let body = read_json(response).await?;
+        Ok(Some(ProviderResponse {
+            status: parse_status(&body),
+            provider_reference: Some(reference.to_string()),
+            raw: body,
+        }))
+    }
+}
+
+impl PaymentProvider for MtnMomoProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::MtnMobileMoney
+    }
+
+    fn initiate_collection<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(self.submit(PaymentDirection::Collection, request))
+    }
+
+    /// La référence du transfert est notre `payment_reference` (`X-Reference-Id`)
+    fn reserve_disbursement<'a>(&'a self, _request: &'a PaymentRequest) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn initiate_disbursement<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(self.submit(PaymentDirection::Disbursement, request))
+    }
+
+    /// Le transfert est recherché par notre référence, même si la réponse à son envoi a été perdue
+    fn lookup_disbursement<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<Option<ProviderResponse>, AppError>> {
+        Box::pin(self.find_status(PaymentDirection::Disbursement, &payment.payment_reference))
+    }
+
+    fn query_status<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(async move {
+            let direction = PaymentDirection::try_from(payment.direction.clone()).map_err(AppError::InternalServerError)?;
+            let reference = payment.provider_reference.as_deref().unwrap_or(&payment.payment_reference);
+            self.request_status(direction, reference).await
+        })
+    }
+
+    fn supports_disbursement(&self) -> bool {
+        self.settings.disbursement_configured()
+    }
+
+    /// Le corps de la notification reprend celui du statut, `externalId` portant notre référence
+    fn handle_callback(&self, payload: &Value) -> Result<CallbackEvent, AppError> {
+        let payment_reference = payload.get("externalId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| AppError::ValidationError("Notification MTN sans externalId".to_string()))?;
+
+        Ok(CallbackEvent {
+            payment_reference: Some(payment_reference),
+            response: ProviderResponse {
+                status: parse_status(payload),
+                provider_reference: None,
+                raw: payload.clone(),
+            },
+        })
+    }
+}
+
+struct Product<'a> {
+    path: &'static str,
+    resource: &'static str,
+    subscription_key: &'a str,
+    api_user: &'a str,
+    api_key: &'a str,
+}
+
+/// `SUCCESSFUL`, `FAILED` (avec `reason`) ou `PENDING`
+fn parse_status(body: &Value) -> PaymentTransactionStatus {
+    match body.get("status").and_then(Value::as_str) {
+        Some("SUCCESSFUL") => PaymentTransactionStatus::Successful,
+        Some("FAILED") | Some("REJECTED") | Some("TIMEOUT") => PaymentTransactionStatus::Failed,
+        _ => PaymentTransactionStatus::Pending,
+    }
+}
+
+async fn read_json(response: reqwest::Response) -> Result<Value, AppError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(provider_error(PROVIDER_NAME, format!("réponse {} {}", status, body)));
+    }
+    response.json().await.map_err(|e| provider_error(PROVIDER_NAME, e))
+}
@@ -0,0 +1,215 @@
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+
+use crate::errors::AppError;
+use crate::model::payment_transactions::{PaymentDirection, PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+use crate::settings::OrangeSettings;
+
+use super::{CallbackEvent, PaymentRequest, PaymentProvider, ProviderResponse, format_amount, local_msisdn, provider_error};
+
+const PROVIDER_NAME: &str = "Orange Money";
+const API_PATH: &str = "/omcoreapis/1.0.2";
+
+/// API Merchant Payment (`mp`) pour les encaissements et Cash In (`cashin`) pour les versements :
+/// un `payToken` est d'abord réservé puis utilisé pour la demande ; il identifie ensuite
+/// l'opération auprès d'Orange
+pub struct OrangeMoneyProvider {
+    client: reqwest::Client,
+    settings: OrangeSettings,
+}
+
+impl OrangeMoneyProvider {
+    pub fn new(settings: OrangeSettings) -> Self {
+        OrangeMoneyProvider {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.settings.base_url.trim_end_matches('/'), path)
+    }
+
+    async fn access_token(&self) -> Result<String, AppError> {
+        let response = self.client
+            .post(self.url("/token"))
+            .basic_auth(&self.settings.client_id, Some(&self.settings.client_secret))
+            .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body("grant_type=client_credentials")
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        let body = read_json(response).await?;
+        body.get("access_token")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| provider_error(PROVIDER_NAME, "jeton d'accès absent de la réponse"))
+    }
+
+    /// Réserve un `payToken`, qui identifie ensuite l'opération
+    async fn init(&self, direction: PaymentDirection, token: &str) -> Result<String, AppError> {
+        let init = self.client
+            .post(self.url(&format!("{}/init", api_path(direction))))
+            .bearer_auth(token)
+            .header("X-AUTH-TOKEN", &self.settings.auth_token)
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+        let init = read_json(init).await?;
+        data(&init).get("payToken")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| provider_error(PROVIDER_NAME, "payToken absent de la réponse"))
+    }
+
+    /// Utilise le `payToken` déjà réservé s'il y en a un : Orange n'exécute qu'une opération par `payToken`
+    async fn pay(&self, direction: PaymentDirection, request: &PaymentRequest) -> Result<ProviderResponse, AppError> {
+        let token = self.access_token().await?;
+        let api = api_path(direction);
+
+        let pay_token = match &request.provider_reference {
+            Some(pay_token) => pay_token.clone(),
+            None => self.init(direction, &token).await?,
+        };
+
+        let response = self.client
+            .post(self.url(&format!("{}/pay", api)))
+            .bearer_auth(&token)
+            .header("X-AUTH-TOKEN", &self.settings.auth_token)
+            .json(&json!({
+                "notifUrl": request.callback_url.clone().unwrap_or_default(),
+                "channelUserMsisdn": self.settings.channel_msisdn,
+                "amount": format_amount(request.amount),
+                "subscriberMsisdn": local_msisdn(&request.phone_number),
+                "pin": self.settings.pin,
+                "orderId": request.payment_reference,
+                "description": request.description,
+                "payToken": pay_token,
+            }))
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        let body = read_json(response).await?;
+        Ok(ProviderResponse {
+            status: parse_status(data(&body)),
+            provider_reference: Some(pay_token),
+            raw: body,
+        })
+    }
+
+    async fn payment_status(&self, direction: PaymentDirection, pay_token: &str) -> Result<ProviderResponse, AppError> {
+        let token = self.access_token().await?;
+
+        let response = self.client
+            .get(self.url(&format!("{}/paymentstatus/{}", api_path(direction), pay_token)))
+            .bearer_auth(token)
+            .header("X-AUTH-TOKEN", &self.settings.auth_token)
+            .send()
+            .await
+            .map_err(|e| provider_error(PROVIDER_NAME, e))?;
+
+        let body = read_json(response).await?;
+        Ok(ProviderResponse {
+            status: parse_status(data(&body)),
+            provider_reference: Some(pay_token.to_string()),
+            raw: body,
+        })
+    }
+}
+
+impl PaymentProvider for OrangeMoneyProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::OrangeMoney
+    }
+
+    fn initiate_collection<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(self.pay(PaymentDirection::Collection, request))
+    }
+
+    /// Le `payToken` est réservé et enregistré avant `/pay`, pour qu'un nouvel essai ne crée pas
+    /// un second cash-in
+    fn reserve_disbursement<'a>(&'a self, _request: &'a PaymentRequest) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async move {
+            let token = self.access_token().await?;
+            self.init(PaymentDirection::Disbursement, &token).await.map(Some)
+        })
+    }
+
+    fn initiate_disbursement<'a>(&'a self, request: &'a PaymentRequest) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(self.pay(PaymentDirection::Disbursement, request))
+    }
+
+    /// Un `payToken` encore `INITIATED` n'a pas été utilisé par `/pay`
+    fn lookup_disbursement<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<Option<ProviderResponse>, AppError>> {
+        Box::pin(async move {
+            let Some(pay_token) = payment.provider_reference.as_deref() else {
+                return Ok(None);
+            };
+            let response = self.payment_status(PaymentDirection::Disbursement, pay_token).await?;
+            if data(&response.raw).get("status").and_then(Value::as_str) == Some("INITIATED") {
+                return Ok(None);
+            }
+            Ok(Some(response))
+        })
+    }
+
+    fn query_status<'a>(&'a self, payment: &'a PaymentTransaction) -> BoxFuture<'a, Result<ProviderResponse, AppError>> {
+        Box::pin(async move {
+            let direction = PaymentDirection::try_from(payment.direction.clone()).map_err(AppError::InternalServerError)?;
+            let pay_token = payment.provider_reference.as_deref().ok_or_else(|| {
+                AppError::ValidationError("Ce paiement n'a pas encore de payToken Orange".to_string())
+            })?;
+            self.payment_status(direction, pay_token).await
+        })
+    }
+
+    /// La notification identifie le paiement par son `payToken`
+    fn handle_callback(&self, payload: &Value) -> Result<CallbackEvent, AppError> {
+        let body = data(payload);
+        let pay_token = body.get("payToken")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| AppError::ValidationError("Notification Orange sans payToken".to_string()))?;
+
+        Ok(CallbackEvent {
+            payment_reference: body.get("orderId").and_then(Value::as_str).map(str::to_string),
+            response: ProviderResponse {
+                status: parse_status(body),
+                provider_reference: Some(pay_token),
+                raw: payload.clone(),
+            },
+        })
+    }
+}
+
+fn api_path(direction: PaymentDirection) -> String {
+    match direction {
+        PaymentDirection::Collection => format!("{}/mp", API_PATH),
+        PaymentDirection::Disbursement => format!("{}/cashin", API_PATH),
+    }
+}
+
+/// Les réponses d'Orange sont enveloppées dans `data`
+fn data(body: &Value) -> &Value {
+    body.get("data").unwrap_or(body)
+}
+
+/// `SUCCESSFULL` (sic), `FAILED`, `EXPIRED`, `CANCELLED`, ou `PENDING`/`INITIATED`
+fn parse_status(body: &Value) -> PaymentTransactionStatus {
+    match body.get("status").and_then(Value::as_str) {
+        Some("SUCCESSFULL") | Some("SUCCESSFUL") => PaymentTransactionStatus::Successful,
+        Some("FAILED") | Some("EXPIRED") | Some("CANCELLED") => PaymentTransactionStatus::Failed,
+        _ => PaymentTransactionStatus::Pending,
+    }
+}
+
+async fn read_json(response: reqwest::Response) -> Result<Value, AppError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(provider_error(PROVIDER_NAME, format!("réponse {} {}", status, body)));
+    }
+    response.json().await.map_err(|e| provider_error(PROVIDER_NAME, e))
+}
@@ -0,0 +1,451 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::model::payment_transactions::PaymentProviderKind;
+use crate::model::reconciliation::{
+    NewReconciliationItem, NewReconciliationRun, ReconciliationItemKind, ReconciliationReport,
+    RecordedPayment, StatementFormat, StatementLine,
+};
+use crate::repositories::reconciliation_repository::ReconciliationRepository;
+
+use super::international_msisdn;
+
+/// Noms de colonnes (CSV) ou de champs (JSON) acceptés, en minuscules
+const REFERENCE_FIELDS: [&str; 6] = ["reference", "ref", "payment_reference", "transaction_id", "financialtransactionid", "txnid"];
+const AMOUNT_FIELDS: [&str; 2] = ["amount", "montant"];
+const PHONE_FIELDS: [&str; 4] = ["phone", "phone_number", "msisdn", "telephone"];
+const DATE_FIELDS: [&str; 4] = ["date", "created_at", "timestamp", "transaction_date"];
+const STATUS_FIELDS: [&str; 2] = ["status", "statut"];
+/// Statuts d'une ligne de relevé qui correspond à un paiement encaissé
+const SUCCESS_STATUSES: [&str; 5] = ["success", "successful", "successfull", "completed", "paid"];
+
+pub struct ReconciliationService;
+
+impl ReconciliationService {
+    /// Lit le relevé, le rapproche des cotisations enregistrées et enregistre le rapport
+    pub async fn import(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        provider: PaymentProviderKind,
+        format: StatementFormat,
+        body: &[u8],
+        imported_by: Uuid,
+    ) -> Result<ReconciliationReport, AppError> {
+        let lines = parse_statement(body, format)?;
+        if lines.is_empty() {
+            return Err(AppError::ValidationError("Le relevé ne contient aucun paiement encaissé".to_string()));
+        }
+
+        let period = statement_period(&lines);
+        let records = ReconciliationRepository::find_recorded_payments(pool, tontine_id, provider, period).await?;
+
+        let references: Vec<String> = lines.iter().filter_map(|line| line.reference.clone()).collect();
+        let foreign: HashSet<String> = ReconciliationRepository::find_foreign_references(pool, tontine_id, provider, &references)
+            .await?
+            .into_iter()
+            .collect();
+
+        let items = match_statement(&lines, &records, &foreign);
+
+        let run = NewReconciliationRun {
+            tontine_id,
+            provider,
+            format,
+            period,
+            lines_count: lines.len(),
+            imported_by,
+        };
+        ReconciliationRepository::create_run(pool, &run, &items).await
+    }
+}
+
+/// Rapproche chaque ligne par référence du paiement, sinon par numéro et montant pour les
+/// cotisations saisies à la main. Les cotisations restées sans ligne sont absentes du relevé.
+fn match_statement(lines: &[StatementLine], records: &[RecordedPayment], foreign: &HashSet<String>) -> Vec<NewReconciliationItem> {
+    let mut items = Vec::new();
+    let mut claimed: HashSet<Uuid> = HashSet::new();
+    let mut seen_references: HashSet<&str> = HashSet::new();
+
+    let item = |kind, line: &StatementLine, record: Option<&RecordedPayment>| NewReconciliationItem {
+        kind,
+        line: Some(line.clone()),
+        record: record.cloned(),
+    };
+
+    for line in lines {
+        if let Some(reference) = line.reference.as_deref() {
+            if !seen_references.insert(reference) {
+                items.push(item(ReconciliationItemKind::Duplicate, line, None));
+                continue;
+            }
+
+            let by_reference = records.iter().find(|record| {
+                record.payment_reference.as_deref() == Some(reference) || record.provider_reference.as_deref() == Some(reference)
+            });
+            if let Some(record) = by_reference {
+                let kind = if !claimed.insert(record.contribution_id) {
+                    ReconciliationItemKind::Duplicate
+                } else if record.amount == line.amount {
+                    ReconciliationItemKind::Matched
+                } else {
+                    ReconciliationItemKind::AmountMismatch
+                };
+                items.push(item(kind, line, Some(record)));
+                continue;
+            }
+
+            // Paiement d'une autre tontine encaissé sur le même compte marchand
+            if foreign.contains(reference) {
+                continue;
+            }
+        }
+
+        let phone = line.phone.as_deref().map(international_msisdn);
+        let manual = |record: &&RecordedPayment| {
+            record.payment_transaction_id.is_none()
+                && !claimed.contains(&record.contribution_id)
+                && phone.as_deref() == Some(international_msisdn(&record.member_phone).as_str())
+        };
+
+        let by_phone = records.iter().filter(manual).find(|record| record.amount == line.amount)
+            .map(|record| (ReconciliationItemKind::Matched, record))
+            .or_else(|| records.iter().find(manual).map(|record| (ReconciliationItemKind::AmountMismatch, record)));
+
+        match by_phone {
+            Some((kind, record)) => {
+                claimed.insert(record.contribution_id);
+                items.push(item(kind, line, Some(record)));
+            }
+            None => items.push(item(ReconciliationItemKind::MissingInRecords, line, None)),
+        }
+    }
+
+    for record in records.iter().filter(|record| !claimed.contains(&record.contribution_id)) {
+        items.push(NewReconciliationItem {
+            kind: ReconciliationItemKind::MissingInStatement,
+            line: None,
+            record: Some(record.clone()),
+        });
+    }
+
+    items
+}
+
+/// Du début du premier jour au lendemain du dernier jour du relevé, s'il est daté
+fn statement_period(lines: &[StatementLine]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let dates = lines.iter().filter_map(|line| line.date);
+    let first = dates.clone().min()?;
+    let last = dates.max()?;
+
+    let start = first.date_naive().and_hms_opt(0, 0, 0)?.and_utc();
+    let end = last.date_naive().and_hms_opt(0, 0, 0)?.and_utc() + Duration::days(1);
+    Some((start, end))
+}
+
+/// Lignes encaissées du relevé ; les lignes dont le statut indique un échec sont ignorées
+pub fn parse_statement(body: &[u8], format: StatementFormat) -> Result<Vec<StatementLine>, AppError> {
+    let records = match format {
+        StatementFormat::Csv => parse_csv(body)?,
+        StatementFormat::Json => parse_json(body)?,
+    };
+
+    let mut lines = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let field = |names: &[&str]| {
+            record.iter()
+                .find(|(key, value)| names.contains(&key.as_str()) && !value.is_empty())
+                .map(|(_, value)| value.clone())
+        };
+
+        if let Some(status) = field(&STATUS_FIELDS)
+            && !SUCCESS_STATUSES.contains(&status.to_lowercase().as_str()) {
+            continue;
+        }
+
+        let line_number = index + 1;
+        let amount = field(&AMOUNT_FIELDS)
+            .ok_or_else(|| AppError::ValidationError(format!("Ligne {} du relevé : montant absent", line_number)))?;
+        let amount = parse_amount(&amount)
+            .ok_or_else(|| AppError::ValidationError(format!("Ligne {} du relevé : montant invalide ({})", line_number, amount)))?;
+        let date = match field(&DATE_FIELDS) {
+            Some(value) => Some(parse_date(&value)
+                .ok_or_else(|| AppError::ValidationError(format!("Ligne {} du relevé : date invalide ({})", line_number, value)))?),
+            None => None,
+        };
+
+        lines.push(StatementLine {
+            reference: field(&REFERENCE_FIELDS),
+            amount,
+            phone: field(&PHONE_FIELDS),
+            date,
+        });
+    }
+
+    Ok(lines)
+}
+
+/// CSV avec ligne d'en-tête, séparé par des virgules ou des points-virgules
+fn parse_csv(body: &[u8]) -> Result<Vec<Vec<(String, String)>>, AppError> {
+    let content = std::str::from_utf8(body)
+        .map_err(|_| AppError::ValidationError("Le relevé CSV doit être encodé en UTF-8".to_string()))?;
+    let mut rows = content.lines().map(str::trim).filter(|row| !row.is_empty());
+
+    let header = rows.next()
+        .ok_or_else(|| AppError::ValidationError("Le relevé CSV est vide".to_string()))?;
+    let separator = if header.contains(';') { ';' } else { ',' };
+    let columns: Vec<String> = split_csv_row(header, separator).into_iter().map(|column| column.to_lowercase()).collect();
+
+    Ok(rows
+        .map(|row| columns.iter().cloned().zip(split_csv_row(row, separator)).collect())
+        .collect())
+}
+
+/// Découpe une ligne en respectant les champs entre guillemets
+fn split_csv_row(row: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut current).trim().to_string()),
+            c => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+
+    fields
+}
+
+/// Tableau d'objets, ou objet dont `lines`, `transactions` ou `data` contient ce tableau
+fn parse_json(body: &[u8]) -> Result<Vec<Vec<(String, String)>>, AppError> {
+    let value: Value = serde_json::from_slice(body)
+        .map_err(|e| AppError::ValidationError(format!("Relevé JSON illisible : {}", e)))?;
+
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(object) => ["lines", "transactions", "data"].iter()
+            .find_map(|key| object.get(*key).and_then(Value::as_array))
+            .ok_or_else(|| AppError::ValidationError("Le relevé JSON doit contenir un tableau de paiements".to_string()))?,
+        _ => return Err(AppError::ValidationError("Le relevé JSON doit contenir un tableau de paiements".to_string())),
+    };
+
+    entries.iter().enumerate().map(|(index, entry)| {
+        let object = entry.as_object()
+            .ok_or_else(|| AppError::ValidationError(format!("Ligne {} du relevé : objet attendu", index + 1)))?;
+        Ok(object.iter()
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    Value::String(text) => text.trim().to_string(),
+                    Value::Number(number) => number.to_string(),
+                    Value::Null => return None,
+                    other => other.to_string(),
+                };
+                Some((key.to_lowercase(), value))
+            })
+            .collect())
+    }).collect()
+}
+
+/// Montant avec espaces de milliers éventuels et virgule ou point décimal ;
+/// avec les deux, la virgule sépare les milliers
+fn parse_amount(value: &str) -> Option<Decimal> {
+    let cleaned: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let cleaned = if cleaned.contains('.') {
+        cleaned.replace(',', "")
+    } else {
+        cleaned.replace(',', ".")
+    };
+    Decimal::from_str(&cleaned).ok()
+}
+
+/// RFC 3339, `AAAA-MM-JJ HH:MM:SS` (UTC) ou `AAAA-MM-JJ`
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).map(|date| date.with_timezone(&Utc)).ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok().map(|date| date.and_utc()))
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn line(reference: Option<&str>, amount: Decimal, phone: Option<&str>) -> StatementLine {
+        StatementLine {
+            reference: reference.map(str::to_string),
+            amount,
+            phone: phone.map(str::to_string),
+            date: None,
+        }
+    }
+
+    /// Cotisation payée par mobile money (avec référence) ou saisie à la main (sans)
+    fn record(id: u128, amount: Decimal, phone: &str, reference: Option<&str>) -> RecordedPayment {
+        RecordedPayment {
+            contribution_id: Uuid::from_u128(id),
+            amount,
+            payment_date: Utc::now(),
+            member_phone: phone.to_string(),
+            payment_transaction_id: reference.map(|_| Uuid::from_u128(id + 1000)),
+            payment_reference: reference.map(str::to_string),
+            provider_reference: None,
+        }
+    }
+
+    fn kinds(items: &[NewReconciliationItem]) -> Vec<ReconciliationItemKind> {
+        items.iter().map(|item| item.kind).collect()
+    }
+
+    fn contribution(item: &NewReconciliationItem) -> Option<Uuid> {
+        item.record.as_ref().map(|record| record.contribution_id)
+    }
+
+    #[test]
+    fn match_statement_matches_by_reference() {
+        let records = [record(1, dec("5000"), "677000001", Some("TON-1"))];
+        let items = match_statement(&[line(Some("TON-1"), dec("5000"), None)], &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::Matched]);
+        assert_eq!(contribution(&items[0]), Some(Uuid::from_u128(1)));
+    }
+
+    #[test]
+    fn match_statement_matches_the_provider_reference_too() {
+        let mut recorded = record(1, dec("5000"), "677000001", Some("TON-1"));
+        recorded.provider_reference = Some("MP2501.1234.A5678".to_string());
+        let items = match_statement(&[line(Some("MP2501.1234.A5678"), dec("5000"), None)], &[recorded], &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::Matched]);
+    }
+
+    #[test]
+    fn match_statement_flags_an_amount_mismatch_on_a_reference() {
+        let records = [record(1, dec("5000"), "677000001", Some("TON-1"))];
+        let items = match_statement(&[line(Some("TON-1"), dec("4500"), None)], &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::AmountMismatch]);
+        assert_eq!(contribution(&items[0]), Some(Uuid::from_u128(1)));
+    }
+
+    #[test]
+    fn match_statement_matches_manual_contributions_by_phone_and_amount() {
+        let records = [
+            record(1, dec("5000"), "677000001", None),
+            record(2, dec("10000"), "677000001", None),
+        ];
+        let lines = [line(None, dec("10000"), Some("+237 677 00 00 01"))];
+        let items = match_statement(&lines, &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::Matched, ReconciliationItemKind::MissingInStatement]);
+        assert_eq!(contribution(&items[0]), Some(Uuid::from_u128(2)));
+        assert_eq!(contribution(&items[1]), Some(Uuid::from_u128(1)));
+    }
+
+    #[test]
+    fn match_statement_flags_an_amount_mismatch_on_a_phone_number() {
+        let records = [record(1, dec("5000"), "237677000001", None)];
+        let items = match_statement(&[line(None, dec("3000"), Some("677000001"))], &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::AmountMismatch]);
+    }
+
+    #[test]
+    fn match_statement_does_not_match_mobile_money_payments_by_phone() {
+        let records = [record(1, dec("5000"), "677000001", Some("TON-1"))];
+        let items = match_statement(&[line(None, dec("5000"), Some("677000001"))], &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::MissingInRecords, ReconciliationItemKind::MissingInStatement]);
+    }
+
+    #[test]
+    fn match_statement_flags_duplicates() {
+        let records = [record(1, dec("5000"), "677000001", Some("TON-1"))];
+        let lines = [line(Some("TON-1"), dec("5000"), None), line(Some("TON-1"), dec("5000"), None)];
+        let items = match_statement(&lines, &records, &HashSet::new());
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::Matched, ReconciliationItemKind::Duplicate]);
+    }
+
+    #[test]
+    fn match_statement_ignores_references_of_other_tontines() {
+        let foreign = HashSet::from(["TON-AUTRE".to_string()]);
+        let lines = [line(Some("TON-AUTRE"), dec("5000"), None), line(Some("INCONNUE"), dec("2000"), None)];
+        let items = match_statement(&lines, &[], &foreign);
+
+        assert_eq!(kinds(&items), [ReconciliationItemKind::MissingInRecords]);
+        assert_eq!(items[0].line.as_ref().and_then(|line| line.reference.as_deref()), Some("INCONNUE"));
+    }
+
+    #[test]
+    fn parse_statement_reads_csv_with_semicolons_and_quotes() {
+        let body = "Reference;Montant;Telephone;Date;Statut\n\
+                    TON-1;\"5 000,50\";677000001;2025-01-15;SUCCESSFUL\n\
+                    TON-2;2000;677000002;2025-01-16 10:30:00;FAILED\n\
+                    \n\
+                    \"TON-3;bis\";\"1,250.00\";;2025-01-17T08:00:00+01:00;completed\n";
+        let lines = parse_statement(body.as_bytes(), StatementFormat::Csv).unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].reference.as_deref(), Some("TON-1"));
+        assert_eq!(lines[0].amount, dec("5000.50"));
+        assert_eq!(lines[0].phone.as_deref(), Some("677000001"));
+        assert_eq!(lines[0].date.map(|date| date.to_rfc3339()).as_deref(), Some("2025-01-15T00:00:00+00:00"));
+        assert_eq!(lines[1].reference.as_deref(), Some("TON-3;bis"));
+        assert_eq!(lines[1].amount, dec("1250.00"));
+        assert_eq!(lines[1].phone, None);
+        assert_eq!(lines[1].date.map(|date| date.to_rfc3339()).as_deref(), Some("2025-01-17T07:00:00+00:00"));
+    }
+
+    #[test]
+    fn parse_statement_reads_json_arrays_and_wrapped_arrays() {
+        let array = br#"[{"financialTransactionId": "MP-1", "amount": 5000, "msisdn": "237677000001", "status": "SUCCESSFUL"},
+                         {"financialTransactionId": "MP-2", "amount": "2000", "status": "FAILED"}]"#;
+        let lines = parse_statement(array, StatementFormat::Json).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].reference.as_deref(), Some("MP-1"));
+        assert_eq!(lines[0].amount, dec("5000"));
+        assert_eq!(lines[0].phone.as_deref(), Some("237677000001"));
+
+        let wrapped = br#"{"transactions": [{"txnid": "OM-1", "montant": "7500.00", "date": null}]}"#;
+        let lines = parse_statement(wrapped, StatementFormat::Json).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].reference.as_deref(), Some("OM-1"));
+        assert_eq!(lines[0].amount, dec("7500.00"));
+        assert_eq!(lines[0].date, None);
+    }
+
+    #[test]
+    fn parse_statement_rejects_invalid_lines() {
+        let missing_amount = parse_statement(b"reference,phone\nTON-1,677000001\n", StatementFormat::Csv);
+        assert!(matches!(missing_amount, Err(AppError::ValidationError(message)) if message.contains("Ligne 1")));
+
+        let invalid_amount = parse_statement(b"reference,amount\nTON-1,cinq mille\n", StatementFormat::Csv);
+        assert!(matches!(invalid_amount, Err(AppError::ValidationError(_))));
+
+        let invalid_date = parse_statement(b"reference,amount,date\nTON-1,5000,15/01/2025\n", StatementFormat::Csv);
+        assert!(matches!(invalid_date, Err(AppError::ValidationError(_))));
+
+        assert!(parse_statement(b"", StatementFormat::Csv).is_err());
+        assert!(parse_statement(br#"{"total": 3}"#, StatementFormat::Json).is_err());
+        assert!(parse_statement(b"[1, 2]", StatementFormat::Json).is_err());
+    }
+}
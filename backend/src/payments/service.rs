@@ -0,0 +1,304 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::model::contributions::CreateContribution;
+use crate::model::payment_transactions::{ContributionPayment, PaymentDirection, PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+use crate::model::payouts::RoundDisbursement;
+use crate::repositories::contributions_repository::ContributionRepository;
+use crate::repositories::payment_transaction_repository::PaymentTransactionRepository;
+use crate::repositories::payout_repository::PayoutRepository;
+use crate::repositories::user_repository::UserRepository;
+
+use super::{CallbackEvent, PaymentProvider, PaymentRequest, PaymentProviders, ProviderResponse};
+
+pub struct PaymentService;
+
+impl PaymentService {
+    /// Enregistre la cotisation et son paiement en attente, puis demande le débit à l'opérateur.
+    /// Si l'opérateur refuse la demande, le paiement et la cotisation passent en échec.
+    pub async fn collect_contribution(
+        pool: &PgPool,
+        providers: &PaymentProviders,
+        contribution_data: &CreateContribution,
+        member_user_id: Uuid,
+    ) -> Result<ContributionPayment, AppError> {
+        let kind = contribution_data.provider.unwrap_or(providers.default_kind());
+        let provider = providers.get(kind)?;
+
+        let phone_number = match contribution_data.phone_number.as_deref().map(str::trim) {
+            Some(phone) if !phone.is_empty() => phone.to_string(),
+            _ => UserRepository::find_by_id(pool, member_user_id).await?.phone,
+        };
+
+        let pending = ContributionRepository::create_pending_payment(
+            pool,
+            contribution_data,
+            kind,
+            providers.currency(),
+            &phone_number,
+        ).await?;
+
+        let request = PaymentRequest {
+            payment_reference: pending.payment.payment_reference.clone(),
+            amount: pending.payment.amount,
+            currency: providers.currency().to_string(),
+            phone_number,
+            description: "Cotisation tontine".to_string(),
+            callback_url: providers.callback_url(kind),
+            provider_reference: None,
+        };
+
+        match provider.initiate_collection(&request).await {
+            Ok(response) => {
+                let payment = Self::apply(pool, pending.payment.id, response).await?;
+                Self::with_contribution(pool, payment).await
+            }
+            Err(e) => {
+                Self::apply(pool, pending.payment.id, ProviderResponse {
+                    status: PaymentTransactionStatus::Failed,
+                    provider_reference: None,
+                    raw: serde_json::json!({ "error": e.to_string() }),
+                }).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Demande le versement mobile money d'un tour au téléphone de son bénéficiaire, puis fait
+    /// un premier envoi. Un échec temporaire est rejoué par le planificateur ; un refus de
+    /// l'opérateur rend le tour à nouveau payable.
+    pub async fn disburse_round(
+        pool: &PgPool,
+        providers: &PaymentProviders,
+        round_id: Uuid,
+        paid_by: Uuid,
+        provider: Option<PaymentProviderKind>,
+    ) -> Result<RoundDisbursement, AppError> {
+        let kind = provider.unwrap_or(providers.default_kind());
+        let provider = providers.disburser(kind)?;
+
+        let mut disbursement = PayoutRepository::start_disbursement(pool, round_id, paid_by, kind, providers.currency()).await?;
+
+        match Self::send_disbursement(pool, providers, provider.as_ref(), &mut disbursement.payment).await {
+            Ok(response) => {
+                disbursement.payment = Self::apply(pool, disbursement.payment.id, response).await?;
+                if disbursement.payment.status == String::from(PaymentTransactionStatus::Failed) {
+                    return Err(AppError::PaymentProviderError("Versement refusé par l'opérateur".to_string()));
+                }
+            }
+            Err(e) => eprintln!("[paiement] Versement du tour {} à vérifier et réessayer : {}", round_id, e),
+        }
+
+        Ok(disbursement)
+    }
+
+    /// Tâche planifiée du versement d'un tour : le versement est d'abord recherché chez l'opérateur,
+    /// puisque la réponse à un envoi précédent a pu être perdue, et n'est envoyé que s'il ne l'a
+    /// jamais reçu. Une erreur fait rejouer la tâche plus tard, y compris tant que l'opérateur
+    /// n'a pas confirmé.
+    pub async fn process_disbursement(pool: &PgPool, providers: &PaymentProviders, round_id: Uuid) -> Result<(), AppError> {
+        let Some(mut payment) = PaymentTransactionRepository::find_pending_disbursement(pool, round_id).await? else {
+            return Ok(());
+        };
+
+        let kind = PaymentProviderKind::try_from(payment.provider.clone()).map_err(AppError::InternalServerError)?;
+        let provider = providers.get(kind)?;
+
+        let response = match provider.lookup_disbursement(&payment).await? {
+            Some(response) => response,
+            None => Self::send_disbursement(pool, providers, provider.as_ref(), &mut payment).await?,
+        };
+
+        let payment = Self::apply(pool, payment.id, response).await?;
+        if payment.status == String::from(PaymentTransactionStatus::Pending) {
+            return Err(AppError::PaymentProviderError("Versement en attente de confirmation de l'opérateur".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Essais épuisés : seul un versement que l'opérateur déclare ne pas connaître échoue et rend
+    /// le tour payable. S'il le connaît, son statut est appliqué ; si la consultation échoue, le
+    /// versement reste en attente, à vérifier par `/payments/{id}/refresh` ou par le rapprochement.
+    pub async fn abandon_disbursement(
+        pool: &PgPool,
+        providers: &PaymentProviders,
+        round_id: Uuid,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        let Some(payment) = PaymentTransactionRepository::find_pending_disbursement(pool, round_id).await? else {
+            return Ok(());
+        };
+
+        let kind = PaymentProviderKind::try_from(payment.provider.clone()).map_err(AppError::InternalServerError)?;
+        let provider = providers.get(kind)?;
+
+        let response = match provider.lookup_disbursement(&payment).await {
+            Ok(Some(response)) => response,
+            Ok(None) => ProviderResponse {
+                status: PaymentTransactionStatus::Failed,
+                provider_reference: None,
+                raw: serde_json::json!({ "error": reason }),
+            },
+            Err(e) => {
+                eprintln!("[paiement] Statut du versement {} du tour {} inconnu : {}", payment.id, round_id, e);
+                return Ok(());
+            }
+        };
+
+        let payment = Self::apply(pool, payment.id, response).await?;
+        if payment.status == String::from(PaymentTransactionStatus::Pending) {
+            eprintln!("[paiement] Versement {} du tour {} toujours non confirmé", payment.id, round_id);
+        }
+
+        Ok(())
+    }
+
+    /// Réserve si besoin la référence de l'opérateur et l'enregistre avant l'envoi, puis envoie
+    /// le versement
+    async fn send_disbursement(
+        pool: &PgPool,
+        providers: &PaymentProviders,
+        provider: &dyn PaymentProvider,
+        payment: &mut PaymentTransaction,
+    ) -> Result<ProviderResponse, AppError> {
+        let phone_number = payment.metadata.as_ref()
+            .and_then(|metadata| metadata.get("phone_number"))
+            .and_then(|phone| phone.as_str())
+            .ok_or_else(|| AppError::InternalServerError(format!("Versement {} sans numéro de téléphone", payment.id)))?;
+
+        let mut request = PaymentRequest {
+            payment_reference: payment.payment_reference.clone(),
+            amount: payment.amount,
+            currency: payment.currency.clone().unwrap_or_else(|| providers.currency().to_string()),
+            phone_number: phone_number.to_string(),
+            description: "Versement tontine".to_string(),
+            callback_url: providers.callback_url(provider.kind()),
+            provider_reference: payment.provider_reference.clone(),
+        };
+
+        if request.provider_reference.is_none()
+            && let Some(reference) = provider.reserve_disbursement(&request).await?
+        {
+            *payment = PaymentTransactionRepository::set_provider_reference(pool, payment.id, &reference).await?;
+            request.provider_reference = Some(reference);
+        }
+
+        provider.initiate_disbursement(&request).await
+    }
+
+    /// Interroge l'opérateur sur un paiement encore en attente
+    pub async fn refresh(pool: &PgPool, providers: &PaymentProviders, payment_id: Uuid) -> Result<PaymentTransaction, AppError> {
+        let payment = PaymentTransactionRepository::find_by_id(pool, payment_id).await?;
+        if payment.status != String::from(PaymentTransactionStatus::Pending) {
+            return Ok(payment);
+        }
+
+        let kind = PaymentProviderKind::try_from(payment.provider.clone()).map_err(AppError::InternalServerError)?;
+        let response = providers.get(kind)?.query_status(&payment).await?;
+
+        Self::apply(pool, payment.id, response).await
+    }
+
+    /// Notification de l'opérateur, dont la signature a été vérifiée : la notification est conservée
+    /// et le paiement, la cotisation ou le tour et la transaction liées sont mis à jour dans une
+    /// seule transaction
+    pub async fn handle_callback(
+        pool: &PgPool,
+        kind: PaymentProviderKind,
+        event: CallbackEvent,
+    ) -> Result<PaymentTransaction, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let payment = PaymentTransactionRepository::lock_by_reference(
+            &mut tx,
+            kind,
+            event.payment_reference.as_deref(),
+            event.response.provider_reference.as_deref(),
+        ).await?;
+        PaymentTransactionRepository::record_callback(&mut tx, payment.id, &event.response.raw).await?;
+
+        let result = Self::apply_in(&mut tx, payment, event.response).await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Applique la réponse de l'opérateur dans sa propre transaction
+    pub async fn apply(pool: &PgPool, payment_id: Uuid, response: ProviderResponse) -> Result<PaymentTransaction, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let payment = PaymentTransactionRepository::lock(&mut tx, payment_id).await?;
+        let result = Self::apply_in(&mut tx, payment, response).await?;
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Seul un paiement en attente change de statut : une réponse répétée ou tardive est
+    /// enregistrée sans effet. Le paiement doit avoir été verrouillé par l'appelant.
+    async fn apply_in(
+        conn: &mut PgConnection,
+        payment: PaymentTransaction,
+        response: ProviderResponse,
+    ) -> Result<PaymentTransaction, AppError> {
+        let direction = PaymentDirection::try_from(payment.direction.clone()).map_err(AppError::InternalServerError)?;
+
+        let was_pending = payment.status == String::from(PaymentTransactionStatus::Pending);
+        let status = if was_pending {
+            response.status
+        } else {
+            PaymentTransactionStatus::try_from(payment.status.clone()).map_err(AppError::InternalServerError)?
+        };
+
+        let updated = PaymentTransactionRepository::record_response(
+            conn,
+            payment.id,
+            status,
+            response.provider_reference.as_deref(),
+            &response.raw,
+        ).await?;
+
+        if !was_pending || status == PaymentTransactionStatus::Pending {
+            return Ok(updated);
+        }
+
+        let successful = status == PaymentTransactionStatus::Successful;
+
+        match direction {
+            PaymentDirection::Collection => {
+                let contribution_id = updated.contribution_id
+                    .ok_or_else(|| AppError::InternalServerError(format!("Paiement {} sans cotisation", updated.id)))?;
+                // La cotisation réglée journalise sa transaction : portefeuille du membre → pot de la tontine
+                ContributionRepository::settle_payment(conn, contribution_id, successful).await?;
+            }
+            PaymentDirection::Disbursement => {
+                let (round_id, transaction_id) = updated.tontine_round_id.zip(updated.transaction_id)
+                    .ok_or_else(|| AppError::InternalServerError(format!("Versement {} sans tour", updated.id)))?;
+
+                // Pot de la tontine → portefeuille du bénéficiaire, ou retour du tour à l'état payable
+                if successful {
+                    PayoutRepository::confirm_disbursement(conn, round_id, transaction_id).await?;
+                } else {
+                    PayoutRepository::revert_disbursement(conn, round_id, transaction_id).await?;
+                }
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Paiement d'une cotisation accompagné de son état courant
+    pub async fn with_contribution(pool: &PgPool, payment: PaymentTransaction) -> Result<ContributionPayment, AppError> {
+        let contribution_id = payment.contribution_id
+            .ok_or_else(|| AppError::InternalServerError(format!("Paiement {} sans cotisation", payment.id)))?;
+
+        let mut conn = pool.acquire().await?;
+        let contribution = ContributionRepository::get(&mut conn, contribution_id).await?;
+
+        Ok(ContributionPayment { contribution, payment })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::model::beneficiary_draws::BeneficiaryDraw;
+use crate::errors::AppError;
+
+const DRAW_COLUMNS: &str = "id, tontine_id, tontine_round_id, strategy, seed, candidates, result, drawn_by, created_at";
+
+pub struct BeneficiaryDrawRepository;
+
+impl BeneficiaryDrawRepository {
+    /// Enregistre un tirage dans la transaction de l'appelant, avec la stratégie courante de la tontine
+    pub async fn create(
+        conn: &mut PgConnection,
+        tontine_id: Uuid,
+        tontine_round_id: Option<Uuid>,
+        seed: &str,
+        candidates: &[Uuid],
+        result: &[Uuid],
+        drawn_by: Option<Uuid>,
+    ) -> Result<BeneficiaryDraw, AppError> {
+        let draw = sqlx::query_as::<_, BeneficiaryDraw>(&format!(
+            "INSERT INTO beneficiary_draws (tontine_id, tontine_round_id, strategy, seed, candidates, result, drawn_by)
+             SELECT $1, $2, payout_strategy, $3, $4, $5, $6 FROM tontines WHERE id = $1
+             RETURNING {}",
+            DRAW_COLUMNS
+        ))
+        .bind(tontine_id)
+        .bind(tontine_round_id)
+        .bind(seed)
+        .bind(candidates)
+        .bind(result)
+        .bind(drawn_by)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(draw)
+    }
+
+    pub async fn find_by_tontine(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<BeneficiaryDraw>, AppError> {
+        let draws = sqlx::query_as::<_, BeneficiaryDraw>(&format!(
+            "SELECT {} FROM beneficiary_draws WHERE tontine_id = $1 ORDER BY created_at",
+            DRAW_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(draws)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, tontine_id: Uuid, draw_id: Uuid) -> Result<BeneficiaryDraw, AppError> {
+        let draw = sqlx::query_as::<_, BeneficiaryDraw>(&format!(
+            "SELECT {} FROM beneficiary_draws WHERE id = $1 AND tontine_id = $2",
+            DRAW_COLUMNS
+        ))
+        .bind(draw_id)
+        .bind(tontine_id)
+        .fetch_optional(pool)
+        .await?;
+
+        draw.ok_or_else(|| AppError::NotFound(format!("Tirage avec l'ID {} non trouvé", draw_id)))
+    }
+}
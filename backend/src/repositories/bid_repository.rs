@@ -0,0 +1,443 @@
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+use chrono::Utc;
+use rust_decimal::Decimal;
+
+use crate::model::bids::{Bid, BidResolution, BidStatus, BidWindow, BiddingState, OpenBidding};
+use crate::model::tontine::{BidDiscountPolicy, PayoutStrategy, TontineStatus};
+use crate::model::tontine_rounds::{RoundStatus, TontineRound};
+use crate::model::transactions::{Transaction, TransactionStatus, TransactionType};
+use crate::repositories::ledger_repository::LedgerRepository;
+use crate::errors::AppError;
+
+const BID_COLUMNS: &str = "id, tontine_round_id, member_id, user_id, amount, status, created_at, updated_at";
+const WINDOW_COLUMNS: &str = "tontine_round_id, opened_by, min_bid, closes_at, status, resolved_at, created_at";
+
+/// Tour mis aux enchères, verrouillé pour la durée de la transaction
+struct AuctionRound {
+    tontine_id: Uuid,
+    round_number: i32,
+    amount: Decimal,
+    bid_discount_policy: BidDiscountPolicy,
+}
+
+pub struct BidRepository;
+
+impl BidRepository {
+    /// Ouvre (ou prolonge) la fenêtre d'enchères d'un tour
+    pub async fn open_window(
+        pool: &PgPool,
+        round_id: Uuid,
+        opened_by: Uuid,
+        bidding_data: &OpenBidding,
+    ) -> Result<BidWindow, AppError> {
+        let mut tx = pool.begin().await?;
+        let round = Self::lock_auction_round(&mut tx, round_id).await?;
+
+        let min_bid = bidding_data.min_bid.unwrap_or(Decimal::ZERO);
+        if bidding_data.closes_at <= Utc::now() {
+            return Err(AppError::ValidationError("La date de clôture des enchères doit être dans le futur".to_string()));
+        }
+        if min_bid < Decimal::ZERO || min_bid >= round.amount {
+            return Err(AppError::ValidationError(format!(
+                "L'offre minimale doit être comprise entre 0 et le montant du tour ({})",
+                round.amount
+            )));
+        }
+
+        let window = sqlx::query_as::<_, BidWindow>(&format!(
+            "INSERT INTO bid_windows (tontine_round_id, opened_by, min_bid, closes_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (tontine_round_id) DO UPDATE
+             SET opened_by = EXCLUDED.opened_by, min_bid = EXCLUDED.min_bid, closes_at = EXCLUDED.closes_at
+             RETURNING {}",
+            WINDOW_COLUMNS
+        ))
+        .bind(round_id)
+        .bind(opened_by)
+        .bind(min_bid)
+        .bind(bidding_data.closes_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(window)
+    }
+
+    /// Clôt la fenêtre immédiatement ; l'enchère peut ensuite être résolue
+    pub async fn close_window(pool: &PgPool, round_id: Uuid) -> Result<BidWindow, AppError> {
+        let window = sqlx::query_as::<_, BidWindow>(&format!(
+            "UPDATE bid_windows SET closes_at = LEAST(closes_at, NOW())
+             WHERE tontine_round_id = $1 AND status = 'open'
+             RETURNING {}",
+            WINDOW_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_optional(pool)
+        .await?;
+
+        window.ok_or_else(|| AppError::ValidationError("Aucune fenêtre d'enchères ouverte pour ce tour".to_string()))
+    }
+
+    /// Fenêtre et offres du tour ; `only_user` limite les offres à celles d'un membre
+    pub async fn find_state(pool: &PgPool, round_id: Uuid, only_user: Option<Uuid>) -> Result<BiddingState, AppError> {
+        let window = sqlx::query_as::<_, BidWindow>(&format!(
+            "SELECT {} FROM bid_windows WHERE tontine_round_id = $1",
+            WINDOW_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_optional(pool)
+        .await?;
+
+        let bids = sqlx::query_as::<_, Bid>(&format!(
+            "SELECT {} FROM bids
+             WHERE tontine_round_id = $1 AND ($2::uuid IS NULL OR user_id = $2)
+             ORDER BY amount DESC, created_at",
+            BID_COLUMNS
+        ))
+        .bind(round_id)
+        .bind(only_user)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(BiddingState { window, bids })
+    }
+
+    /// Dépose ou modifie l'offre du membre pendant la fenêtre d'enchères
+    pub async fn submit(pool: &PgPool, round_id: Uuid, user_id: Uuid, amount: Decimal) -> Result<Bid, AppError> {
+        let mut tx = pool.begin().await?;
+        let round = Self::lock_auction_round(&mut tx, round_id).await?;
+        let window = Self::open_window_for_bids(&mut tx, round_id).await?;
+
+        if amount < window.min_bid || amount < Decimal::ZERO {
+            return Err(AppError::ValidationError(format!("L'offre doit être d'au moins {}", window.min_bid)));
+        }
+        if amount >= round.amount {
+            return Err(AppError::ValidationError(format!(
+                "L'offre doit être inférieure au montant du tour ({})",
+                round.amount
+            )));
+        }
+
+        let member_id: Uuid = sqlx::query_scalar(
+            "SELECT id FROM tontine_members WHERE tontine_id = $1 AND user_id = $2 AND is_active = true"
+        )
+        .bind(round.tontine_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::Forbidden("Seuls les membres actifs de la tontine peuvent enchérir".to_string()))?;
+
+        if Self::assigned_beneficiaries(&mut tx, round.tontine_id).await?.contains(&user_id) {
+            return Err(AppError::ValidationError("Vous avez déjà un tour attribué dans cette tontine".to_string()));
+        }
+
+        let updated = sqlx::query_as::<_, Bid>(&format!(
+            "UPDATE bids SET amount = $1, updated_at = $2
+             WHERE tontine_round_id = $3 AND member_id = $4 AND status = 'active'
+             RETURNING {}",
+            BID_COLUMNS
+        ))
+        .bind(amount)
+        .bind(Utc::now())
+        .bind(round_id)
+        .bind(member_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let bid = match updated {
+            Some(bid) => bid,
+            None => sqlx::query_as::<_, Bid>(&format!(
+                "INSERT INTO bids (tontine_round_id, member_id, user_id, amount)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING {}",
+                BID_COLUMNS
+            ))
+            .bind(round_id)
+            .bind(member_id)
+            .bind(user_id)
+            .bind(amount)
+            .fetch_one(&mut *tx)
+            .await?,
+        };
+
+        tx.commit().await?;
+
+        Ok(bid)
+    }
+
+    /// Retire l'offre active du membre tant que la fenêtre est ouverte
+    pub async fn withdraw(pool: &PgPool, round_id: Uuid, user_id: Uuid) -> Result<Bid, AppError> {
+        let mut tx = pool.begin().await?;
+        Self::lock_auction_round(&mut tx, round_id).await?;
+        Self::open_window_for_bids(&mut tx, round_id).await?;
+
+        let bid = sqlx::query_as::<_, Bid>(&format!(
+            "UPDATE bids SET status = $1, updated_at = $2
+             WHERE tontine_round_id = $3 AND user_id = $4 AND status = 'active'
+             RETURNING {}",
+            BID_COLUMNS
+        ))
+        .bind(String::from(BidStatus::Withdrawn))
+        .bind(Utc::now())
+        .bind(round_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Aucune offre active pour ce tour".to_string()))?;
+
+        tx.commit().await?;
+
+        Ok(bid)
+    }
+
+    /// Désigne le plus offrant (à égalité, la première offre) comme bénéficiaire du tour.
+    /// La remise est enregistrée en transaction `bid_discount` et, selon la politique de la
+    /// tontine, partagée entre les autres membres actifs (transactions `bid_dividend` en attente).
+    /// Sans offre, le tour n'est attribué d'office que s'il ne reste qu'un seul membre sans tour.
+    pub async fn resolve(pool: &PgPool, round_id: Uuid) -> Result<BidResolution, AppError> {
+        let mut tx = pool.begin().await?;
+        let round = Self::lock_auction_round(&mut tx, round_id).await?;
+
+        let window = sqlx::query_as::<_, BidWindow>(&format!(
+            "SELECT {} FROM bid_windows WHERE tontine_round_id = $1 AND status = 'open' FOR UPDATE",
+            WINDOW_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::ValidationError("Aucune fenêtre d'enchères ouverte pour ce tour".to_string()))?;
+
+        if window.closes_at > Utc::now() {
+            return Err(AppError::ValidationError(format!(
+                "Les enchères sont ouvertes jusqu'au {}",
+                window.closes_at.to_rfc3339()
+            )));
+        }
+
+        let assigned = Self::assigned_beneficiaries(&mut tx, round.tontine_id).await?;
+
+        // Un membre ayant obtenu un autre tour entre-temps ne peut plus gagner
+        let winning_bid = sqlx::query_as::<_, Bid>(&format!(
+            "SELECT {} FROM bids b
+             WHERE b.tontine_round_id = $1 AND b.status = 'active' AND NOT (b.user_id = ANY($2))
+               AND EXISTS (SELECT 1 FROM tontine_members tm WHERE tm.id = b.member_id AND tm.is_active = true)
+             ORDER BY b.amount DESC, b.created_at
+             LIMIT 1",
+            BID_COLUMNS
+        ))
+        .bind(round_id)
+        .bind(&assigned)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let active_members: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM tontine_members WHERE tontine_id = $1 AND is_active = true ORDER BY position_order NULLS LAST, join_date, id"
+        )
+        .bind(round.tontine_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let (winner, discount) = match &winning_bid {
+            Some(bid) => (bid.user_id, bid.amount),
+            None => {
+                let remaining: Vec<Uuid> = active_members.iter()
+                    .filter(|user_id| !assigned.contains(user_id))
+                    .copied()
+                    .collect();
+                match remaining.as_slice() {
+                    [only] => (*only, Decimal::ZERO),
+                    _ => return Err(AppError::ValidationError(
+                        "Aucune offre valide : rouvrez la fenêtre d'enchères".to_string(),
+                    )),
+                }
+            }
+        };
+
+        let now = Utc::now();
+        sqlx::query(
+            "UPDATE bids SET status = CASE WHEN id = $1 THEN 'won' ELSE 'lost' END, updated_at = $2
+             WHERE tontine_round_id = $3 AND status = 'active'"
+        )
+        .bind(winning_bid.as_ref().map(|bid| bid.id))
+        .bind(now)
+        .bind(round_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let winning_bid = match winning_bid {
+            Some(bid) => Some(sqlx::query_as::<_, Bid>(&format!("SELECT {} FROM bids WHERE id = $1", BID_COLUMNS))
+                .bind(bid.id)
+                .fetch_one(&mut *tx)
+                .await?),
+            None => None,
+        };
+
+        let updated_round = sqlx::query_as::<_, TontineRound>(
+            "UPDATE tontine_rounds SET beneficiary_user_id = $1 WHERE id = $2
+             RETURNING id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at"
+        )
+        .bind(winner)
+        .bind(round_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut discount_transaction = None;
+        let mut dividends = Vec::new();
+
+        if discount > Decimal::ZERO {
+            discount_transaction = Some(Self::insert_transaction(
+                &mut tx,
+                round.tontine_id,
+                Some(winner),
+                None,
+                discount,
+                TransactionType::BidDiscount,
+                format!("Remise d'enchère - tour {}", round.round_number),
+            ).await?);
+
+            if round.bid_discount_policy == BidDiscountPolicy::Redistribute {
+                let others: Vec<Uuid> = active_members.into_iter().filter(|user_id| *user_id != winner).collect();
+                if !others.is_empty() {
+                    let share = (discount / Decimal::from(others.len() as i64)).round_dp(2);
+                    // L'arrondi éventuel revient au premier membre
+                    let remainder = discount - share * Decimal::from(others.len() as i64);
+
+                    for (index, user_id) in others.into_iter().enumerate() {
+                        let amount = if index == 0 { share + remainder } else { share };
+                        if amount <= Decimal::ZERO {
+                            continue;
+                        }
+                        dividends.push(Self::insert_transaction(
+                            &mut tx,
+                            round.tontine_id,
+                            None,
+                            Some(user_id),
+                            amount,
+                            TransactionType::BidDividend,
+                            format!("Part de la remise d'enchère - tour {}", round.round_number),
+                        ).await?);
+                    }
+                }
+            }
+        }
+
+        sqlx::query("UPDATE bid_windows SET status = 'resolved', resolved_at = $1 WHERE tontine_round_id = $2")
+            .bind(now)
+            .bind(round_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(BidResolution {
+            net_payout: round.amount - discount,
+            round: updated_round,
+            winning_bid,
+            discount,
+            discount_transaction,
+            dividends,
+        })
+    }
+
+    /// Vérifie que le tour peut être mis aux enchères : tontine en cours à la stratégie
+    /// `auction`, tour en attente et sans bénéficiaire
+    async fn lock_auction_round(conn: &mut PgConnection, round_id: Uuid) -> Result<AuctionRound, AppError> {
+        let row = sqlx::query(
+            "SELECT tr.tontine_id, tr.round_number, tr.amount, tr.status, tr.beneficiary_user_id,
+                    t.status as tontine_status, t.payout_strategy, t.bid_discount_policy
+             FROM tontine_rounds tr
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tr.id = $1
+             FOR UPDATE OF tr"
+        )
+        .bind(round_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        if row.get::<String, _>("payout_strategy") != String::from(PayoutStrategy::Auction) {
+            return Err(AppError::ValidationError("Les bénéficiaires de cette tontine ne sont pas désignés par enchère".to_string()));
+        }
+        if row.get::<String, _>("tontine_status") != String::from(TontineStatus::Running)
+            || row.get::<String, _>("status") != String::from(RoundStatus::Pending)
+        {
+            return Err(AppError::ValidationError("Seul un tour en attente d'une tontine en cours peut être mis aux enchères".to_string()));
+        }
+        if row.get::<Option<Uuid>, _>("beneficiary_user_id").is_some() {
+            return Err(AppError::ValidationError("Le bénéficiaire de ce tour est déjà désigné".to_string()));
+        }
+
+        Ok(AuctionRound {
+            tontine_id: row.get("tontine_id"),
+            round_number: row.get("round_number"),
+            amount: row.get("amount"),
+            bid_discount_policy: BidDiscountPolicy::try_from(row.get::<String, _>("bid_discount_policy"))
+                .map_err(AppError::InternalServerError)?,
+        })
+    }
+
+    async fn open_window_for_bids(conn: &mut PgConnection, round_id: Uuid) -> Result<BidWindow, AppError> {
+        let window = sqlx::query_as::<_, BidWindow>(&format!(
+            "SELECT {} FROM bid_windows WHERE tontine_round_id = $1 AND status = 'open'",
+            WINDOW_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        match window {
+            Some(window) if window.closes_at > Utc::now() => Ok(window),
+            _ => Err(AppError::ValidationError("Les enchères ne sont pas ouvertes pour ce tour".to_string())),
+        }
+    }
+
+    /// Membres ayant déjà un tour attribué (hors tours annulés)
+    async fn assigned_beneficiaries(conn: &mut PgConnection, tontine_id: Uuid) -> Result<Vec<Uuid>, AppError> {
+        let users = sqlx::query_scalar(
+            "SELECT beneficiary_user_id FROM tontine_rounds
+             WHERE tontine_id = $1 AND beneficiary_user_id IS NOT NULL AND status <> 'cancelled'"
+        )
+        .bind(tontine_id)
+        .fetch_all(conn)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// La remise est acquise dès la résolution ; les parts reversées restent en attente de paiement
+    async fn insert_transaction(
+        conn: &mut PgConnection,
+        tontine_id: Uuid,
+        from_user_id: Option<Uuid>,
+        to_user_id: Option<Uuid>,
+        amount: Decimal,
+        transaction_type: TransactionType,
+        description: String,
+    ) -> Result<Transaction, AppError> {
+        let status = match transaction_type {
+            TransactionType::BidDividend => TransactionStatus::Pending,
+            _ => TransactionStatus::Completed,
+        };
+
+        let transaction = sqlx::query_as::<_, Transaction>(
+            "INSERT INTO transactions (tontine_id, from_user_id, to_user_id, amount, transaction_type, status, description)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, tontine_id, from_user_id, to_user_id, amount, transaction_type, status, description, created_at"
+        )
+        .bind(tontine_id)
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .bind(amount)
+        .bind(String::from(transaction_type))
+        .bind(String::from(status))
+        .bind(description)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        LedgerRepository::sync_transaction(conn, &transaction).await?;
+
+        Ok(transaction)
+    }
+}
@@ -1,14 +1,21 @@
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
 use uuid::Uuid;
 use chrono::{ Utc};
-
-use crate::model::contributions::{Contribution, CreateContribution, UpdateContribution, ContributionWithDetails};
+use rust_decimal::Decimal;
+
+use crate::model::contributions::{Contribution, CreateContribution, UpdateContribution, ContributionWithDetails, PaymentMethod, PaymentStatus};
+use crate::model::payment_transactions::{ContributionPayment, PaymentProviderKind};
+use crate::model::transactions::{CreateTransaction, TransactionStatus, TransactionType};
+use crate::repositories::payment_transaction_repository::PaymentTransactionRepository;
+use crate::repositories::transaction_repository::TransactionRepository;
+use crate::repositories::penalty_repository::PenaltyRepository;
 use crate::errors::AppError;
+use crate::validation::Validator;
 
 pub struct ContributionRepository;
 
 impl ContributionRepository {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<ContributionWithDetails>, AppError> {
+    pub async fn find_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<ContributionWithDetails>, AppError> {
         let contributions = sqlx::query(
             "SELECT c.*, u.full_name as member_name, tr.round_number, t.name as tontine_name
              FROM contributions c
@@ -16,8 +23,16 @@ impl ContributionRepository {
              JOIN users u ON tm.user_id = u.id
              JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
              JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tm.user_id = $1
+                OR t.id IN (
+                    SELECT id FROM tontines WHERE created_by = $1
+                    UNION
+                    SELECT tontine_id FROM tontine_members
+                    WHERE user_id = $1 AND is_active = true AND role IN ('owner', 'treasurer')
+                )
              ORDER BY c.created_at DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -137,12 +152,92 @@ impl ContributionRepository {
         Ok(contributions_with_details)
     }
 
-    pub async fn create(pool: &PgPool, contribution_data: &CreateContribution) -> Result<Contribution, AppError> {
+    /// Cotisation hors mobile money : payée si elle est enregistrée par le trésorier,
+    /// en attente de sa confirmation si le membre la déclare lui-même
+    pub async fn create(pool: &PgPool, contribution_data: &CreateContribution, payment_status: PaymentStatus) -> Result<Contribution, AppError> {
+        if matches!(contribution_data.payment_method, PaymentMethod::MobileMoney) {
+            return Err(AppError::ValidationError("Une cotisation mobile money doit passer par un opérateur de paiement".to_string()));
+        }
+
+        Self::check_new(pool, contribution_data).await?;
+
+        let mut tx = pool.begin().await?;
+
+        let paid = matches!(payment_status, PaymentStatus::Paid);
+        let contribution = Self::insert(&mut tx, contribution_data, payment_status).await?;
+
+        // Le versement qui complète une cotisation après l'échéance du tour est soumis à l'amende de retard
+        if paid {
+            PenaltyRepository::assess(&mut tx, contribution.id).await?;
+            Self::journal(&mut tx, &contribution).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(contribution)
+    }
+
+    /// Cotisation mobile money : enregistrée en attente, avec le paiement que l'opérateur
+    /// devra confirmer avant qu'elle soit marquée payée
+    pub async fn create_pending_payment(
+        pool: &PgPool,
+        contribution_data: &CreateContribution,
+        provider: PaymentProviderKind,
+        currency: &str,
+        phone_number: &str,
+    ) -> Result<ContributionPayment, AppError> {
+        Self::check_new(pool, contribution_data).await?;
+
+        let mut tx = pool.begin().await?;
+
+        let contribution = Self::insert(&mut tx, contribution_data, PaymentStatus::Pending).await?;
+
+        // Transaction de cotisation en attente, réglée avec le paiement
+        let member = sqlx::query("SELECT tontine_id, user_id FROM tontine_members WHERE id = $1")
+            .bind(contribution.member_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let transaction = TransactionRepository::insert(
+            &mut tx,
+            &CreateTransaction {
+                tontine_id: member.get("tontine_id"),
+                from_user_id: Some(member.get("user_id")),
+                to_user_id: None,
+                amount: contribution.amount,
+                transaction_type: TransactionType::Contribution,
+                description: Some("Cotisation mobile money".to_string()),
+            },
+            TransactionStatus::Pending,
+        ).await?;
+
+        Self::link_transaction(&mut tx, contribution.id, transaction.id).await?;
+
+        let payment = PaymentTransactionRepository::create(
+            &mut tx,
+            contribution.id,
+            transaction.id,
+            provider,
+            contribution.amount,
+            currency,
+            serde_json::json!({ "phone_number": phone_number }),
+        ).await?;
+
+        tx.commit().await?;
+
+        Ok(ContributionPayment { contribution, payment })
+    }
+
+    /// Vérifie que le round et le membre existent et que le membre participe au round
+    async fn check_new(pool: &PgPool, contribution_data: &CreateContribution) -> Result<(), AppError> {
+        let mut validator = Validator::default();
+        validator.positive("amount", contribution_data.amount);
+        validator.finish()?;
+
         // Vérifier si le round existe
         let round_exists = sqlx::query(
             "SELECT id, amount FROM tontine_rounds WHERE id = $1"
         )
-        .bind(&contribution_data.tontine_round_id)
+        .bind(contribution_data.tontine_round_id)
         .fetch_optional(pool)
         .await?;
 
@@ -154,7 +249,7 @@ impl ContributionRepository {
         let member_exists = sqlx::query(
             "SELECT id, tontine_id FROM tontine_members WHERE id = $1 AND is_active = true"
         )
-        .bind(&contribution_data.member_id)
+        .bind(contribution_data.member_id)
         .fetch_optional(pool)
         .await?;
 
@@ -169,8 +264,8 @@ impl ContributionRepository {
              JOIN tontine_rounds tr ON tm.tontine_id = tr.tontine_id
              WHERE tm.id = $1 AND tr.id = $2"
         )
-        .bind(&contribution_data.member_id)
-        .bind(&contribution_data.tontine_round_id)
+        .bind(contribution_data.member_id)
+        .bind(contribution_data.tontine_round_id)
         .fetch_optional(pool)
         .await?;
 
@@ -178,36 +273,155 @@ impl ContributionRepository {
             return Err(AppError::ValidationError("Le membre n'appartient pas à cette tontine/round".to_string()));
         }
 
-        // Vérifier si le membre a déjà cotisé pour ce round
-        let existing_contribution = sqlx::query(
-            "SELECT id FROM contributions WHERE tontine_round_id = $1 AND member_id = $2"
+        Ok(())
+    }
+
+    /// Insère le versement s'il respecte le reste à verser du membre pour ce round.
+    /// S'exécute dans la transaction de l'appelant.
+    async fn insert(
+        conn: &mut PgConnection,
+        contribution_data: &CreateContribution,
+        payment_status: PaymentStatus,
+    ) -> Result<Contribution, AppError> {
+        let payment_method_str: String = contribution_data.payment_method.clone().into();
+
+        // Les versements s'accumulent jusqu'au montant attendu du membre pour ce round
+        let balance = Self::member_round_balance(conn, contribution_data.tontine_round_id, contribution_data.member_id, None).await?;
+        if balance.remaining() <= Decimal::ZERO {
+            return Err(AppError::ValidationError("Le membre a déjà réglé sa cotisation pour ce round".to_string()));
+        }
+        balance.validate(contribution_data.amount)?;
+
+        let contribution = sqlx::query_as::<_, Contribution>(
+            "INSERT INTO contributions (tontine_round_id, member_id, amount, payment_method, payment_status) 
+             VALUES ($1, $2, $3, $4, $5) 
+             RETURNING id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at"
         )
-        .bind(&contribution_data.tontine_round_id)
-        .bind(&contribution_data.member_id)
-        .fetch_optional(pool)
+        .bind(contribution_data.tontine_round_id)
+        .bind(contribution_data.member_id)
+        .bind(contribution_data.amount)
+        .bind(&payment_method_str)
+        .bind(String::from(payment_status))
+        .fetch_one(&mut *conn)
         .await?;
 
-        if existing_contribution.is_some() {
-            return Err(AppError::ValidationError("Le membre a déjà cotisé pour ce round".to_string()));
-        }
+        Ok(contribution)
+    }
 
-        let payment_method_str: String = contribution_data.payment_method.clone().into();
+    /// Cotisation telle qu'enregistrée, dans la transaction de l'appelant
+    pub async fn get(conn: &mut PgConnection, contribution_id: Uuid) -> Result<Contribution, AppError> {
+        let contribution = sqlx::query_as::<_, Contribution>(
+            "SELECT id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at
+             FROM contributions WHERE id = $1"
+        )
+        .bind(contribution_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        contribution.ok_or_else(|| AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id)))
+    }
+
+    /// Solde une cotisation mobile money selon la réponse définitive de l'opérateur :
+    /// payée (avec contrôle de l'amende de retard) ou échouée.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn settle_payment(conn: &mut PgConnection, contribution_id: Uuid, paid: bool) -> Result<Contribution, AppError> {
+        let status = if paid { PaymentStatus::Paid } else { PaymentStatus::Failed };
 
         let contribution = sqlx::query_as::<_, Contribution>(
-            "INSERT INTO contributions (tontine_round_id, member_id, amount, payment_method) 
-             VALUES ($1, $2, $3, $4) 
+            "UPDATE contributions
+             SET payment_status = $2,
+                 payment_date = CASE WHEN $2 = 'paid' THEN NOW() ELSE payment_date END
+             WHERE id = $1 AND payment_status IN ('pending', 'late')
              RETURNING id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at"
         )
-        .bind(&contribution_data.tontine_round_id)
-        .bind(&contribution_data.member_id)
-        .bind(&contribution_data.amount)
-        .bind(&payment_method_str)
-        .fetch_one(pool)
+        .bind(contribution_id)
+        .bind(String::from(status))
+        .fetch_optional(&mut *conn)
         .await?;
 
+        // Déjà soldée autrement (suppression du retard, saisie manuelle) : rien à changer
+        let Some(contribution) = contribution else {
+            return Self::get(conn, contribution_id).await;
+        };
+
+        if paid {
+            PenaltyRepository::assess(conn, contribution.id).await?;
+        }
+        Self::journal(conn, &contribution).await?;
+
         Ok(contribution)
     }
 
+    /// Aligne la transaction 'contribution' sur le statut de la cotisation : créée et journalisée
+    /// au paiement, contre-passée (échouée) si la cotisation n'est plus payée ou si son montant change.
+    /// S'exécute dans la transaction de l'appelant.
+    async fn journal(conn: &mut PgConnection, contribution: &Contribution) -> Result<(), AppError> {
+        let row = sqlx::query(
+            "SELECT tm.tontine_id, tm.user_id, t.id as transaction_id, t.amount as transaction_amount
+             FROM contributions c
+             JOIN tontine_members tm ON c.member_id = tm.id
+             LEFT JOIN transactions t ON c.transaction_id = t.id
+             WHERE c.id = $1"
+        )
+        .bind(contribution.id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let transaction_id: Option<Uuid> = row.get("transaction_id");
+        let transaction_amount: Option<Decimal> = row.get("transaction_amount");
+        let status = match PaymentStatus::try_from(contribution.payment_status.clone()).map_err(AppError::InternalServerError)? {
+            PaymentStatus::Paid => TransactionStatus::Completed,
+            PaymentStatus::Failed => TransactionStatus::Failed,
+            PaymentStatus::Pending | PaymentStatus::Late => TransactionStatus::Pending,
+        };
+
+        if let Some(transaction_id) = transaction_id {
+            if transaction_amount == Some(contribution.amount) {
+                TransactionRepository::set_status(conn, transaction_id, status).await?;
+                return Ok(());
+            }
+            // Montant modifié : l'ancienne écriture est contre-passée et remplacée
+            TransactionRepository::set_status(conn, transaction_id, TransactionStatus::Failed).await?;
+        }
+
+        if !matches!(status, TransactionStatus::Completed) {
+            return Ok(());
+        }
+
+        let transaction = TransactionRepository::insert(
+            conn,
+            &CreateTransaction {
+                tontine_id: row.get("tontine_id"),
+                from_user_id: Some(row.get("user_id")),
+                to_user_id: None,
+                amount: contribution.amount,
+                transaction_type: TransactionType::Contribution,
+                description: Some("Cotisation tontine".to_string()),
+            },
+            TransactionStatus::Completed,
+        ).await?;
+        Self::link_transaction(conn, contribution.id, transaction.id).await
+    }
+
+    async fn link_transaction(conn: &mut PgConnection, contribution_id: Uuid, transaction_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE contributions SET transaction_id = $1 WHERE id = $2")
+            .bind(transaction_id)
+            .bind(contribution_id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Le statut payé d'une cotisation mobile money ne vient que de l'opérateur
+    fn ensure_not_mobile_money(payment_method: Option<&str>) -> Result<(), AppError> {
+        if payment_method == Some("mobile_money") {
+            return Err(AppError::ValidationError(
+                "Une cotisation mobile money est marquée payée à la confirmation de l'opérateur".to_string()
+            ));
+        }
+        Ok(())
+    }
+
 
     pub async fn update(pool: &PgPool, contribution_id: Uuid, contribution_data: &UpdateContribution) -> Result<Contribution, AppError> {
     // Vérifier si la contribution existe
@@ -224,6 +438,13 @@ impl ContributionRepository {
         None => return Err(AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id))),
     };
 
+    let mut validator = Validator::default();
+    validator.positive_opt("amount", contribution_data.amount);
+    validator.finish()?;
+
+    let existing_method = existing.payment_method.clone();
+    let was_paid = existing.payment_status == "paid";
+
     // Utiliser les nouvelles valeurs ou conserver les anciennes
     let amount = contribution_data.amount.unwrap_or(existing.amount);
     let payment_method = match &contribution_data.payment_method {
@@ -241,9 +462,22 @@ impl ContributionRepository {
         None => existing.payment_status,
     };
 
+    if payment_status == "paid" && !was_paid {
+        Self::ensure_not_mobile_money(existing_method.as_deref())?;
+        Self::ensure_not_mobile_money(Some(payment_method.as_str()))?;
+    }
+
+    let mut tx = pool.begin().await?;
+
+    if payment_status != "failed" {
+        let balance = Self::member_round_balance(&mut tx, existing.tontine_round_id, existing.member_id, Some(contribution_id)).await?;
+        balance.validate(amount)?;
+    }
+
     let contribution = sqlx::query_as::<_, Contribution>(
         "UPDATE contributions 
-         SET amount = $1, payment_method = $2, payment_status = $3
+         SET amount = $1, payment_method = $2, payment_status = $3,
+             payment_date = CASE WHEN $3 = 'paid' AND payment_status <> 'paid' THEN NOW() ELSE payment_date END
          WHERE id = $4 
          RETURNING id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at"
     )
@@ -251,79 +485,238 @@ impl ContributionRepository {
     .bind(payment_method)
     .bind(payment_status)
     .bind(contribution_id)
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    PenaltyRepository::assess(&mut tx, contribution.id).await?;
+    Self::journal(&mut tx, &contribution).await?;
+
+    tx.commit().await?;
+
     Ok(contribution)
 }
 
 
+    /// Supprime la cotisation ; sa transaction passe en échec et son écriture est contre-passée.
+    /// Une cotisation passée par un paiement mobile money est conservée avec son historique.
     pub async fn delete(pool: &PgPool, contribution_id: Uuid) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM contributions WHERE id = $1")
-            .bind(contribution_id)
-            .execute(pool)
-            .await?;
+        let mut tx = pool.begin().await?;
+
+        // Le verrou empêche un paiement d'être initié pendant la suppression
+        let has_payment: Option<bool> = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM payment_transactions WHERE contribution_id = c.id)
+             FROM contributions c WHERE c.id = $1 FOR UPDATE"
+        )
+        .bind(contribution_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match has_payment {
+            None => return Err(AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id))),
+            Some(true) => return Err(AppError::ValidationError(
+                "Une cotisation liée à un paiement mobile money ne peut pas être supprimée : marquez-la en échec".to_string()
+            )),
+            Some(false) => {}
+        }
+
+        let transaction_id: Option<Option<Uuid>> = sqlx::query_scalar(
+            "DELETE FROM contributions WHERE id = $1 RETURNING transaction_id"
+        )
+        .bind(contribution_id)
+        .fetch_optional(&mut *tx)
+        .await?;
 
-        if result.rows_affected() == 0 {
+        let Some(transaction_id) = transaction_id else {
             return Err(AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id)));
+        };
+
+        if let Some(transaction_id) = transaction_id {
+            TransactionRepository::set_status(&mut tx, transaction_id, TransactionStatus::Failed).await?;
         }
 
+        tx.commit().await?;
+
         Ok(())
     }
 
     pub async fn mark_as_paid(pool: &PgPool, contribution_id: Uuid) -> Result<Contribution, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let existing = Self::get(&mut tx, contribution_id).await?;
+        Self::ensure_not_mobile_money(existing.payment_method.as_deref())?;
+        Self::ensure_payable(&existing.payment_status)?;
+
+        // Verrouille le membre comme à l'enregistrement : le versement ne doit pas dépasser ce qui reste dû
+        let balance = Self::member_round_balance(&mut tx, existing.tontine_round_id, existing.member_id, Some(contribution_id)).await?;
+        balance.validate(existing.amount)?;
+
+        // Le statut est revérifié sous verrou : une saisie concurrente a pu la solder entre-temps
         let contribution = sqlx::query_as::<_, Contribution>(
             "UPDATE contributions 
              SET payment_status = 'paid', payment_date = $1 
-             WHERE id = $2 
+             WHERE id = $2 AND payment_status IN ('pending', 'late')
              RETURNING id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at"
         )
         .bind(Utc::now())
         .bind(contribution_id)
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
+        let Some(contribution) = contribution else {
+            let current = Self::get(&mut tx, contribution_id).await?;
+            Self::ensure_payable(&current.payment_status)?;
+            return Err(AppError::Conflict("La cotisation a été modifiée entre-temps, réessayez".to_string()));
+        };
+
+        PenaltyRepository::assess(&mut tx, contribution.id).await?;
+        Self::journal(&mut tx, &contribution).await?;
+
+        tx.commit().await?;
+
         Ok(contribution)
     }
 
+    /// Seule une cotisation en attente ou en retard peut être marquée payée, et une seule fois
+    fn ensure_payable(payment_status: &str) -> Result<(), AppError> {
+        if !matches!(payment_status, "pending" | "late") {
+            return Err(AppError::ValidationError(format!(
+                "Une cotisation au statut {} ne peut pas être marquée comme payée", payment_status
+            )));
+        }
+        Ok(())
+    }
+
     pub async fn mark_as_failed(pool: &PgPool, contribution_id: Uuid) -> Result<Contribution, AppError> {
+        let mut tx = pool.begin().await?;
+        let contribution = Self::set_failed(&mut tx, contribution_id).await?;
+        tx.commit().await?;
+        Ok(contribution)
+    }
+
+    /// Passe la cotisation en échec et contre-passe sa transaction, dans la transaction de l'appelant
+    pub async fn set_failed(conn: &mut PgConnection, contribution_id: Uuid) -> Result<Contribution, AppError> {
         let contribution = sqlx::query_as::<_, Contribution>(
             "UPDATE contributions 
              SET payment_status = 'failed'
-             WHERE id = $2 
+             WHERE id = $1 
              RETURNING id, tontine_round_id, member_id, amount, payment_date, payment_method, payment_status, created_at"
         )
         .bind(contribution_id)
-        .fetch_one(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
+        let contribution = contribution.ok_or_else(|| AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id)))?;
+        Self::journal(conn, &contribution).await?;
+
         Ok(contribution)
     }
 
+    /// Passe en retard les cotisations du tour encore impayées
+    pub async fn mark_late_for_round(pool: &PgPool, round_id: Uuid) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "UPDATE contributions SET payment_status = 'late' WHERE tontine_round_id = $1 AND payment_status = 'pending'"
+        )
+        .bind(round_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Montant attendu du membre pour le round et total de ses versements non échoués,
+    /// hors `exclude`. Verrouille le membre pour sérialiser les versements concurrents.
+    /// S'exécute dans la transaction de l'appelant.
+    async fn member_round_balance(
+        conn: &mut PgConnection,
+        round_id: Uuid,
+        member_id: Uuid,
+        exclude: Option<Uuid>,
+    ) -> Result<RoundBalance, AppError> {
+        sqlx::query("SELECT id FROM tontine_members WHERE id = $1 FOR UPDATE")
+            .bind(member_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let row = sqlx::query(
+            "SELECT t.amount_per_member, t.allow_partial_payments,
+                    (SELECT COALESCE(SUM(c.amount), 0) FROM contributions c
+                     WHERE c.tontine_round_id = tr.id AND c.member_id = $2
+                       AND c.payment_status <> 'failed'
+                       AND ($3::uuid IS NULL OR c.id <> $3)) as recorded
+             FROM tontine_rounds tr
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tr.id = $1"
+        )
+        .bind(round_id)
+        .bind(member_id)
+        .bind(exclude)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        Ok(RoundBalance {
+            expected: row.get("amount_per_member"),
+            recorded: row.get("recorded"),
+            allow_partial_payments: row.get("allow_partial_payments"),
+        })
+    }
+
     pub async fn get_round_summary(pool: &PgPool, round_id: Uuid) -> Result<RoundSummary, AppError> {
-        let summary = sqlx::query(
-            "SELECT 
-                COUNT(*) as total_members,
-                COUNT(c.id) as contributions_count,
-                COALESCE(SUM(c.amount), 0) as total_collected,
-                tr.amount as round_amount
+        let round = sqlx::query(
+            "SELECT tr.amount, t.amount_per_member
              FROM tontine_rounds tr
              JOIN tontines t ON tr.tontine_id = t.id
-             JOIN tontine_members tm ON t.id = tm.tontine_id AND tm.is_active = true
-             LEFT JOIN contributions c ON tr.id = c.tontine_round_id AND c.member_id = tm.id AND c.payment_status = 'paid'
+             WHERE tr.id = $1"
+        )
+        .bind(round_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        let round_amount: Decimal = round.get("amount");
+        let amount_per_member: Decimal = round.get("amount_per_member");
+
+        let rows = sqlx::query(
+            "SELECT tm.id as member_id, u.full_name as member_name,
+                    COUNT(c.id) as instalments_count,
+                    COALESCE(SUM(CASE WHEN c.payment_status = 'paid' THEN c.amount ELSE 0 END), 0) as paid_amount,
+                    COALESCE(SUM(CASE WHEN c.payment_status IN ('pending', 'late') THEN c.amount ELSE 0 END), 0) as pending_amount
+             FROM tontine_rounds tr
+             JOIN tontine_members tm ON tm.tontine_id = tr.tontine_id AND tm.is_active = true
+             JOIN users u ON tm.user_id = u.id
+             LEFT JOIN contributions c ON c.tontine_round_id = tr.id AND c.member_id = tm.id
              WHERE tr.id = $1
-             GROUP BY tr.id, tr.amount"
+             GROUP BY tm.id, tm.position_order, u.full_name
+             ORDER BY tm.position_order"
         )
         .bind(round_id)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await?;
 
+        let members: Vec<MemberRoundBalance> = rows.into_iter().map(|row| {
+            let paid_amount: Decimal = row.get("paid_amount");
+            MemberRoundBalance {
+                member_id: row.get("member_id"),
+                member_name: row.get("member_name"),
+                instalments_count: row.get("instalments_count"),
+                expected_amount: amount_per_member,
+                paid_amount,
+                pending_amount: row.get("pending_amount"),
+                outstanding_amount: (amount_per_member - paid_amount).max(Decimal::ZERO),
+                is_complete: paid_amount >= amount_per_member,
+            }
+        }).collect();
+
+        let total_collected: Decimal = members.iter().map(|member| member.paid_amount).sum();
+
         let round_summary = RoundSummary {
-            total_members: summary.get("total_members"),
-            contributions_count: summary.get("contributions_count"),
-            total_collected: summary.get("total_collected"),
-            round_amount: summary.get("round_amount"),
-            remaining_amount: summary.get::<rust_decimal::Decimal, _>("round_amount") - summary.get::<rust_decimal::Decimal, _>("total_collected"),
+            total_members: members.len() as i64,
+            contributions_count: members.iter().filter(|member| member.is_complete).count() as i64,
+            total_collected,
+            round_amount,
+            remaining_amount: round_amount - total_collected,
+            amount_per_member,
+            members,
         };
 
         Ok(round_summary)
@@ -335,7 +728,11 @@ impl ContributionRepository {
                 COUNT(*) as total_contributions,
                 COALESCE(SUM(amount), 0) as total_amount,
                 COUNT(CASE WHEN payment_status = 'paid' THEN 1 END) as paid_contributions,
-                COUNT(CASE WHEN payment_status = 'pending' THEN 1 END) as pending_contributions
+                COUNT(CASE WHEN payment_status = 'pending' THEN 1 END) as pending_contributions,
+                COUNT(CASE WHEN payment_status = 'late' THEN 1 END) as late_contributions,
+                (SELECT COUNT(*) FROM penalties WHERE member_id = $1) as penalties_count,
+                (SELECT COALESCE(SUM(amount), 0) FROM penalties WHERE member_id = $1 AND status <> 'waived') as total_fines,
+                (SELECT COALESCE(SUM(amount), 0) FROM penalties WHERE member_id = $1 AND status = 'unpaid') as unpaid_fines
              FROM contributions 
              WHERE member_id = $1"
         )
@@ -348,19 +745,72 @@ impl ContributionRepository {
             total_amount: summary.get("total_amount"),
             paid_contributions: summary.get("paid_contributions"),
             pending_contributions: summary.get("pending_contributions"),
+            late_contributions: summary.get("late_contributions"),
+            penalties_count: summary.get("penalties_count"),
+            total_fines: summary.get("total_fines"),
+            unpaid_fines: summary.get("unpaid_fines"),
         };
 
         Ok(member_summary)
     }
 }
 
+/// Cotisation d'un membre pour un round au moment d'enregistrer un versement
+struct RoundBalance {
+    expected: Decimal,
+    /// Versements déjà enregistrés, hors versements échoués
+    recorded: Decimal,
+    allow_partial_payments: bool,
+}
+
+impl RoundBalance {
+    fn remaining(&self) -> Decimal {
+        self.expected - self.recorded
+    }
+
+    /// Sans paiement partiel, le versement doit solder exactement la cotisation ;
+    /// sinon il ne doit pas dépasser le reste à verser
+    fn validate(&self, amount: Decimal) -> Result<(), AppError> {
+        let remaining = self.remaining();
+        let mut validator = Validator::default();
+        if self.allow_partial_payments {
+            validator.check(amount <= remaining, "amount", format!(
+                "Le montant dépasse le reste à verser pour ce round ({})", remaining
+            ));
+        } else {
+            validator.check(amount == remaining, "amount", format!(
+                "Cette tontine n'accepte pas les paiements partiels : le montant doit être égal à {}", remaining
+            ));
+        }
+        validator.finish()
+    }
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct RoundSummary {
     pub total_members: i64,
+    /// Membres dont la cotisation du round est complète
     pub contributions_count: i64,
     pub total_collected: rust_decimal::Decimal,
     pub round_amount: rust_decimal::Decimal,
     pub remaining_amount: rust_decimal::Decimal,
+    pub amount_per_member: rust_decimal::Decimal,
+    pub members: Vec<MemberRoundBalance>,
+}
+
+/// Situation d'un membre pour un round : la cotisation n'est complète
+/// qu'une fois `expected_amount` atteint par les versements payés
+#[derive(Debug, serde::Serialize)]
+pub struct MemberRoundBalance {
+    pub member_id: Uuid,
+    pub member_name: String,
+    pub instalments_count: i64,
+    pub expected_amount: rust_decimal::Decimal,
+    pub paid_amount: rust_decimal::Decimal,
+    /// Versements en attente ou en retard, pas encore comptés comme payés
+    pub pending_amount: rust_decimal::Decimal,
+    pub outstanding_amount: rust_decimal::Decimal,
+    pub is_complete: bool,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -369,4 +819,9 @@ pub struct MemberContributionsSummary {
     pub total_amount: rust_decimal::Decimal,
     pub paid_contributions: i64,
     pub pending_contributions: i64,
+    pub late_contributions: i64,
+    pub penalties_count: i64,
+    /// Amendes dues ou réglées (hors amendes annulées)
+    pub total_fines: rust_decimal::Decimal,
+    pub unpaid_fines: rust_decimal::Decimal,
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::idempotency_keys::{IdempotencyKey, IdempotencyKeyStatus, IdempotencyReservation};
+use crate::errors::AppError;
+
+const KEY_COLUMNS: &str = "id, user_id, idempotency_key, request_hash, status, response_status, response_content_type, response_body, created_at, updated_at, expires_at";
+
+pub struct IdempotencyKeyRepository;
+
+impl IdempotencyKeyRepository {
+    /// Réserve la clé de l'utilisateur. Une clé expirée est réutilisable, ainsi qu'une clé
+    /// restée en cours depuis `stale_before` (arrêt du serveur) pour la même requête.
+    pub async fn reserve(
+        pool: &PgPool,
+        user_id: Uuid,
+        idempotency_key: &str,
+        request_hash: &str,
+        expires_at: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<IdempotencyReservation, AppError> {
+        let reserved: Option<Uuid> = sqlx::query_scalar(
+            "INSERT INTO idempotency_keys (user_id, idempotency_key, request_hash, status, expires_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (user_id, idempotency_key) DO UPDATE
+             SET request_hash = EXCLUDED.request_hash, status = EXCLUDED.status, expires_at = EXCLUDED.expires_at,
+                 response_status = NULL, response_content_type = NULL, response_body = NULL, created_at = NOW()
+             WHERE idempotency_keys.expires_at <= NOW()
+                OR (idempotency_keys.status = $4 AND idempotency_keys.updated_at < $6
+                    AND idempotency_keys.request_hash = EXCLUDED.request_hash)
+             RETURNING id"
+        )
+        .bind(user_id)
+        .bind(idempotency_key)
+        .bind(request_hash)
+        .bind(String::from(IdempotencyKeyStatus::Processing))
+        .bind(expires_at)
+        .bind(stale_before)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(id) = reserved {
+            return Ok(IdempotencyReservation::Reserved(id));
+        }
+
+        let existing = sqlx::query_as::<_, IdempotencyKey>(&format!(
+            "SELECT {} FROM idempotency_keys WHERE user_id = $1 AND idempotency_key = $2",
+            KEY_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(idempotency_key)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(IdempotencyReservation::Existing(existing))
+    }
+
+    /// Enregistre la réponse à rejouer
+    pub async fn complete(
+        pool: &PgPool,
+        id: Uuid,
+        response_status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE idempotency_keys
+             SET status = $1, response_status = $2, response_content_type = $3, response_body = $4
+             WHERE id = $5"
+        )
+        .bind(String::from(IdempotencyKeyStatus::Completed))
+        .bind(response_status as i32)
+        .bind(content_type)
+        .bind(body)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Libère la clé : la requête pourra être rejouée
+    pub async fn release(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_expired(pool: &PgPool) -> Result<u64, AppError> {
+        let purged = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(pool)
+            .await?
+            .rows_affected();
+
+        Ok(purged)
+    }
+}
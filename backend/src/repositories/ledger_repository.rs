@@ -0,0 +1,313 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+
+use crate::model::ledger::{AccountBalance, AccountKind, JournalEntry, JournalEntryWithPostings, LedgerCheck, LedgerPosting, UnbalancedEntry};
+use crate::model::penalties::Penalty;
+use crate::model::transactions::{Transaction, TransactionStatus, TransactionType};
+use crate::errors::AppError;
+
+const ENTRY_COLUMNS: &str = "id, tontine_id, transaction_id, penalty_id, reversal_of, description, created_at";
+
+pub struct LedgerRepository;
+
+impl LedgerRepository {
+    /// Aligne le grand livre sur le statut de la transaction : une transaction réglée est
+    /// journalisée une fois, une transaction qui ne l'est plus voit son écriture contre-passée.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn sync_transaction(conn: &mut PgConnection, transaction: &Transaction) -> Result<Option<JournalEntry>, AppError> {
+        let live_entry = Self::live_entry_for_transaction(conn, transaction.id).await?;
+        let completed = transaction.status == String::from(TransactionStatus::Completed);
+
+        match (completed, live_entry) {
+            (true, None) => {
+                let transaction_type = TransactionType::try_from(transaction.transaction_type.clone())
+                    .map_err(AppError::InternalServerError)?;
+                let (debit, credit) = Self::transaction_accounts(conn, transaction, transaction_type).await?;
+                let description = transaction.description.clone()
+                    .unwrap_or_else(|| transaction.transaction_type.clone());
+
+                let entry = Self::insert_entry(
+                    conn,
+                    transaction.tontine_id,
+                    (Some(transaction.id), None, None),
+                    &description,
+                    &[(debit, -transaction.amount), (credit, transaction.amount)],
+                ).await?;
+                Ok(Some(entry))
+            }
+            (false, Some(entry)) => Self::reverse(conn, &entry).await.map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Journalise le règlement d'une amende : débit du portefeuille du membre, crédit du compte des amendes
+    pub async fn record_penalty_payment(conn: &mut PgConnection, penalty: &Penalty) -> Result<JournalEntry, AppError> {
+        let user_id: Uuid = sqlx::query_scalar("SELECT user_id FROM tontine_members WHERE id = $1")
+            .bind(penalty.member_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let wallet = Self::account(conn, AccountKind::MemberWallet, penalty.tontine_id, Some(user_id)).await?;
+        let penalties = Self::account(conn, AccountKind::Penalties, penalty.tontine_id, None).await?;
+
+        Self::insert_entry(
+            conn,
+            penalty.tontine_id,
+            (None, Some(penalty.id), None),
+            "Amende de retard",
+            &[(wallet, -penalty.amount), (penalties, penalty.amount)],
+        ).await
+    }
+
+    /// Soldes des comptes de la tontine
+    pub async fn find_balances(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<AccountBalance>, AppError> {
+        let balances = sqlx::query_as::<_, AccountBalance>(
+            "SELECT a.id as account_id, a.kind, a.tontine_id, a.user_id, COALESCE(SUM(p.amount), 0) as balance
+             FROM ledger_accounts a
+             LEFT JOIN ledger_postings p ON p.account_id = a.id
+             WHERE a.tontine_id = $1
+             GROUP BY a.id
+             ORDER BY a.kind, a.created_at"
+        )
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(balances)
+    }
+
+    /// Solde cumulé des comptes d'un type pour une tontine
+    pub async fn balance_of(pool: &PgPool, tontine_id: Uuid, kind: AccountKind) -> Result<Decimal, AppError> {
+        let balance: Decimal = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(p.amount), 0)
+             FROM ledger_postings p
+             JOIN ledger_accounts a ON p.account_id = a.id
+             WHERE a.tontine_id = $1 AND a.kind = $2"
+        )
+        .bind(tontine_id)
+        .bind(String::from(kind))
+        .fetch_one(pool)
+        .await?;
+
+        Ok(balance)
+    }
+
+    /// Solde des portefeuilles d'un utilisateur, toutes tontines confondues
+    pub async fn wallet_balance(pool: &PgPool, user_id: Uuid) -> Result<Decimal, AppError> {
+        let balance: Decimal = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(p.amount), 0)
+             FROM ledger_postings p
+             JOIN ledger_accounts a ON p.account_id = a.id
+             WHERE a.kind = 'member_wallet' AND a.user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(balance)
+    }
+
+    pub async fn find_entries(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<JournalEntryWithPostings>, AppError> {
+        let entries = sqlx::query_as::<_, JournalEntry>(&format!(
+            "SELECT {} FROM journal_entries WHERE tontine_id = $1 ORDER BY created_at, id",
+            ENTRY_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        let postings = sqlx::query_as::<_, LedgerPosting>(
+            "SELECT p.id, p.entry_id, p.account_id, a.kind as account_kind, a.user_id, p.amount
+             FROM ledger_postings p
+             JOIN journal_entries e ON p.entry_id = e.id
+             JOIN ledger_accounts a ON p.account_id = a.id
+             WHERE e.tontine_id = $1
+             ORDER BY p.amount"
+        )
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        let entries = entries.into_iter().map(|entry| {
+            let postings = postings.iter().filter(|posting| posting.entry_id == entry.id).cloned().collect();
+            JournalEntryWithPostings { entry, postings }
+        }).collect();
+
+        Ok(entries)
+    }
+
+    /// Vérifie que chaque écriture de la tontine s'équilibre
+    pub async fn check(pool: &PgPool, tontine_id: Uuid) -> Result<LedgerCheck, AppError> {
+        let entries_checked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM journal_entries WHERE tontine_id = $1")
+            .bind(tontine_id)
+            .fetch_one(pool)
+            .await?;
+
+        let unbalanced_entries = sqlx::query_as::<_, UnbalancedEntry>(
+            "SELECT e.id as entry_id, COALESCE(SUM(p.amount), 0) as total
+             FROM journal_entries e
+             LEFT JOIN ledger_postings p ON p.entry_id = e.id
+             WHERE e.tontine_id = $1
+             GROUP BY e.id
+             HAVING COALESCE(SUM(p.amount), 0) <> 0 OR COUNT(p.id) < 2"
+        )
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        let ledger_total: Decimal = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(p.amount), 0)
+             FROM ledger_postings p
+             JOIN ledger_accounts a ON p.account_id = a.id
+             WHERE a.tontine_id = $1"
+        )
+        .bind(tontine_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(LedgerCheck {
+            tontine_id,
+            entries_checked,
+            balanced: unbalanced_entries.is_empty() && ledger_total == Decimal::ZERO,
+            unbalanced_entries,
+            ledger_total,
+        })
+    }
+
+    /// Comptes débité et crédité par une transaction selon son type
+    async fn transaction_accounts(
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        transaction_type: TransactionType,
+    ) -> Result<(Uuid, Uuid), AppError> {
+        let tontine_id = transaction.tontine_id;
+        let (debit_kind, debit_user, credit_kind, credit_user) = match transaction_type {
+            TransactionType::Contribution => {
+                let from = transaction.from_user_id.ok_or_else(|| {
+                    AppError::ValidationError("Une cotisation doit indiquer le membre qui paie (from_user_id)".to_string())
+                })?;
+                (AccountKind::MemberWallet, Some(from), AccountKind::TontinePot, None)
+            }
+            TransactionType::Payout | TransactionType::Refund => {
+                (AccountKind::TontinePot, None, AccountKind::MemberWallet, Some(Self::recipient(transaction)?))
+            }
+            // Part de remise reversée depuis la réserve
+            TransactionType::BidDividend => {
+                (AccountKind::TontineReserve, None, AccountKind::MemberWallet, Some(Self::recipient(transaction)?))
+            }
+            // La remise n'est pas versée au gagnant : elle passe du pot à la réserve
+            TransactionType::BidDiscount => (AccountKind::TontinePot, None, AccountKind::TontineReserve, None),
+        };
+
+        let debit = Self::account(conn, debit_kind, tontine_id, debit_user).await?;
+        let credit = Self::account(conn, credit_kind, tontine_id, credit_user).await?;
+
+        Ok((debit, credit))
+    }
+
+    fn recipient(transaction: &Transaction) -> Result<Uuid, AppError> {
+        transaction.to_user_id.ok_or_else(|| {
+            AppError::ValidationError("Ce type de transaction doit indiquer son destinataire (to_user_id)".to_string())
+        })
+    }
+
+    /// Compte de la tontine, créé au premier usage
+    async fn account(conn: &mut PgConnection, kind: AccountKind, tontine_id: Uuid, user_id: Option<Uuid>) -> Result<Uuid, AppError> {
+        sqlx::query(
+            "INSERT INTO ledger_accounts (kind, tontine_id, user_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+        )
+        .bind(String::from(kind))
+        .bind(tontine_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let account_id = sqlx::query_scalar(
+            "SELECT id FROM ledger_accounts
+             WHERE kind = $1 AND tontine_id = $2 AND user_id IS NOT DISTINCT FROM $3"
+        )
+        .bind(String::from(kind))
+        .bind(tontine_id)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(account_id)
+    }
+
+    /// Écriture d'une transaction qui n'a pas été contre-passée
+    async fn live_entry_for_transaction(conn: &mut PgConnection, transaction_id: Uuid) -> Result<Option<JournalEntry>, AppError> {
+        let entry = sqlx::query_as::<_, JournalEntry>(&format!(
+            "SELECT {} FROM journal_entries e
+             WHERE e.transaction_id = $1 AND e.reversal_of IS NULL
+               AND NOT EXISTS (SELECT 1 FROM journal_entries r WHERE r.reversal_of = e.id)",
+            ENTRY_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Contre-passe une écriture : mêmes comptes, montants opposés
+    async fn reverse(conn: &mut PgConnection, entry: &JournalEntry) -> Result<JournalEntry, AppError> {
+        let postings: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            "SELECT account_id, amount FROM ledger_postings WHERE entry_id = $1"
+        )
+        .bind(entry.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let reversed: Vec<(Uuid, Decimal)> = postings.into_iter().map(|(account, amount)| (account, -amount)).collect();
+
+        Self::insert_entry(
+            conn,
+            entry.tontine_id,
+            (entry.transaction_id, entry.penalty_id, Some(entry.id)),
+            &format!("Contre-passation : {}", entry.description),
+            &reversed,
+        ).await
+    }
+
+    /// `source` : transaction, amende et écriture contre-passée à l'origine de l'écriture
+    async fn insert_entry(
+        conn: &mut PgConnection,
+        tontine_id: Uuid,
+        source: (Option<Uuid>, Option<Uuid>, Option<Uuid>),
+        description: &str,
+        postings: &[(Uuid, Decimal)],
+    ) -> Result<JournalEntry, AppError> {
+        let total: Decimal = postings.iter().map(|(_, amount)| *amount).sum();
+        if postings.len() < 2 || total != Decimal::ZERO {
+            return Err(AppError::InternalServerError(format!("Écriture déséquilibrée (solde {})", total)));
+        }
+
+        let (transaction_id, penalty_id, reversal_of) = source;
+        let entry = sqlx::query_as::<_, JournalEntry>(&format!(
+            "INSERT INTO journal_entries (tontine_id, transaction_id, penalty_id, reversal_of, description)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING {}",
+            ENTRY_COLUMNS
+        ))
+        .bind(tontine_id)
+        .bind(transaction_id)
+        .bind(penalty_id)
+        .bind(reversal_of)
+        .bind(description)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        for (account_id, amount) in postings {
+            sqlx::query("INSERT INTO ledger_postings (entry_id, account_id, amount) VALUES ($1, $2, $3)")
+                .bind(entry.id)
+                .bind(account_id)
+                .bind(amount)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(entry)
+    }
+}
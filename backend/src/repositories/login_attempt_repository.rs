@@ -0,0 +1,96 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::model::sessions::DeviceInfo;
+use crate::errors::AppError;
+
+/// Nombre d'échecs tolérés avant le premier verrouillage
+const MAX_FAILED_ATTEMPTS: i32 = 5;
+/// Durée du premier verrouillage, doublée à chaque nouvel échec
+const BASE_LOCKOUT_SECONDS: i64 = 60;
+/// Durée maximale d'un verrouillage
+const MAX_LOCKOUT_SECONDS: i64 = 24 * 60 * 60;
+
+pub struct LoginAttemptRepository;
+
+impl LoginAttemptRepository {
+    /// Date jusqu'à laquelle le compte est verrouillé pour cette adresse, si le verrouillage est en cours
+    pub async fn locked_until(pool: &PgPool, user_id: Uuid, ip_address: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        let locked_until: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(
+            "SELECT locked_until FROM login_lockouts WHERE user_id = $1 AND ip_address = $2"
+        )
+        .bind(user_id)
+        .bind(ip_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(locked_until.flatten().filter(|until| *until > Utc::now()))
+    }
+
+    /// Journalise l'échec et, pour un compte existant, prolonge le verrouillage progressif
+    /// du compte depuis l'adresse `ip_address`
+    pub async fn record_failure(
+        pool: &PgPool,
+        user_id: Option<Uuid>,
+        identifier: &str,
+        method: &str,
+        reason: &str,
+        device: &DeviceInfo,
+        ip_address: &str,
+    ) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO failed_login_attempts (user_id, identifier, method, reason, ip_address, user_agent)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+        .bind(user_id)
+        .bind(identifier)
+        .bind(method)
+        .bind(reason)
+        .bind(&device.ip_address)
+        .bind(&device.user_agent)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(user_id) = user_id {
+            let failed_count: i32 = sqlx::query_scalar(
+                "INSERT INTO login_lockouts (user_id, ip_address, failed_count) VALUES ($1, $2, 1)
+                 ON CONFLICT (user_id, ip_address) DO UPDATE SET failed_count = login_lockouts.failed_count + 1
+                 RETURNING failed_count"
+            )
+            .bind(user_id)
+            .bind(ip_address)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if failed_count >= MAX_FAILED_ATTEMPTS {
+                let exponent = (failed_count - MAX_FAILED_ATTEMPTS).min(16) as u32;
+                let seconds = (BASE_LOCKOUT_SECONDS * 2_i64.pow(exponent)).min(MAX_LOCKOUT_SECONDS);
+
+                sqlx::query("UPDATE login_lockouts SET locked_until = $1 WHERE user_id = $2 AND ip_address = $3")
+                    .bind(Utc::now() + Duration::seconds(seconds))
+                    .bind(user_id)
+                    .bind(ip_address)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remet le compteur de l'adresse à zéro après une connexion réussie
+    pub async fn reset(pool: &PgPool, user_id: Uuid, ip_address: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM login_lockouts WHERE user_id = $1 AND ip_address = $2")
+            .bind(user_id)
+            .bind(ip_address)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
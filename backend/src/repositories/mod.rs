@@ -3,4 +3,18 @@ pub mod tontine_repository;
 pub mod tontine_member_repository;
 pub mod tontine_round_repository;
 pub mod contributions_repository;
-pub mod transaction_repository;
\ No newline at end of file
+pub mod transaction_repository;
+pub mod password_reset_repository;
+pub mod session_repository;
+pub mod otp_repository;
+pub mod login_attempt_repository;
+pub mod two_factor_repository;
+pub mod scheduled_job_repository;
+pub mod beneficiary_draw_repository;
+pub mod bid_repository;
+pub mod penalty_repository;
+pub mod payout_repository;
+pub mod ledger_repository;
+pub mod payment_transaction_repository;
+pub mod reconciliation_repository;
+pub mod idempotency_key_repository;
\ No newline at end of file
@@ -0,0 +1,108 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::otp_codes::{OtpCode, OtpPurpose};
+use crate::errors::AppError;
+
+pub struct OtpRepository;
+
+impl OtpRepository {
+    /// Enregistre un nouveau code ; les codes précédents de même usage sont invalidés
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<OtpCode, AppError> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE otp_codes SET consumed_at = $1 WHERE user_id = $2 AND purpose = $3 AND consumed_at IS NULL")
+            .bind(Utc::now())
+            .bind(user_id)
+            .bind(String::from(purpose))
+            .execute(&mut *tx)
+            .await?;
+
+        let otp = sqlx::query_as::<_, OtpCode>(
+            "INSERT INTO otp_codes (user_id, purpose, code_hash, expires_at)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, user_id, purpose, code_hash, attempts, expires_at, consumed_at, created_at"
+        )
+        .bind(user_id)
+        .bind(String::from(purpose))
+        .bind(code_hash)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(otp)
+    }
+
+    /// Vérifie le code en attente : chaque échec est compté et le code est
+    /// abandonné une fois le nombre maximal de tentatives atteint
+    pub async fn verify(
+        pool: &PgPool,
+        user_id: Uuid,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        max_attempts: i32,
+    ) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        let otp = sqlx::query_as::<_, OtpCode>(
+            "SELECT id, user_id, purpose, code_hash, attempts, expires_at, consumed_at, created_at
+             FROM otp_codes
+             WHERE user_id = $1 AND purpose = $2 AND consumed_at IS NULL
+             ORDER BY created_at DESC
+             LIMIT 1
+             FOR UPDATE"
+        )
+        .bind(user_id)
+        .bind(String::from(purpose))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        let otp = match otp {
+            Some(otp) if otp.expires_at > now => otp,
+            _ => return Err(AppError::AuthenticationError("Code invalide ou expiré".to_string())),
+        };
+
+        if otp.code_hash != code_hash {
+            let attempts = otp.attempts + 1;
+
+            // Le code est abandonné après la dernière tentative autorisée
+            sqlx::query(
+                "UPDATE otp_codes SET attempts = $1, consumed_at = CASE WHEN $1 >= $2 THEN $3 ELSE NULL END WHERE id = $4"
+            )
+            .bind(attempts)
+            .bind(max_attempts)
+            .bind(now)
+            .bind(otp.id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            if attempts >= max_attempts {
+                return Err(AppError::AuthenticationError(
+                    "Trop de tentatives, veuillez demander un nouveau code".to_string(),
+                ));
+            }
+            return Err(AppError::AuthenticationError("Code invalide ou expiré".to_string()));
+        }
+
+        sqlx::query("UPDATE otp_codes SET consumed_at = $1 WHERE id = $2")
+            .bind(now)
+            .bind(otp.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::password_reset_tokens::PasswordResetToken;
+use crate::repositories::session_repository::SessionRepository;
+use crate::errors::AppError;
+
+pub struct PasswordResetRepository;
+
+impl PasswordResetRepository {
+    pub async fn create(pool: &PgPool, user_id: Uuid, token_hash: &str, expires_at: DateTime<Utc>) -> Result<PasswordResetToken, AppError> {
+        let mut tx = pool.begin().await?;
+
+        // Un seul jeton valide à la fois : les demandes précédentes sont invalidées
+        sqlx::query("UPDATE password_reset_tokens SET used_at = $1 WHERE user_id = $2 AND used_at IS NULL")
+            .bind(Utc::now())
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let token = sqlx::query_as::<_, PasswordResetToken>(
+            "INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+             VALUES ($1, $2, $3)
+             RETURNING id, user_id, token_hash, expires_at, used_at, created_at"
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(token)
+    }
+
+    /// Consomme le jeton et remplace le mot de passe : le jeton devient inutilisable
+    /// et toutes les sessions ouvertes de l'utilisateur sont révoquées
+    pub async fn consume(pool: &PgPool, token_hash: &str, new_password_hash: &str) -> Result<Uuid, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let token = sqlx::query_as::<_, PasswordResetToken>(
+            "SELECT id, user_id, token_hash, expires_at, used_at, created_at
+             FROM password_reset_tokens
+             WHERE token_hash = $1
+             FOR UPDATE"
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        let token = match token {
+            Some(token) if token.used_at.is_none() && token.expires_at > now => token,
+            _ => return Err(AppError::ValidationError("Jeton de réinitialisation invalide ou expiré".to_string())),
+        };
+
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(new_password_hash)
+            .bind(token.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        SessionRepository::revoke_all_for_user(&mut tx, token.user_id, None).await?;
+
+        sqlx::query("UPDATE password_reset_tokens SET used_at = $1 WHERE user_id = $2 AND used_at IS NULL")
+            .bind(now)
+            .bind(token.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(token.user_id)
+    }
+}
@@ -0,0 +1,212 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+
+use crate::model::payment_transactions::{PaymentDirection, PaymentProviderKind, PaymentTransaction, PaymentTransactionStatus};
+use crate::errors::AppError;
+
+const PAYMENT_COLUMNS: &str = "id, contribution_id, tontine_round_id, direction, transaction_id, payment_reference, provider, amount, currency, status, provider_reference, metadata, created_at, updated_at";
+
+pub struct PaymentTransactionRepository;
+
+impl PaymentTransactionRepository {
+    /// Enregistre un paiement en attente. S'exécute dans la transaction de l'appelant.
+    pub async fn create(
+        conn: &mut PgConnection,
+        contribution_id: Uuid,
+        transaction_id: Uuid,
+        provider: PaymentProviderKind,
+        amount: Decimal,
+        currency: &str,
+        metadata: serde_json::Value,
+    ) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "INSERT INTO payment_transactions (contribution_id, transaction_id, payment_reference, provider, amount, currency, status, metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING {}",
+            PAYMENT_COLUMNS
+        ))
+        .bind(contribution_id)
+        .bind(transaction_id)
+        .bind(Uuid::new_v4().to_string())
+        .bind(String::from(provider))
+        .bind(amount)
+        .bind(currency)
+        .bind(String::from(PaymentTransactionStatus::Pending))
+        .bind(metadata)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// Enregistre le versement en attente d'un tour vers le téléphone du bénéficiaire.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn create_disbursement(
+        conn: &mut PgConnection,
+        round_id: Uuid,
+        transaction_id: Uuid,
+        provider: PaymentProviderKind,
+        amount: Decimal,
+        currency: &str,
+        phone_number: &str,
+    ) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "INSERT INTO payment_transactions (tontine_round_id, direction, transaction_id, payment_reference, provider, amount, currency, status, metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING {}",
+            PAYMENT_COLUMNS
+        ))
+        .bind(round_id)
+        .bind(String::from(PaymentDirection::Disbursement))
+        .bind(transaction_id)
+        .bind(Uuid::new_v4().to_string())
+        .bind(String::from(provider))
+        .bind(amount)
+        .bind(currency)
+        .bind(String::from(PaymentTransactionStatus::Pending))
+        .bind(serde_json::json!({ "phone_number": phone_number }))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(payment)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, payment_id: Uuid) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "SELECT {} FROM payment_transactions WHERE id = $1",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .fetch_optional(pool)
+        .await?;
+
+        payment.ok_or_else(|| AppError::NotFound(format!("Paiement avec l'ID {} non trouvé", payment_id)))
+    }
+
+    pub async fn find_by_contribution(pool: &PgPool, contribution_id: Uuid) -> Result<Vec<PaymentTransaction>, AppError> {
+        let payments = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "SELECT {} FROM payment_transactions WHERE contribution_id = $1 ORDER BY created_at DESC",
+            PAYMENT_COLUMNS
+        ))
+        .bind(contribution_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(payments)
+    }
+
+    /// Enregistre la référence réservée auprès de l'opérateur avant l'envoi
+    pub async fn set_provider_reference(pool: &PgPool, payment_id: Uuid, provider_reference: &str) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "UPDATE payment_transactions SET provider_reference = $1 WHERE id = $2 RETURNING {}",
+            PAYMENT_COLUMNS
+        ))
+        .bind(provider_reference)
+        .bind(payment_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// Versement du tour en attente de confirmation, s'il y en a un
+    pub async fn find_pending_disbursement(pool: &PgPool, round_id: Uuid) -> Result<Option<PaymentTransaction>, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "SELECT {} FROM payment_transactions
+             WHERE tontine_round_id = $1 AND direction = $2 AND status = $3
+             ORDER BY created_at DESC
+             LIMIT 1",
+            PAYMENT_COLUMNS
+        ))
+        .bind(round_id)
+        .bind(String::from(PaymentDirection::Disbursement))
+        .bind(String::from(PaymentTransactionStatus::Pending))
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// Verrouille le paiement jusqu'à la fin de la transaction de l'appelant
+    pub async fn lock(conn: &mut PgConnection, payment_id: Uuid) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "SELECT {} FROM payment_transactions WHERE id = $1 FOR UPDATE",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        payment.ok_or_else(|| AppError::NotFound(format!("Paiement avec l'ID {} non trouvé", payment_id)))
+    }
+
+    /// Verrouille le paiement désigné par une notification, par notre référence ou par celle de l'opérateur
+    pub async fn lock_by_reference(
+        conn: &mut PgConnection,
+        provider: PaymentProviderKind,
+        payment_reference: Option<&str>,
+        provider_reference: Option<&str>,
+    ) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "SELECT {} FROM payment_transactions
+             WHERE provider = $1 AND (payment_reference = $2 OR provider_reference = $3)
+             FOR UPDATE",
+            PAYMENT_COLUMNS
+        ))
+        .bind(String::from(provider))
+        .bind(payment_reference)
+        .bind(provider_reference)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        payment.ok_or_else(|| AppError::NotFound("Aucun paiement ne correspond à cette notification".to_string()))
+    }
+
+    /// Conserve la notification brute dans `metadata.callbacks`, y compris les notifications répétées
+    pub async fn record_callback(conn: &mut PgConnection, payment_id: Uuid, payload: &serde_json::Value) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE payment_transactions
+             SET metadata = jsonb_set(
+                 COALESCE(metadata, '{}'::jsonb),
+                 '{callbacks}',
+                 COALESCE(metadata->'callbacks', '[]'::jsonb) || jsonb_build_array(jsonb_build_object('received_at', NOW(), 'payload', $2::jsonb))
+             )
+             WHERE id = $1"
+        )
+        .bind(payment_id)
+        .bind(payload)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Enregistre la réponse de l'opérateur : nouveau statut, référence opérateur
+    /// et réponse brute conservée dans `metadata.last_response`
+    pub async fn record_response(
+        conn: &mut PgConnection,
+        payment_id: Uuid,
+        status: PaymentTransactionStatus,
+        provider_reference: Option<&str>,
+        response: &serde_json::Value,
+    ) -> Result<PaymentTransaction, AppError> {
+        let payment = sqlx::query_as::<_, PaymentTransaction>(&format!(
+            "UPDATE payment_transactions
+             SET status = $2,
+                 provider_reference = COALESCE($3, provider_reference),
+                 metadata = COALESCE(metadata, '{{}}'::jsonb) || jsonb_build_object('last_response', $4::jsonb)
+             WHERE id = $1
+             RETURNING {}",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .bind(String::from(status))
+        .bind(provider_reference)
+        .bind(response)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(payment)
+    }
+}
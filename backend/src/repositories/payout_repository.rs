@@ -0,0 +1,341 @@
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use chrono::{Duration, Utc};
+
+use crate::model::payment_transactions::PaymentProviderKind;
+use crate::model::payouts::{PayoutMethod, PayoutStatus, RoundDisbursement, RoundPayout, RoundPayoutResult};
+use crate::model::scheduled_jobs::JobType;
+use crate::model::tontine::{ShortfallPolicy, Tontine, TontineStatus};
+use crate::model::tontine_rounds::{RoundStatus, TontineRound};
+use crate::model::transactions::{CreateTransaction, TransactionStatus, TransactionType};
+use crate::repositories::payment_transaction_repository::PaymentTransactionRepository;
+use crate::repositories::scheduled_job_repository::ScheduledJobRepository;
+use crate::repositories::tontine_repository::TontineRepository;
+use crate::repositories::transaction_repository::TransactionRepository;
+use crate::errors::AppError;
+
+const PAYOUT_COLUMNS: &str = "tontine_round_id, tontine_id, transaction_id, beneficiary_user_id, collected_amount, discount, amount, shortfall, paid_by, paid_at, status, payment_method";
+/// Le premier envoi est fait pendant la requête : le planificateur ne prend le relais qu'ensuite,
+/// pour ne jamais envoyer le même versement en parallèle
+const DISBURSEMENT_CHECK_DELAY_SECONDS: i64 = 120;
+const ROUND_COLUMNS: &str = "id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at";
+
+pub struct PayoutRepository;
+
+impl PayoutRepository {
+    /// Verse le pot d'un tour à son bénéficiaire en une seule transaction : contrôle des
+    /// cotisations selon la politique de la tontine, clôture du tour, transaction `payout`
+    /// et avancement du tour courant. La tontine est terminée après le dernier tour.
+    pub async fn pay_round(pool: &PgPool, round_id: Uuid, paid_by: Uuid) -> Result<RoundPayoutResult, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let prepared = Self::prepare(&mut tx, round_id).await?;
+
+        let transaction = TransactionRepository::insert(
+            &mut tx,
+            &prepared.transaction_data(),
+            TransactionStatus::Completed,
+        ).await?;
+
+        let payout = Self::insert_payout(&mut tx, &prepared, transaction.id, paid_by, PayoutStatus::Completed, PayoutMethod::Manual).await?;
+        let (round, tontine) = Self::complete_round(&mut tx, round_id, Some(paid_by)).await?;
+
+        tx.commit().await?;
+
+        Ok(RoundPayoutResult { payout, round, transaction, tontine })
+    }
+
+    /// Prépare le versement mobile money du tour vers le téléphone du bénéficiaire : le tour
+    /// passe `disbursing`, la transaction `payout` et le versement restent en attente jusqu'à
+    /// la confirmation de l'opérateur. L'envoi est planifié pour être rejoué en cas d'échec.
+    pub async fn start_disbursement(
+        pool: &PgPool,
+        round_id: Uuid,
+        paid_by: Uuid,
+        provider: PaymentProviderKind,
+        currency: &str,
+    ) -> Result<RoundDisbursement, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let prepared = Self::prepare(&mut tx, round_id).await?;
+
+        let phone: Option<String> = sqlx::query_scalar("SELECT phone FROM users WHERE id = $1")
+            .bind(prepared.beneficiary)
+            .fetch_one(&mut *tx)
+            .await?;
+        let phone = phone
+            .filter(|phone| !phone.trim().is_empty())
+            .ok_or_else(|| AppError::ValidationError("Le bénéficiaire de ce tour n'a pas de numéro de téléphone".to_string()))?;
+
+        let round = sqlx::query_as::<_, TontineRound>(&format!(
+            "UPDATE tontine_rounds SET status = $1 WHERE id = $2 RETURNING {}",
+            ROUND_COLUMNS
+        ))
+        .bind(String::from(RoundStatus::Disbursing))
+        .bind(round_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let transaction = TransactionRepository::insert(
+            &mut tx,
+            &prepared.transaction_data(),
+            TransactionStatus::Pending,
+        ).await?;
+
+        let payout = Self::insert_payout(&mut tx, &prepared, transaction.id, paid_by, PayoutStatus::Pending, PayoutMethod::MobileMoney).await?;
+
+        let payment = PaymentTransactionRepository::create_disbursement(
+            &mut tx,
+            round_id,
+            transaction.id,
+            provider,
+            prepared.amount,
+            currency,
+            &phone,
+        ).await?;
+
+        let check_at = Utc::now() + Duration::seconds(DISBURSEMENT_CHECK_DELAY_SECONDS);
+        ScheduledJobRepository::reschedule(&mut tx, JobType::DisbursePayout, prepared.tontine_id, Some(round_id), check_at).await?;
+
+        tx.commit().await?;
+
+        Ok(RoundDisbursement { payout, round, transaction, payment })
+    }
+
+    /// Versement confirmé par l'opérateur : transaction réglée, versement effectué et tour clôturé.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn confirm_disbursement(conn: &mut PgConnection, round_id: Uuid, transaction_id: Uuid) -> Result<(), AppError> {
+        TransactionRepository::set_status(conn, transaction_id, TransactionStatus::Completed).await?;
+
+        let paid_by: Option<Uuid> = sqlx::query_scalar(
+            "UPDATE round_payouts SET status = $1, paid_at = NOW() WHERE tontine_round_id = $2 RETURNING paid_by"
+        )
+        .bind(String::from(PayoutStatus::Completed))
+        .bind(round_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Self::complete_round(conn, round_id, paid_by).await?;
+
+        Ok(())
+    }
+
+    /// Versement refusé ou abandonné : la transaction échoue, le versement est supprimé et
+    /// le tour redevient payable. S'exécute dans la transaction de l'appelant.
+    pub async fn revert_disbursement(conn: &mut PgConnection, round_id: Uuid, transaction_id: Uuid) -> Result<(), AppError> {
+        TransactionRepository::set_status(conn, transaction_id, TransactionStatus::Failed).await?;
+
+        sqlx::query("DELETE FROM round_payouts WHERE tontine_round_id = $1 AND status = $2")
+            .bind(round_id)
+            .bind(String::from(PayoutStatus::Pending))
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query("UPDATE tontine_rounds SET status = $1 WHERE id = $2 AND status = $3")
+            .bind(String::from(RoundStatus::Pending))
+            .bind(round_id)
+            .bind(String::from(RoundStatus::Disbursing))
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Contrôle qu'un tour peut être versé et calcule le montant dû au bénéficiaire.
+    /// Verrouille le tour et la tontine.
+    async fn prepare(conn: &mut PgConnection, round_id: Uuid) -> Result<PreparedPayout, AppError> {
+        let row = sqlx::query(
+            "SELECT tr.tontine_id, tr.round_number, tr.amount, tr.status, tr.beneficiary_user_id,
+                    t.status as tontine_status, t.amount_per_member, t.payout_shortfall_policy
+             FROM tontine_rounds tr
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tr.id = $1
+             FOR UPDATE OF tr, t"
+        )
+        .bind(round_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        if row.get::<String, _>("tontine_status") != String::from(TontineStatus::Running) {
+            return Err(AppError::ValidationError("Seuls les tours d'une tontine en cours peuvent être versés".to_string()));
+        }
+        if row.get::<String, _>("status") != String::from(RoundStatus::Pending) {
+            return Err(AppError::ValidationError("Ce tour n'est plus en attente".to_string()));
+        }
+        let beneficiary: Uuid = row.get::<Option<Uuid>, _>("beneficiary_user_id")
+            .ok_or_else(|| AppError::ValidationError("Le bénéficiaire de ce tour n'est pas encore désigné".to_string()))?;
+
+        let tontine_id: Uuid = row.get("tontine_id");
+        let round_number: i32 = row.get("round_number");
+        let pot: Decimal = row.get("amount");
+        let policy = ShortfallPolicy::try_from(row.get::<String, _>("payout_shortfall_policy"))
+            .map_err(AppError::InternalServerError)?;
+
+        // Cotisations payées par les membres actifs, chacune plafonnée au montant attendu
+        let balances = sqlx::query(
+            "SELECT COUNT(*) FILTER (WHERE paid < $2) as incomplete_members,
+                    COALESCE(SUM(LEAST(paid, $2)), 0) as collected
+             FROM (
+                SELECT tm.id, COALESCE(SUM(c.amount) FILTER (WHERE c.payment_status = 'paid'), 0) as paid
+                FROM tontine_members tm
+                JOIN tontine_rounds tr ON tr.tontine_id = tm.tontine_id
+                LEFT JOIN contributions c ON c.tontine_round_id = tr.id AND c.member_id = tm.id
+                WHERE tr.id = $1 AND tm.is_active = true
+                GROUP BY tm.id
+             ) members"
+        )
+        .bind(round_id)
+        .bind(row.get::<Decimal, _>("amount_per_member"))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let incomplete_members: i64 = balances.get("incomplete_members");
+        let collected: Decimal = balances.get("collected");
+        let shortfall = (pot - collected).max(Decimal::ZERO);
+
+        if shortfall > Decimal::ZERO && policy == ShortfallPolicy::Reject {
+            return Err(AppError::ValidationError(format!(
+                "{} membre(s) n'ont pas encore réglé leur cotisation pour ce tour (manque {})",
+                incomplete_members, shortfall
+            )));
+        }
+
+        // Le gagnant d'une enchère reçoit le pot moins sa remise
+        let discount: Decimal = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0) FROM bids WHERE tontine_round_id = $1 AND status = 'won'"
+        )
+        .bind(round_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let amount = pot.min(collected) - discount;
+        if amount <= Decimal::ZERO {
+            return Err(AppError::ValidationError("Aucun montant à verser pour ce tour".to_string()));
+        }
+
+        Ok(PreparedPayout { round_id, tontine_id, round_number, beneficiary, collected, discount, amount, shortfall })
+    }
+
+    async fn insert_payout(
+        conn: &mut PgConnection,
+        prepared: &PreparedPayout,
+        transaction_id: Uuid,
+        paid_by: Uuid,
+        status: PayoutStatus,
+        method: PayoutMethod,
+    ) -> Result<RoundPayout, AppError> {
+        let payout = sqlx::query_as::<_, RoundPayout>(&format!(
+            "INSERT INTO round_payouts (tontine_round_id, tontine_id, transaction_id, beneficiary_user_id, collected_amount, discount, amount, shortfall, paid_by, status, payment_method)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             RETURNING {}",
+            PAYOUT_COLUMNS
+        ))
+        .bind(prepared.round_id)
+        .bind(prepared.tontine_id)
+        .bind(transaction_id)
+        .bind(prepared.beneficiary)
+        .bind(prepared.collected)
+        .bind(prepared.discount)
+        .bind(prepared.amount)
+        .bind(prepared.shortfall)
+        .bind(paid_by)
+        .bind(String::from(status))
+        .bind(String::from(method))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(payout)
+    }
+
+    /// Clôture le tour versé et avance le tour courant ; la tontine est terminée après le dernier tour
+    async fn complete_round(conn: &mut PgConnection, round_id: Uuid, paid_by: Option<Uuid>) -> Result<(TontineRound, Tontine), AppError> {
+        let round = sqlx::query_as::<_, TontineRound>(&format!(
+            "UPDATE tontine_rounds SET status = 'completed' WHERE id = $1 RETURNING {}",
+            ROUND_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Le tour suivant devient le tour courant ; sans effet si son ouverture l'a déjà fait avancer
+        let mut tontine = sqlx::query_as::<_, Tontine>(
+            "UPDATE tontines SET current_round = GREATEST(current_round, $1), updated_at = NOW()
+             WHERE id = $2
+             RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at"
+        )
+        .bind(round.round_number + 1)
+        .bind(round.tontine_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let open_rounds: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tontine_rounds WHERE tontine_id = $1 AND status IN ('pending', 'disbursing')"
+        )
+        .bind(round.tontine_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if open_rounds == 0 {
+            tontine = TontineRepository::transition(
+                conn,
+                round.tontine_id,
+                TontineStatus::Completed,
+                paid_by,
+                Some("Dernier tour versé"),
+            ).await?;
+        }
+
+        Ok((round, tontine))
+    }
+
+    pub async fn find_by_round(pool: &PgPool, round_id: Uuid) -> Result<RoundPayout, AppError> {
+        let payout = sqlx::query_as::<_, RoundPayout>(&format!(
+            "SELECT {} FROM round_payouts WHERE tontine_round_id = $1",
+            PAYOUT_COLUMNS
+        ))
+        .bind(round_id)
+        .fetch_optional(pool)
+        .await?;
+
+        payout.ok_or_else(|| AppError::NotFound("Aucun versement pour ce tour".to_string()))
+    }
+
+    pub async fn find_by_tontine(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<RoundPayout>, AppError> {
+        let payouts = sqlx::query_as::<_, RoundPayout>(&format!(
+            "SELECT {} FROM round_payouts WHERE tontine_id = $1 ORDER BY paid_at",
+            PAYOUT_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(payouts)
+    }
+}
+
+/// Tour contrôlé et montant calculé, prêt à être versé
+struct PreparedPayout {
+    round_id: Uuid,
+    tontine_id: Uuid,
+    round_number: i32,
+    beneficiary: Uuid,
+    collected: Decimal,
+    discount: Decimal,
+    amount: Decimal,
+    shortfall: Decimal,
+}
+
+impl PreparedPayout {
+    fn transaction_data(&self) -> CreateTransaction {
+        CreateTransaction {
+            tontine_id: self.tontine_id,
+            from_user_id: None,
+            to_user_id: Some(self.beneficiary),
+            amount: self.amount,
+            transaction_type: TransactionType::Payout,
+            description: Some(format!("Versement du tour {}", self.round_number)),
+        }
+    }
+}
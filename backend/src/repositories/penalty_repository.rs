@@ -0,0 +1,213 @@
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::penalties::{Penalty, PenaltyKind, PenaltyRule, PenaltyStatus, SetPenaltyRule};
+use crate::repositories::ledger_repository::LedgerRepository;
+use crate::errors::AppError;
+use crate::validation::Validator;
+
+const RULE_COLUMNS: &str = "tontine_id, kind, amount, per_day, grace_days, cap, created_at, updated_at";
+const PENALTY_COLUMNS: &str = "id, tontine_id, contribution_id, member_id, amount, days_late, status, assessed_at, settled_at";
+
+pub struct PenaltyRepository;
+
+impl PenaltyRepository {
+    pub async fn find_rule(pool: &PgPool, tontine_id: Uuid) -> Result<Option<PenaltyRule>, AppError> {
+        let rule = sqlx::query_as::<_, PenaltyRule>(&format!(
+            "SELECT {} FROM penalty_rules WHERE tontine_id = $1",
+            RULE_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    /// Crée ou remplace la règle d'amende de la tontine ; s'applique aux règlements suivants
+    pub async fn set_rule(pool: &PgPool, tontine_id: Uuid, rule_data: &SetPenaltyRule) -> Result<PenaltyRule, AppError> {
+        let mut validator = Validator::default();
+        validator
+            .positive("amount", rule_data.amount)
+            .check(
+                rule_data.kind != PenaltyKind::Percentage || rule_data.amount <= Decimal::from(100),
+                "amount",
+                "Le pourcentage de l'amende ne peut pas dépasser 100",
+            )
+            .check(rule_data.grace_days.is_none_or(|days| days >= 0), "grace_days", "Le nombre de jours de grâce ne doit pas être négatif")
+            .positive_opt("cap", rule_data.cap);
+        validator.finish()?;
+
+        let rule = sqlx::query_as::<_, PenaltyRule>(&format!(
+            "INSERT INTO penalty_rules (tontine_id, kind, amount, per_day, grace_days, cap)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (tontine_id) DO UPDATE
+             SET kind = EXCLUDED.kind, amount = EXCLUDED.amount, per_day = EXCLUDED.per_day,
+                 grace_days = EXCLUDED.grace_days, cap = EXCLUDED.cap, updated_at = NOW()
+             RETURNING {}",
+            RULE_COLUMNS
+        ))
+        .bind(tontine_id)
+        .bind(String::from(rule_data.kind))
+        .bind(rule_data.amount)
+        .bind(rule_data.per_day.unwrap_or(false))
+        .bind(rule_data.grace_days.unwrap_or(0))
+        .bind(rule_data.cap)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn delete_rule(pool: &PgPool, tontine_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM penalty_rules WHERE tontine_id = $1")
+            .bind(tontine_id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Aucune règle d'amende pour cette tontine".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Calcule l'amende d'un membre dont la cotisation du tour a été réglée après l'échéance.
+    /// L'amende est rattachée au versement qui complète la cotisation et porte sur le montant
+    /// attendu (`amount_per_member`). Sans effet si la cotisation n'est pas encore complète,
+    /// si la tontine n'a pas de règle ou si une amende a déjà été calculée pour ce tour.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn assess(conn: &mut PgConnection, contribution_id: Uuid) -> Result<Option<Penalty>, AppError> {
+        let row = sqlx::query(
+            "SELECT c.member_id, c.payment_date, c.payment_status, tr.round_date, tr.tontine_id,
+                    t.amount_per_member,
+                    (SELECT COALESCE(SUM(paid.amount), 0) FROM contributions paid
+                     WHERE paid.tontine_round_id = c.tontine_round_id AND paid.member_id = c.member_id
+                       AND paid.payment_status = 'paid') as paid_amount,
+                    EXISTS (SELECT 1 FROM penalties p
+                            JOIN contributions other ON p.contribution_id = other.id
+                            WHERE other.tontine_round_id = c.tontine_round_id
+                              AND other.member_id = c.member_id) as already_assessed
+             FROM contributions c
+             JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE c.id = $1"
+        )
+        .bind(contribution_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Contribution avec l'ID {} non trouvée", contribution_id)))?;
+
+        let due_date: Option<DateTime<Utc>> = row.get("round_date");
+        let due_date = match due_date {
+            Some(due_date) if row.get::<String, _>("payment_status") == "paid" => due_date,
+            _ => return Ok(None),
+        };
+        let expected: Decimal = row.get("amount_per_member");
+        if row.get::<Decimal, _>("paid_amount") < expected || row.get::<bool, _>("already_assessed") {
+            return Ok(None);
+        }
+        let tontine_id: Uuid = row.get("tontine_id");
+
+        let rule = sqlx::query_as::<_, PenaltyRule>(&format!(
+            "SELECT {} FROM penalty_rules WHERE tontine_id = $1",
+            RULE_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let rule = match rule {
+            Some(rule) => rule,
+            None => return Ok(None),
+        };
+
+        let days_late = rule.days_late(due_date, row.get("payment_date"));
+        let fine = rule.fine_for(expected, days_late);
+        if fine <= Decimal::ZERO {
+            return Ok(None);
+        }
+
+        let penalty = sqlx::query_as::<_, Penalty>(&format!(
+            "INSERT INTO penalties (tontine_id, contribution_id, member_id, amount, days_late)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (contribution_id) DO NOTHING
+             RETURNING {}",
+            PENALTY_COLUMNS
+        ))
+        .bind(tontine_id)
+        .bind(contribution_id)
+        .bind(row.get::<Uuid, _>("member_id"))
+        .bind(fine)
+        .bind(days_late as i32)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(penalty)
+    }
+
+    pub async fn find_by_tontine(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<Penalty>, AppError> {
+        let penalties = sqlx::query_as::<_, Penalty>(&format!(
+            "SELECT {} FROM penalties WHERE tontine_id = $1 ORDER BY assessed_at DESC",
+            PENALTY_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(penalties)
+    }
+
+    pub async fn find_by_member(pool: &PgPool, member_id: Uuid) -> Result<Vec<Penalty>, AppError> {
+        let penalties = sqlx::query_as::<_, Penalty>(&format!(
+            "SELECT {} FROM penalties WHERE member_id = $1 ORDER BY assessed_at DESC",
+            PENALTY_COLUMNS
+        ))
+        .bind(member_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(penalties)
+    }
+
+    pub async fn find_tontine_id(pool: &PgPool, penalty_id: Uuid) -> Result<Uuid, AppError> {
+        let tontine_id: Option<Uuid> = sqlx::query_scalar("SELECT tontine_id FROM penalties WHERE id = $1")
+            .bind(penalty_id)
+            .fetch_optional(pool)
+            .await?;
+
+        tontine_id.ok_or_else(|| AppError::NotFound(format!("Amende avec l'ID {} non trouvée", penalty_id)))
+    }
+
+    /// Règle ou annule une amende encore due
+    pub async fn settle(pool: &PgPool, penalty_id: Uuid, status: PenaltyStatus) -> Result<Penalty, AppError> {
+        if status == PenaltyStatus::Unpaid {
+            return Err(AppError::ValidationError("Une amende ne peut être que payée ou annulée".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+
+        let penalty = sqlx::query_as::<_, Penalty>(&format!(
+            "UPDATE penalties SET status = $1, settled_at = $2
+             WHERE id = $3 AND status = 'unpaid'
+             RETURNING {}",
+            PENALTY_COLUMNS
+        ))
+        .bind(String::from(status))
+        .bind(Utc::now())
+        .bind(penalty_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::ValidationError("Cette amende n'est plus due".to_string()))?;
+
+        if status == PenaltyStatus::Paid {
+            LedgerRepository::record_penalty_payment(&mut tx, &penalty).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(penalty)
+    }
+}
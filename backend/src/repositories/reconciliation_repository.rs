@@ -0,0 +1,287 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::payment_transactions::{PaymentProviderKind, PaymentTransactionStatus};
+use crate::model::reconciliation::{
+    NewReconciliationItem, NewReconciliationRun, ReconciliationItem, ReconciliationItemKind, ReconciliationReport,
+    ReconciliationResolution, ReconciliationRun, ReconciliationSummary, RecordedPayment,
+};
+use crate::repositories::contributions_repository::ContributionRepository;
+use crate::repositories::payment_transaction_repository::PaymentTransactionRepository;
+use crate::errors::AppError;
+
+const RUN_COLUMNS: &str = "id, tontine_id, provider, source_format, period_start, period_end, lines_count, imported_by, created_at";
+const ITEM_COLUMNS: &str = "id, run_id, kind, statement_reference, statement_amount, statement_phone, statement_date, payment_transaction_id, contribution_id, recorded_amount, resolution, resolution_note, resolved_by, resolved_at, created_at";
+
+pub struct ReconciliationRepository;
+
+impl ReconciliationRepository {
+    /// Cotisations payées de la tontine qui peuvent figurer au relevé de l'opérateur : hors espèces,
+    /// saisies à la main ou réglées par ce même opérateur, sur la période si elle est connue
+    pub async fn find_recorded_payments(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        provider: PaymentProviderKind,
+        period: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Result<Vec<RecordedPayment>, AppError> {
+        let records = sqlx::query_as::<_, RecordedPayment>(
+            "SELECT c.id as contribution_id, c.amount, c.payment_date, u.phone as member_phone,
+                    pt.id as payment_transaction_id, pt.payment_reference, pt.provider_reference
+             FROM contributions c
+             JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
+             JOIN tontine_members tm ON c.member_id = tm.id
+             JOIN users u ON tm.user_id = u.id
+             LEFT JOIN payment_transactions pt ON pt.contribution_id = c.id AND pt.status = 'successful'
+             WHERE tr.tontine_id = $1
+               AND c.payment_status = 'paid'
+               AND COALESCE(c.payment_method, '') <> 'cash'
+               AND (pt.id IS NULL OR pt.provider = $2)
+               AND ($3::timestamptz IS NULL OR c.payment_date >= $3)
+               AND ($4::timestamptz IS NULL OR c.payment_date < $4)
+             ORDER BY c.payment_date"
+        )
+        .bind(tontine_id)
+        .bind(String::from(provider))
+        .bind(period.map(|(start, _)| start))
+        .bind(period.map(|(_, end)| end))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Références du relevé qui désignent des paiements d'autres tontines, à ignorer
+    pub async fn find_foreign_references(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        provider: PaymentProviderKind,
+        references: &[String],
+    ) -> Result<Vec<String>, AppError> {
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT pt.payment_reference, pt.provider_reference
+             FROM payment_transactions pt
+             JOIN contributions c ON pt.contribution_id = c.id
+             JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
+             WHERE pt.provider = $1 AND tr.tontine_id <> $2
+               AND (pt.payment_reference = ANY($3) OR pt.provider_reference = ANY($3))"
+        )
+        .bind(String::from(provider))
+        .bind(tontine_id)
+        .bind(references)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter()
+            .flat_map(|(payment_reference, provider_reference)| std::iter::once(payment_reference).chain(provider_reference))
+            .collect())
+    }
+
+    /// Enregistre l'import et ses résultats en une seule transaction
+    pub async fn create_run(
+        pool: &PgPool,
+        run: &NewReconciliationRun,
+        items: &[NewReconciliationItem],
+    ) -> Result<ReconciliationReport, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let saved_run = sqlx::query_as::<_, ReconciliationRun>(&format!(
+            "INSERT INTO reconciliation_runs (tontine_id, provider, source_format, period_start, period_end, lines_count, imported_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING {}",
+            RUN_COLUMNS
+        ))
+        .bind(run.tontine_id)
+        .bind(String::from(run.provider))
+        .bind(String::from(run.format))
+        .bind(run.period.map(|(start, _)| start))
+        .bind(run.period.map(|(_, end)| end))
+        .bind(run.lines_count as i32)
+        .bind(run.imported_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut saved = Vec::with_capacity(items.len());
+        for item in items {
+            let line = item.line.as_ref();
+            let record = item.record.as_ref();
+
+            let saved_item = sqlx::query_as::<_, ReconciliationItem>(&format!(
+                "INSERT INTO reconciliation_items (run_id, kind, statement_reference, statement_amount, statement_phone, statement_date,
+                                                   payment_transaction_id, contribution_id, recorded_amount)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 RETURNING {}",
+                ITEM_COLUMNS
+            ))
+            .bind(saved_run.id)
+            .bind(String::from(item.kind))
+            .bind(line.and_then(|line| line.reference.clone()))
+            .bind(line.map(|line| line.amount))
+            .bind(line.and_then(|line| line.phone.clone()))
+            .bind(line.and_then(|line| line.date))
+            .bind(record.and_then(|record| record.payment_transaction_id))
+            .bind(record.map(|record| record.contribution_id))
+            .bind(record.map(|record| record.amount))
+            .fetch_one(&mut *tx)
+            .await?;
+
+            saved.push(saved_item);
+        }
+
+        tx.commit().await?;
+
+        Ok(Self::report(saved_run, saved))
+    }
+
+    pub async fn find_runs(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<ReconciliationRun>, AppError> {
+        let runs = sqlx::query_as::<_, ReconciliationRun>(&format!(
+            "SELECT {} FROM reconciliation_runs WHERE tontine_id = $1 ORDER BY created_at DESC",
+            RUN_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    pub async fn find_run(pool: &PgPool, run_id: Uuid) -> Result<ReconciliationRun, AppError> {
+        let run = sqlx::query_as::<_, ReconciliationRun>(&format!(
+            "SELECT {} FROM reconciliation_runs WHERE id = $1",
+            RUN_COLUMNS
+        ))
+        .bind(run_id)
+        .fetch_optional(pool)
+        .await?;
+
+        run.ok_or_else(|| AppError::NotFound(format!("Rapprochement avec l'ID {} non trouvé", run_id)))
+    }
+
+    pub async fn get_report(pool: &PgPool, run_id: Uuid) -> Result<ReconciliationReport, AppError> {
+        let run = Self::find_run(pool, run_id).await?;
+
+        let items = sqlx::query_as::<_, ReconciliationItem>(&format!(
+            "SELECT {} FROM reconciliation_items WHERE run_id = $1
+             ORDER BY (kind = 'matched'), created_at",
+            ITEM_COLUMNS
+        ))
+        .bind(run_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Self::report(run, items))
+    }
+
+    pub async fn find_item(pool: &PgPool, item_id: Uuid) -> Result<ReconciliationItem, AppError> {
+        let item = sqlx::query_as::<_, ReconciliationItem>(&format!(
+            "SELECT {} FROM reconciliation_items WHERE id = $1",
+            ITEM_COLUMNS
+        ))
+        .bind(item_id)
+        .fetch_optional(pool)
+        .await?;
+
+        item.ok_or_else(|| AppError::NotFound(format!("Écart avec l'ID {} non trouvé", item_id)))
+    }
+
+    /// Résout un écart. `ContributionFailed` ne s'applique qu'à une cotisation absente du relevé :
+    /// la cotisation, son paiement et sa transaction passent en échec dans la même transaction.
+    pub async fn resolve(
+        pool: &PgPool,
+        item_id: Uuid,
+        resolution: ReconciliationResolution,
+        note: Option<&str>,
+        resolved_by: Uuid,
+    ) -> Result<ReconciliationItem, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let item = sqlx::query_as::<_, ReconciliationItem>(&format!(
+            "SELECT {} FROM reconciliation_items WHERE id = $1 FOR UPDATE",
+            ITEM_COLUMNS
+        ))
+        .bind(item_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Écart avec l'ID {} non trouvé", item_id)))?;
+
+        let kind = ReconciliationItemKind::try_from(item.kind.clone()).map_err(AppError::InternalServerError)?;
+        if kind == ReconciliationItemKind::Matched {
+            return Err(AppError::ValidationError("Cette ligne est rapprochée, il n'y a rien à résoudre".to_string()));
+        }
+        if item.resolution.is_some() {
+            return Err(AppError::ValidationError("Cet écart est déjà résolu".to_string()));
+        }
+
+        if resolution == ReconciliationResolution::ContributionFailed {
+            let contribution_id = match (kind, item.contribution_id) {
+                (ReconciliationItemKind::MissingInStatement, Some(contribution_id)) => contribution_id,
+                _ => return Err(AppError::ValidationError(
+                    "Seule une cotisation absente du relevé peut être passée en échec".to_string()
+                )),
+            };
+
+            Self::fail_contribution(&mut tx, contribution_id, item.payment_transaction_id, item.run_id).await?;
+        }
+
+        let item = sqlx::query_as::<_, ReconciliationItem>(&format!(
+            "UPDATE reconciliation_items
+             SET resolution = $2, resolution_note = $3, resolved_by = $4, resolved_at = NOW()
+             WHERE id = $1
+             RETURNING {}",
+            ITEM_COLUMNS
+        ))
+        .bind(item_id)
+        .bind(String::from(resolution))
+        .bind(note)
+        .bind(resolved_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(item)
+    }
+
+    /// Annule une cotisation jamais arrivée ; sa transaction est contre-passée au grand livre avec elle
+    async fn fail_contribution(
+        conn: &mut PgConnection,
+        contribution_id: Uuid,
+        payment_transaction_id: Option<Uuid>,
+        run_id: Uuid,
+    ) -> Result<(), AppError> {
+        ContributionRepository::set_failed(conn, contribution_id).await?;
+
+        let Some(payment_id) = payment_transaction_id else {
+            return Ok(());
+        };
+
+        PaymentTransactionRepository::record_response(
+            conn,
+            payment_id,
+            PaymentTransactionStatus::Failed,
+            None,
+            &serde_json::json!({ "reconciliation_run_id": run_id }),
+        ).await?;
+
+        Ok(())
+    }
+
+    fn report(run: ReconciliationRun, items: Vec<ReconciliationItem>) -> ReconciliationReport {
+        let mut summary = ReconciliationSummary::default();
+        for item in &items {
+            match ReconciliationItemKind::try_from(item.kind.clone()) {
+                Ok(ReconciliationItemKind::Matched) => summary.matched += 1,
+                Ok(ReconciliationItemKind::MissingInStatement) => summary.missing_in_statement += 1,
+                Ok(ReconciliationItemKind::MissingInRecords) => summary.missing_in_records += 1,
+                Ok(ReconciliationItemKind::Duplicate) => summary.duplicate += 1,
+                Ok(ReconciliationItemKind::AmountMismatch) => summary.amount_mismatch += 1,
+                Err(_) => {}
+            }
+            if item.kind != String::from(ReconciliationItemKind::Matched) && item.resolution.is_none() {
+                summary.open += 1;
+            }
+        }
+
+        ReconciliationReport { run, summary, items }
+    }
+}
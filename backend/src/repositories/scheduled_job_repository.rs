@@ -0,0 +1,137 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::scheduled_jobs::{JobStatus, JobType, ScheduledJob};
+use crate::errors::AppError;
+
+const JOB_COLUMNS: &str = "id, job_type, tontine_id, tontine_round_id, run_at, status, attempts, last_error, locked_at, completed_at, created_at";
+
+pub struct ScheduledJobRepository;
+
+impl ScheduledJobRepository {
+    /// Planifie une tâche ; sans effet si la même tâche existe déjà pour ce tour.
+    /// Prend une connexion pour pouvoir s'exécuter dans la transaction de l'appelant.
+    pub async fn enqueue(
+        conn: &mut PgConnection,
+        job_type: JobType,
+        tontine_id: Uuid,
+        tontine_round_id: Option<Uuid>,
+        run_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO scheduled_jobs (job_type, tontine_id, tontine_round_id, run_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (job_type, tontine_round_id) DO NOTHING"
+        )
+        .bind(String::from(job_type))
+        .bind(tontine_id)
+        .bind(tontine_round_id)
+        .bind(run_at)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Planifie une tâche, en réarmant celle qui existe déjà pour ce tour (nouvel essai
+    /// après une exécution terminée ou abandonnée). S'exécute dans la transaction de l'appelant.
+    pub async fn reschedule(
+        conn: &mut PgConnection,
+        job_type: JobType,
+        tontine_id: Uuid,
+        tontine_round_id: Option<Uuid>,
+        run_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO scheduled_jobs (job_type, tontine_id, tontine_round_id, run_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (job_type, tontine_round_id) DO UPDATE
+             SET run_at = EXCLUDED.run_at, status = 'pending', attempts = 0, last_error = NULL,
+                 locked_at = NULL, completed_at = NULL"
+        )
+        .bind(String::from(job_type))
+        .bind(tontine_id)
+        .bind(tontine_round_id)
+        .bind(run_at)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Réserve les tâches arrivées à échéance, ainsi que celles restées bloquées
+    /// après un arrêt du serveur (verrou antérieur à `stale_before`)
+    pub async fn claim_due(pool: &PgPool, limit: i64, stale_before: DateTime<Utc>) -> Result<Vec<ScheduledJob>, AppError> {
+        let jobs = sqlx::query_as::<_, ScheduledJob>(&format!(
+            "UPDATE scheduled_jobs
+             SET status = 'running', locked_at = NOW(), attempts = attempts + 1
+             WHERE id IN (
+                SELECT id FROM scheduled_jobs
+                WHERE (status = 'pending' AND run_at <= NOW())
+                   OR (status = 'running' AND locked_at < $1)
+                ORDER BY run_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(stale_before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn mark_done(pool: &PgPool, job_id: Uuid) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE scheduled_jobs SET status = $1, completed_at = NOW(), locked_at = NULL, last_error = NULL WHERE id = $2"
+        )
+        .bind(String::from(JobStatus::Done))
+        .bind(job_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replanifie la tâche à `retry_at`, ou l'abandonne une fois le nombre maximal d'essais atteint
+    pub async fn mark_failed(
+        pool: &PgPool,
+        job_id: Uuid,
+        error: &str,
+        max_attempts: i32,
+        retry_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE scheduled_jobs
+             SET status = CASE WHEN attempts >= $1 THEN 'failed' ELSE 'pending' END,
+                 run_at = CASE WHEN attempts >= $1 THEN run_at ELSE $2 END,
+                 last_error = $3,
+                 locked_at = NULL
+             WHERE id = $4"
+        )
+        .bind(max_attempts)
+        .bind(retry_at)
+        .bind(error)
+        .bind(job_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_tontine(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<ScheduledJob>, AppError> {
+        let jobs = sqlx::query_as::<_, ScheduledJob>(&format!(
+            "SELECT {} FROM scheduled_jobs WHERE tontine_id = $1 ORDER BY run_at",
+            JOB_COLUMNS
+        ))
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(jobs)
+    }
+}
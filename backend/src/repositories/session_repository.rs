@@ -0,0 +1,212 @@
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::sessions::{DeviceInfo, Session};
+use crate::errors::AppError;
+
+const SESSION_COLUMNS: &str = "id, user_id, user_agent, ip_address, expires_at, last_used_at, revoked_at, two_factor_verified_at, created_at";
+
+pub struct SessionRepository;
+
+impl SessionRepository {
+    /// Ouvre une session et enregistre son premier jeton de rafraîchissement
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        refresh_token_hash: &str,
+        device: &DeviceInfo,
+        expires_at: DateTime<Utc>,
+        two_factor_verified_at: Option<DateTime<Utc>>,
+    ) -> Result<Session, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let session = sqlx::query_as::<_, Session>(&format!(
+            "INSERT INTO sessions (user_id, user_agent, ip_address, expires_at, two_factor_verified_at)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING {}",
+            SESSION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(&device.user_agent)
+        .bind(&device.ip_address)
+        .bind(expires_at)
+        .bind(two_factor_verified_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO refresh_tokens (session_id, token_hash) VALUES ($1, $2)")
+            .bind(session.id)
+            .bind(refresh_token_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(session)
+    }
+
+    /// Remplace un jeton de rafraîchissement par un nouveau.
+    /// La réutilisation d'un jeton déjà échangé révoque toute la session.
+    pub async fn rotate(
+        pool: &PgPool,
+        refresh_token_hash: &str,
+        new_refresh_token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError> {
+        let invalid = || AppError::AuthenticationError("Jeton de rafraîchissement invalide ou expiré".to_string());
+        let mut tx = pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT rt.id, rt.session_id, rt.used_at, s.revoked_at, s.expires_at
+             FROM refresh_tokens rt
+             JOIN sessions s ON rt.session_id = s.id
+             WHERE rt.token_hash = $1
+             FOR UPDATE OF rt, s"
+        )
+        .bind(refresh_token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(invalid)?;
+
+        let token_id: Uuid = row.get("id");
+        let session_id: Uuid = row.get("session_id");
+        let used_at: Option<DateTime<Utc>> = row.get("used_at");
+        let revoked_at: Option<DateTime<Utc>> = row.get("revoked_at");
+        let session_expires_at: DateTime<Utc> = row.get("expires_at");
+        let now = Utc::now();
+
+        if revoked_at.is_some() || session_expires_at <= now {
+            return Err(invalid());
+        }
+
+        if used_at.is_some() {
+            // Jeton rejoué : il a probablement été volé, on coupe la session
+            sqlx::query("UPDATE sessions SET revoked_at = $1 WHERE id = $2")
+                .bind(now)
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            return Err(AppError::AuthenticationError(
+                "Jeton de rafraîchissement déjà utilisé, la session a été révoquée".to_string(),
+            ));
+        }
+
+        sqlx::query("UPDATE refresh_tokens SET used_at = $1 WHERE id = $2")
+            .bind(now)
+            .bind(token_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO refresh_tokens (session_id, token_hash) VALUES ($1, $2)")
+            .bind(session_id)
+            .bind(new_refresh_token_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        let session = sqlx::query_as::<_, Session>(&format!(
+            "UPDATE sessions SET last_used_at = $1, expires_at = $2 WHERE id = $3 RETURNING {}",
+            SESSION_COLUMNS
+        ))
+        .bind(now)
+        .bind(expires_at)
+        .bind(session_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(session)
+    }
+
+    /// Une session est active si elle n'est ni révoquée ni expirée et que le compte est actif
+    pub async fn is_active(pool: &PgPool, session_id: Uuid, user_id: Uuid) -> Result<bool, AppError> {
+        let active: Option<bool> = sqlx::query_scalar(
+            "SELECT s.revoked_at IS NULL AND s.expires_at > NOW() AND COALESCE(u.is_active, false)
+             FROM sessions s
+             JOIN users u ON s.user_id = u.id
+             WHERE s.id = $1 AND s.user_id = $2"
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(active.unwrap_or(false))
+    }
+
+    /// Enregistre une vérification du second facteur pour la session
+    pub async fn mark_two_factor_verified(pool: &PgPool, session_id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE sessions SET two_factor_verified_at = $1 WHERE id = $2")
+            .bind(Utc::now())
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn two_factor_verified_at(pool: &PgPool, session_id: Uuid) -> Result<Option<DateTime<Utc>>, AppError> {
+        let verified_at: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(
+            "SELECT two_factor_verified_at FROM sessions WHERE id = $1"
+        )
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(verified_at.flatten())
+    }
+
+    pub async fn find_active_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let sessions = sqlx::query_as::<_, Session>(&format!(
+            "SELECT {} FROM sessions
+             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+             ORDER BY last_used_at DESC",
+            SESSION_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    pub async fn revoke(pool: &PgPool, session_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query(
+            "UPDATE sessions SET revoked_at = $1 WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL"
+        )
+        .bind(Utc::now())
+        .bind(session_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Session avec l'ID {} non trouvée", session_id)));
+        }
+
+        Ok(())
+    }
+
+    /// Révoque toutes les sessions de l'utilisateur, sauf éventuellement la session courante.
+    /// Prend une connexion pour pouvoir s'exécuter dans la transaction de l'appelant.
+    pub async fn revoke_all_for_user(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        keep_session_id: Option<Uuid>,
+    ) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "UPDATE sessions SET revoked_at = $1
+             WHERE user_id = $2 AND revoked_at IS NULL AND ($3::uuid IS NULL OR id <> $3)"
+        )
+        .bind(Utc::now())
+        .bind(user_id)
+        .bind(keep_session_id)
+        .execute(conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,20 +1,27 @@
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-use crate::model::tontine_members::{TontineMember, CreateTontineMember, UpdateTontineMember, TontineMemberWithUser};
+use crate::model::tontine_members::{TontineMember, CreateTontineMember, UpdateTontineMember, TontineMemberWithUser, TontineRole};
+use crate::model::tontine::TontineStatus;
 use crate::errors::AppError;
 
 pub struct TontineMemberRepository;
 
 impl TontineMemberRepository {
 
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<TontineMemberWithUser>, AppError> {
+    pub async fn find_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<TontineMemberWithUser>, AppError> {
         let members = sqlx::query(
             "SELECT tm.*, u.email as user_email, u.phone as user_phone, u.full_name as user_full_name
              FROM tontine_members tm
              JOIN users u ON tm.user_id = u.id
+             WHERE tm.tontine_id IN (
+                SELECT id FROM tontines WHERE created_by = $1
+                UNION
+                SELECT tontine_id FROM tontine_members WHERE user_id = $1 AND is_active = true
+             )
              ORDER BY tm.join_date DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -25,6 +32,7 @@ impl TontineMemberRepository {
             join_date: row.get("join_date"),
             is_active: row.get("is_active"),
             position_order: row.get("position_order"),
+            role: row.get("role"),
             user_email: row.get("user_email"),
             user_phone: row.get("user_phone"),
             user_full_name: row.get("user_full_name"),
@@ -53,6 +61,7 @@ impl TontineMemberRepository {
                     join_date: row.get("join_date"),
                     is_active: row.get("is_active"),
                     position_order: row.get("position_order"),
+                    role: row.get("role"),
                     user_email: row.get("user_email"),
                     user_phone: row.get("user_phone"),
                     user_full_name: row.get("user_full_name"),
@@ -82,6 +91,7 @@ impl TontineMemberRepository {
             join_date: row.get("join_date"),
             is_active: row.get("is_active"),
             position_order: row.get("position_order"),
+            role: row.get("role"),
             user_email: row.get("user_email"),
             user_phone: row.get("user_phone"),
             user_full_name: row.get("user_full_name"),
@@ -109,6 +119,7 @@ impl TontineMemberRepository {
             join_date: row.get("join_date"),
             is_active: row.get("is_active"),
             position_order: row.get("position_order"),
+            role: row.get("role"),
             user_email: row.get("user_email"),
             user_phone: row.get("user_phone"),
             user_full_name: row.get("user_full_name"),
@@ -121,18 +132,24 @@ impl TontineMemberRepository {
     
     pub async fn create(pool: &PgPool, member_data: &CreateTontineMember) -> Result<TontineMember, AppError> {
         // Vérifier si la tontine existe
-        let tontine_exists = sqlx::query("SELECT id FROM tontines WHERE id = $1")
-            .bind(&member_data.tontine_id)
+        let tontine_status: Option<String> = sqlx::query_scalar("SELECT status FROM tontines WHERE id = $1")
+            .bind(member_data.tontine_id)
             .fetch_optional(pool)
             .await?;
 
-        if tontine_exists.is_none() {
-            return Err(AppError::ValidationError("La tontine spécifiée n'existe pas".to_string()));
+        let tontine_status = match tontine_status {
+            Some(status) => TontineStatus::try_from(status).map_err(AppError::InternalServerError)?,
+            None => return Err(AppError::ValidationError("La tontine spécifiée n'existe pas".to_string())),
+        };
+
+        // Les adhésions sont closes une fois la tontine démarrée
+        if !tontine_status.allows_terms_change() {
+            return Err(AppError::ValidationError("La tontine n'accepte plus de nouveaux membres".to_string()));
         }
 
         // Vérifier si l'utilisateur existe
         let user_exists = sqlx::query("SELECT id FROM users WHERE id = $1")
-            .bind(&member_data.user_id)
+            .bind(member_data.user_id)
             .fetch_optional(pool)
             .await?;
 
@@ -142,8 +159,8 @@ impl TontineMemberRepository {
 
         // Vérifier si l'utilisateur est déjà membre
         let existing_member = sqlx::query("SELECT id FROM tontine_members WHERE tontine_id = $1 AND user_id = $2")
-            .bind(&member_data.tontine_id)
-            .bind(&member_data.user_id)
+            .bind(member_data.tontine_id)
+            .bind(member_data.user_id)
             .fetch_optional(pool)
             .await?;
 
@@ -153,12 +170,12 @@ impl TontineMemberRepository {
 
         // Vérifier le nombre maximum de membres
         let member_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tontine_members WHERE tontine_id = $1 AND is_active = true")
-            .bind(&member_data.tontine_id)
+            .bind(member_data.tontine_id)
             .fetch_one(pool)
             .await?;
 
         let tontine_max_members: i64 = sqlx::query_scalar("SELECT max_members FROM tontines WHERE id = $1")
-            .bind(&member_data.tontine_id)
+            .bind(member_data.tontine_id)
             .fetch_one(pool)
             .await?;
 
@@ -173,7 +190,7 @@ impl TontineMemberRepository {
                 let max_position: Option<i32> = sqlx::query_scalar(
                     "SELECT MAX(position_order) FROM tontine_members WHERE tontine_id = $1"
                 )
-                .bind(&member_data.tontine_id)
+                .bind(member_data.tontine_id)
                 .fetch_one(pool)
                 .await?;
 
@@ -181,14 +198,17 @@ impl TontineMemberRepository {
             }
         };
 
+        let role: String = member_data.role.unwrap_or(TontineRole::Member).into();
+
         let member = sqlx::query_as::<_, TontineMember>(
-            "INSERT INTO tontine_members (tontine_id, user_id, position_order) 
-             VALUES ($1, $2, $3) 
-             RETURNING id, tontine_id, user_id, join_date, is_active, position_order"
+            "INSERT INTO tontine_members (tontine_id, user_id, position_order, role) 
+             VALUES ($1, $2, $3, $4) 
+             RETURNING id, tontine_id, user_id, join_date, is_active, position_order, role"
         )
-        .bind(&member_data.tontine_id)
-        .bind(&member_data.user_id)
+        .bind(member_data.tontine_id)
+        .bind(member_data.user_id)
         .bind(position_order)
+        .bind(&role)
         .fetch_one(pool)
         .await?;
 
@@ -200,73 +220,57 @@ impl TontineMemberRepository {
     pub async fn update(pool: &PgPool, member_id: Uuid, member_data: &UpdateTontineMember) -> Result<TontineMember, AppError> {
     // Vérifier si le membre existe
     let existing_member = sqlx::query_as::<_, TontineMember>(
-        "SELECT id, tontine_id, user_id, join_date, is_active, position_order FROM tontine_members WHERE id = $1"
+        "SELECT id, tontine_id, user_id, join_date, is_active, position_order, role FROM tontine_members WHERE id = $1"
     )
     .bind(member_id)
     .fetch_optional(pool)
     .await?;
 
-    if existing_member.is_none() {
-        return Err(AppError::NotFound(format!("Membre avec l'ID {} non trouvé", member_id)));
+    let existing_member = match existing_member {
+        Some(member) => member,
+        None => return Err(AppError::NotFound(format!("Membre avec l'ID {} non trouvé", member_id))),
+    };
+
+    // Une fois la tontine démarrée, le calendrier repose sur les membres actifs et leurs positions
+    let changes_membership = member_data.is_active.is_some_and(|is_active| is_active != existing_member.is_active)
+        || member_data.position_order.is_some_and(|position| Some(position) != existing_member.position_order);
+    if changes_membership && !Self::tontine_status(pool, existing_member.tontine_id).await?.allows_terms_change() {
+        return Err(AppError::ValidationError(
+            "La tontine a démarré : seul le rôle d'un membre peut encore être modifié".to_string()
+        ));
     }
 
-    // Construire la requête en fonction des champs fournis
-    match (member_data.is_active, member_data.position_order) {
-        (Some(is_active), Some(position_order)) => {
-            // Les deux champs sont fournis
-            let member = sqlx::query_as::<_, TontineMember>(
-                "UPDATE tontine_members SET is_active = $1, position_order = $2 
-                 WHERE id = $3 
-                 RETURNING id, tontine_id, user_id, join_date, is_active, position_order"
-            )
-            .bind(is_active)
-            .bind(position_order)
-            .bind(member_id)
-            .fetch_one(pool)
-            .await?;
-            Ok(member)
-        }
-        (Some(is_active), None) => {
-            // Seul is_active est fourni
-            let member = sqlx::query_as::<_, TontineMember>(
-                "UPDATE tontine_members SET is_active = $1 
-                 WHERE id = $2 
-                 RETURNING id, tontine_id, user_id, join_date, is_active, position_order"
-            )
-            .bind(is_active)
-            .bind(member_id)
-            .fetch_one(pool)
-            .await?;
-            Ok(member)
-        }
-        (None, Some(position_order)) => {
-            // Seul position_order est fourni
-            let member = sqlx::query_as::<_, TontineMember>(
-                "UPDATE tontine_members SET position_order = $1 
-                 WHERE id = $2 
-                 RETURNING id, tontine_id, user_id, join_date, is_active, position_order"
-            )
-            .bind(position_order)
-            .bind(member_id)
-            .fetch_one(pool)
-            .await?;
-            Ok(member)
-        }
-        (None, None) => {
-            // Aucun champ fourni, retourner le membre existant
-            Ok(existing_member.unwrap())
-        }
-    }
+    // Mettre à jour seulement les champs fournis
+    let role: Option<String> = member_data.role.map(|role| role.into());
+
+    let member = sqlx::query_as::<_, TontineMember>(
+        "UPDATE tontine_members 
+         SET is_active = COALESCE($1, is_active), position_order = COALESCE($2, position_order), role = COALESCE($3, role) 
+         WHERE id = $4 
+         RETURNING id, tontine_id, user_id, join_date, is_active, position_order, role"
+    )
+    .bind(member_data.is_active)
+    .bind(member_data.position_order)
+    .bind(role)
+    .bind(member_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(member)
 }
 
     pub async fn delete(pool: &PgPool, member_id: Uuid) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM tontine_members WHERE id = $1")
-            .bind(member_id)
-            .execute(pool)
-            .await?;
+        let result = sqlx::query(
+            "DELETE FROM tontine_members
+             WHERE id = $1
+               AND tontine_id IN (SELECT id FROM tontines WHERE status IN ('draft', 'recruiting'))"
+        )
+        .bind(member_id)
+        .execute(pool)
+        .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(format!("Membre avec l'ID {} non trouvé", member_id)));
+            return Err(Self::membership_frozen(pool, member_id, "retiré").await);
         }
 
         Ok(())
@@ -276,13 +280,42 @@ impl TontineMemberRepository {
         let member = sqlx::query_as::<_, TontineMember>(
             "UPDATE tontine_members SET is_active = false 
              WHERE id = $1 
-             RETURNING id, tontine_id, user_id, join_date, is_active, position_order"
+               AND tontine_id IN (SELECT id FROM tontines WHERE status IN ('draft', 'recruiting'))
+             RETURNING id, tontine_id, user_id, join_date, is_active, position_order, role"
         )
         .bind(member_id)
-        .fetch_one(pool)
+        .fetch_optional(pool)
         .await?;
 
-        Ok(member)
+        match member {
+            Some(member) => Ok(member),
+            None => Err(Self::membership_frozen(pool, member_id, "désactivé").await),
+        }
+    }
+
+    async fn tontine_status(pool: &PgPool, tontine_id: Uuid) -> Result<TontineStatus, AppError> {
+        let status: String = sqlx::query_scalar("SELECT status FROM tontines WHERE id = $1")
+            .bind(tontine_id)
+            .fetch_one(pool)
+            .await?;
+
+        TontineStatus::try_from(status).map_err(AppError::InternalServerError)
+    }
+
+    /// Erreur d'un retrait ou d'une désactivation refusé : membre absent ou tontine déjà démarrée
+    async fn membership_frozen(pool: &PgPool, member_id: Uuid, action: &str) -> AppError {
+        let exists = sqlx::query("SELECT id FROM tontine_members WHERE id = $1")
+            .bind(member_id)
+            .fetch_optional(pool)
+            .await;
+
+        match exists {
+            Ok(Some(_)) => AppError::ValidationError(format!(
+                "La tontine a démarré : le membre ne peut plus être {}", action
+            )),
+            Ok(None) => AppError::NotFound(format!("Membre avec l'ID {} non trouvé", member_id)),
+            Err(e) => e.into(),
+        }
     }
 
     pub async fn get_tontine_member_count(pool: &PgPool, tontine_id: Uuid) -> Result<i64, AppError> {
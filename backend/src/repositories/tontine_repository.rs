@@ -1,21 +1,25 @@
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
 use uuid::Uuid;
 use chrono::Utc;
 use rust_decimal::Decimal;
 
-use crate::model::tontine::{Tontine, CreateTontine, UpdateTontine};
+use crate::model::tontine::{Tontine, CreateTontine, UpdateTontine, TontineStatus, TontineStatusChange, PayoutStrategy, BidDiscountPolicy, ShortfallPolicy, MemberRefund, TontineCancellation};
 use crate::errors::AppError;
+use crate::validation::Validator;
 
 pub struct TontineRepository;
 
 impl TontineRepository {
     
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Tontine>, AppError> {
+    pub async fn find_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<Tontine>, AppError> {
         let tontines = sqlx::query_as::<_, Tontine>(
-            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at 
+            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at 
              FROM tontines 
+             WHERE created_by = $1
+                OR id IN (SELECT tontine_id FROM tontine_members WHERE user_id = $1 AND is_active = true)
              ORDER BY created_at DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -24,7 +28,7 @@ impl TontineRepository {
 
     pub async fn find_by_id(pool: &PgPool, tontine_id: Uuid) -> Result<Tontine, AppError> {
         let tontine = sqlx::query_as::<_, Tontine>(
-            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at 
+            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at 
              FROM tontines 
              WHERE id = $1"
         )
@@ -40,7 +44,7 @@ impl TontineRepository {
 
     pub async fn find_by_creator(pool: &PgPool, user_id: Uuid) -> Result<Vec<Tontine>, AppError> {
         let tontines = sqlx::query_as::<_, Tontine>(
-            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at 
+            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at 
              FROM tontines 
              WHERE created_by = $1 
              ORDER BY created_at DESC"
@@ -52,14 +56,10 @@ impl TontineRepository {
         Ok(tontines)
     }
 
-    pub async fn create(pool: &PgPool, tontine_data: &CreateTontine, user_id: String) -> Result<Tontine, AppError> {
-    // Convertir la String en UUID
-    let user_uuid = uuid::Uuid::parse_str(&user_id)
-        .map_err(|_| AppError::ValidationError("ID utilisateur invalide".to_string()))?;
-
+    pub async fn create(pool: &PgPool, tontine_data: &CreateTontine, user_id: Uuid) -> Result<Tontine, AppError> {
     // Vérifier si l'utilisateur créateur existe
     let user_exists = sqlx::query("SELECT id FROM users WHERE id = $1")
-        .bind(&user_uuid) // Utiliser l'UUID converti
+        .bind(user_id)
         .fetch_optional(pool)
         .await?;
 
@@ -67,19 +67,33 @@ impl TontineRepository {
         return Err(AppError::ValidationError("L'utilisateur créateur n'existe pas".to_string()));
     }
 
+    let mut validator = Validator::default();
+    validator
+        .check(!tontine_data.name.trim().is_empty(), "name", "Le nom de la tontine est obligatoire")
+        .positive("amount_per_member", tontine_data.amount_per_member)
+        .check(tontine_data.max_members >= 2, "max_members", "Une tontine doit compter au moins 2 membres");
+    validator.finish()?;
+
     let frequency_str: String = tontine_data.frequency.clone().into();
+    let payout_strategy: String = tontine_data.payout_strategy.unwrap_or(PayoutStrategy::Fixed).into();
+    let bid_discount_policy: String = tontine_data.bid_discount_policy.unwrap_or(BidDiscountPolicy::KeepAsInterest).into();
+    let payout_shortfall_policy: String = tontine_data.payout_shortfall_policy.unwrap_or(ShortfallPolicy::Reject).into();
 
     let tontine = sqlx::query_as::<_, Tontine>(
-        "INSERT INTO tontines (name, description, amount_per_member, frequency, max_members, created_by) 
-         VALUES ($1, $2, $3, $4, $5, $6) 
-         RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at"
+        "INSERT INTO tontines (name, description, amount_per_member, frequency, max_members, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) 
+         RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at"
     )
     .bind(&tontine_data.name)
     .bind(&tontine_data.description)
-    .bind(&tontine_data.amount_per_member)
+    .bind(tontine_data.amount_per_member)
     .bind(&frequency_str)
-    .bind(&tontine_data.max_members)
-    .bind(&user_uuid) // ← ICI : Utiliser l'UUID converti
+    .bind(tontine_data.max_members)
+    .bind(&payout_strategy)
+    .bind(&bid_discount_policy)
+    .bind(tontine_data.allow_partial_payments.unwrap_or(false))
+    .bind(&payout_shortfall_policy)
+    .bind(user_id)
     .fetch_one(pool)
     .await?;
 
@@ -87,6 +101,13 @@ impl TontineRepository {
 }
 
     pub async fn update(pool: &PgPool, tontine_id: Uuid, tontine_data: &UpdateTontine) -> Result<Tontine, AppError> {
+        let mut validator = Validator::default();
+        validator
+            .check(tontine_data.name.as_ref().is_none_or(|name| !name.trim().is_empty()), "name", "Le nom de la tontine est obligatoire")
+            .positive_opt("amount_per_member", tontine_data.amount_per_member)
+            .check(tontine_data.max_members.is_none_or(|max| max >= 2), "max_members", "Une tontine doit compter au moins 2 membres");
+        validator.finish()?;
+
         // Vérifier si la tontine existe
         let existing = Self::find_by_id(pool, tontine_id).await?;
 
@@ -100,23 +121,49 @@ impl TontineRepository {
             existing.frequency
         };
         let max_members = tontine_data.max_members.unwrap_or(existing.max_members);
-        let status = if let Some(stat) = &tontine_data.status {
-            stat.clone().into()
-        } else {
-            existing.status
+        let payout_strategy = match tontine_data.payout_strategy {
+            Some(strategy) => strategy.into(),
+            None => existing.payout_strategy,
+        };
+        let bid_discount_policy = match tontine_data.bid_discount_policy {
+            Some(policy) => policy.into(),
+            None => existing.bid_discount_policy,
+        };
+        let allow_partial_payments = tontine_data.allow_partial_payments.unwrap_or(existing.allow_partial_payments);
+        let payout_shortfall_policy = match tontine_data.payout_shortfall_policy {
+            Some(policy) => policy.into(),
+            None => existing.payout_shortfall_policy,
         };
 
+        // Le statut ne change que par les transitions du cycle de vie
+        let status = TontineStatus::try_from(existing.status).map_err(AppError::InternalServerError)?;
+        let changes_terms = tontine_data.amount_per_member.is_some()
+            || tontine_data.frequency.is_some()
+            || tontine_data.max_members.is_some()
+            || tontine_data.payout_strategy.is_some()
+            || tontine_data.bid_discount_policy.is_some()
+            || tontine_data.allow_partial_payments.is_some()
+            || tontine_data.payout_shortfall_policy.is_some();
+        if changes_terms && !status.allows_terms_change() {
+            return Err(AppError::ValidationError(
+                "Le montant, la fréquence, le nombre de membres et les règles de versement et de paiement ne sont plus modifiables après le démarrage".to_string(),
+            ));
+        }
+
         let tontine = sqlx::query_as::<_, Tontine>(
-            "UPDATE tontines SET name = $1, description = $2, amount_per_member = $3, frequency = $4, max_members = $5, status = $6, updated_at = $7 
-            WHERE id = $8 
-            RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at"
+            "UPDATE tontines SET name = $1, description = $2, amount_per_member = $3, frequency = $4, max_members = $5, payout_strategy = $6, bid_discount_policy = $7, allow_partial_payments = $8, payout_shortfall_policy = $9, updated_at = $10 
+            WHERE id = $11 
+            RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at"
         )
         .bind(name)
         .bind(description)
         .bind(amount_per_member)
         .bind(frequency)
         .bind(max_members)
-        .bind(status)
+        .bind(payout_strategy)
+        .bind(bid_discount_policy)
+        .bind(allow_partial_payments)
+        .bind(payout_shortfall_policy)
         .bind(Utc::now())
         .bind(tontine_id)
         .fetch_one(pool)
@@ -127,45 +174,64 @@ impl TontineRepository {
    
 
 
+    /// Suppression définitive, réservée aux tontines pas encore démarrées :
+    /// une tontine qui a fait circuler de l'argent s'annule (`cancel`) pour conserver son historique
     pub async fn delete(pool: &PgPool, tontine_id: Uuid) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM tontines WHERE id = $1")
+        let result = sqlx::query("DELETE FROM tontines WHERE id = $1 AND status IN ('draft', 'recruiting')")
             .bind(tontine_id)
             .execute(pool)
             .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id)));
+            let tontine = Self::find_by_id(pool, tontine_id).await?;
+            return Err(AppError::ValidationError(format!(
+                "Une tontine au statut {} ne peut pas être supprimée : utilisez l'annulation",
+                tontine.status
+            )));
         }
 
         Ok(())
     }
 
-    pub async fn get_active_tontines(pool: &PgPool) -> Result<Vec<Tontine>, AppError> {
+    pub async fn get_active_tontines(pool: &PgPool, user_id: Uuid) -> Result<Vec<Tontine>, AppError> {
         let tontines = sqlx::query_as::<_, Tontine>(
-            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at 
+            "SELECT id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at 
              FROM tontines 
-             WHERE status = 'active' 
+             WHERE status IN ('recruiting', 'running') 
+               AND (created_by = $1
+                    OR id IN (SELECT tontine_id FROM tontine_members WHERE user_id = $1 AND is_active = true))
              ORDER BY created_at DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
         Ok(tontines)
     }
 
+    /// Réservé aux tontines sans calendrier : ensuite, `current_round` suit l'ouverture et le versement des tours
     pub async fn increment_round(pool: &PgPool, tontine_id: Uuid) -> Result<Tontine, AppError> {
         let tontine = sqlx::query_as::<_, Tontine>(
             "UPDATE tontines 
              SET current_round = current_round + 1, updated_at = $1 
-             WHERE id = $2 
-             RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, created_by, created_at, updated_at"
+             WHERE id = $2 AND status IN ('draft', 'recruiting')
+               AND NOT EXISTS (SELECT 1 FROM tontine_rounds WHERE tontine_id = $2)
+             RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at"
         )
         .bind(Utc::now())
         .bind(tontine_id)
-        .fetch_one(pool)
+        .fetch_optional(pool)
         .await?;
 
-        Ok(tontine)
+        match tontine {
+            Some(tontine) => Ok(tontine),
+            None => {
+                Self::find_by_id(pool, tontine_id).await?;
+                Err(AppError::ValidationError(
+                    "Le tour courant d'une tontine démarrée avance avec ses tours et ne peut pas être modifié".to_string()
+                ))
+            }
+        }
     }
 
     pub async fn get_tontine_with_creator(pool: &PgPool, tontine_id: Uuid) -> Result<TontineWithCreator, AppError> {
@@ -190,6 +256,10 @@ impl TontineRepository {
                     max_members: row.get("max_members"),
                     current_round: row.get("current_round"),
                     status: row.get("status"),
+                    payout_strategy: row.get("payout_strategy"),
+                    bid_discount_policy: row.get("bid_discount_policy"),
+                    allow_partial_payments: row.get("allow_partial_payments"),
+                    payout_shortfall_policy: row.get("payout_shortfall_policy"),
                     created_by: row.get("created_by"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
@@ -201,6 +271,225 @@ impl TontineRepository {
             None => Err(AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id))),
         }
     }
+
+    /// Applique une transition du cycle de vie après vérification de ses conditions,
+    /// et l'enregistre dans l'historique. S'exécute dans la transaction de l'appelant.
+    pub async fn transition(
+        conn: &mut PgConnection,
+        tontine_id: Uuid,
+        to: TontineStatus,
+        changed_by: Option<Uuid>,
+        reason: Option<&str>,
+    ) -> Result<Tontine, AppError> {
+        let current: Option<String> = sqlx::query_scalar("SELECT status FROM tontines WHERE id = $1 FOR UPDATE")
+            .bind(tontine_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        let current = match current {
+            Some(status) => TontineStatus::try_from(status).map_err(AppError::InternalServerError)?,
+            None => return Err(AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id))),
+        };
+
+        if !current.can_transition_to(to) {
+            return Err(AppError::ValidationError(format!(
+                "Transition de statut impossible : {} -> {}",
+                String::from(current),
+                String::from(to)
+            )));
+        }
+
+        match to {
+            TontineStatus::Running => {
+                let (active_members, max_members): (i64, i32) = sqlx::query_as(
+                    "SELECT (SELECT COUNT(*) FROM tontine_members WHERE tontine_id = $1 AND is_active = true), max_members
+                     FROM tontines WHERE id = $1"
+                )
+                .bind(tontine_id)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                if active_members != max_members as i64 {
+                    return Err(AppError::ValidationError(format!(
+                        "La tontine doit être complète pour démarrer ({} membres actifs sur {})",
+                        active_members, max_members
+                    )));
+                }
+            }
+            TontineStatus::Completed => {
+                let (total_rounds, pending_rounds): (i64, i64) = sqlx::query_as(
+                    "SELECT COUNT(*), COUNT(*) FILTER (WHERE status IN ('pending', 'disbursing')) FROM tontine_rounds WHERE tontine_id = $1"
+                )
+                .bind(tontine_id)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                if total_rounds == 0 || pending_rounds > 0 {
+                    return Err(AppError::ValidationError(format!(
+                        "La tontine ne peut pas être terminée : {} tour(s) encore en attente",
+                        pending_rounds
+                    )));
+                }
+            }
+            _ => {}
+        }
+
+        let tontine = sqlx::query_as::<_, Tontine>(
+            "UPDATE tontines SET status = $1, updated_at = $2 
+             WHERE id = $3 
+             RETURNING id, name, description, amount_per_member, frequency, max_members, current_round, status, payout_strategy, bid_discount_policy, allow_partial_payments, payout_shortfall_policy, created_by, created_at, updated_at"
+        )
+        .bind(String::from(to))
+        .bind(Utc::now())
+        .bind(tontine_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO tontine_status_history (tontine_id, from_status, to_status, changed_by, reason)
+             VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(tontine_id)
+        .bind(String::from(current))
+        .bind(String::from(to))
+        .bind(changed_by)
+        .bind(reason)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(tontine)
+    }
+
+    /// Ouvre les inscriptions (draft -> recruiting)
+    pub async fn open_recruitment(pool: &PgPool, tontine_id: Uuid, changed_by: Uuid) -> Result<Tontine, AppError> {
+        let mut tx = pool.begin().await?;
+        let tontine = Self::transition(&mut tx, tontine_id, TontineStatus::Recruiting, Some(changed_by), None).await?;
+        tx.commit().await?;
+
+        Ok(tontine)
+    }
+
+    /// Termine la tontine une fois tous les tours clôturés ou annulés (running -> completed)
+    pub async fn complete(pool: &PgPool, tontine_id: Uuid, changed_by: Uuid) -> Result<Tontine, AppError> {
+        let mut tx = pool.begin().await?;
+        let tontine = Self::transition(&mut tx, tontine_id, TontineStatus::Completed, Some(changed_by), None).await?;
+        tx.commit().await?;
+
+        Ok(tontine)
+    }
+
+    /// Annule la tontine : les tours en attente sont annulés et chaque membre ayant cotisé
+    /// plus qu'il n'a reçu se voit attribuer un remboursement (transaction `refund` en attente)
+    pub async fn cancel(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        changed_by: Uuid,
+        reason: Option<&str>,
+    ) -> Result<TontineCancellation, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let tontine = Self::transition(&mut tx, tontine_id, TontineStatus::Cancelled, Some(changed_by), reason).await?;
+
+        // Le pot d'un tour en cours de versement est peut-être déjà chez le bénéficiaire
+        let disbursing: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tontine_rounds WHERE tontine_id = $1 AND status = 'disbursing'"
+        )
+        .bind(tontine_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if disbursing > 0 {
+            return Err(AppError::ValidationError(
+                "Un versement mobile money est en attente de confirmation, la tontine ne peut pas être annulée".to_string()
+            ));
+        }
+
+        let cancelled_rounds = sqlx::query(
+            "UPDATE tontine_rounds SET status = 'cancelled' WHERE tontine_id = $1 AND status = 'pending'"
+        )
+        .bind(tontine_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let balances = sqlx::query(
+            "SELECT tm.id as member_id, tm.user_id,
+                COALESCE((SELECT SUM(c.amount)
+                          FROM contributions c
+                          JOIN tontine_rounds tr ON c.tontine_round_id = tr.id
+                          WHERE c.member_id = tm.id AND tr.tontine_id = $1 AND c.payment_status = 'paid'), 0) as total_contributed,
+                COALESCE((SELECT SUM(t.amount)
+                          FROM transactions t
+                          WHERE t.tontine_id = $1 AND t.to_user_id = tm.user_id
+                            AND t.transaction_type = 'payout' AND t.status = 'completed'), 0) as total_received
+             FROM tontine_members tm
+             WHERE tm.tontine_id = $1
+             ORDER BY tm.position_order NULLS LAST, tm.join_date"
+        )
+        .bind(tontine_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut refunds = Vec::new();
+        let mut total_refunds = Decimal::ZERO;
+
+        for row in balances {
+            let user_id: Uuid = row.get("user_id");
+            let total_contributed: Decimal = row.get("total_contributed");
+            let total_received: Decimal = row.get("total_received");
+            let refund_amount = (total_contributed - total_received).max(Decimal::ZERO);
+
+            let transaction_id = if refund_amount > Decimal::ZERO {
+                let id: Uuid = sqlx::query_scalar(
+                    "INSERT INTO transactions (tontine_id, to_user_id, amount, transaction_type, status, description)
+                     VALUES ($1, $2, $3, 'refund', 'pending', $4)
+                     RETURNING id"
+                )
+                .bind(tontine_id)
+                .bind(user_id)
+                .bind(refund_amount)
+                .bind("Remboursement suite à l'annulation de la tontine")
+                .fetch_one(&mut *tx)
+                .await?;
+
+                total_refunds += refund_amount;
+                Some(id)
+            } else {
+                None
+            };
+
+            refunds.push(MemberRefund {
+                member_id: row.get("member_id"),
+                user_id,
+                total_contributed,
+                total_received,
+                refund_amount,
+                transaction_id,
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(TontineCancellation {
+            tontine,
+            cancelled_rounds,
+            refunds,
+            total_refunds,
+        })
+    }
+
+    pub async fn find_status_history(pool: &PgPool, tontine_id: Uuid) -> Result<Vec<TontineStatusChange>, AppError> {
+        let history = sqlx::query_as::<_, TontineStatusChange>(
+            "SELECT id, tontine_id, from_status, to_status, changed_by, reason, created_at
+             FROM tontine_status_history
+             WHERE tontine_id = $1
+             ORDER BY created_at"
+        )
+        .bind(tontine_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(history)
+    }
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -213,6 +502,10 @@ pub struct TontineWithCreator {
     pub max_members: i32,
     pub current_round: i32,
     pub status: String,
+    pub payout_strategy: String,
+    pub bid_discount_policy: String,
+    pub allow_partial_payments: bool,
+    pub payout_shortfall_policy: String,
     pub created_by: Uuid,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
@@ -1,20 +1,35 @@
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
 use uuid::Uuid;
-use chrono::{ Utc};
-
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+
+use crate::model::tontine::{PayoutStrategy, TontineFrequency, TontineStatus};
+use crate::model::beneficiary_draws::BeneficiaryDraw;
+use crate::ordering::{self, InitialOrder};
+use crate::model::scheduled_jobs::JobType;
+use crate::repositories::tontine_repository::TontineRepository;
+use crate::repositories::scheduled_job_repository::ScheduledJobRepository;
+use crate::repositories::beneficiary_draw_repository::BeneficiaryDrawRepository;
 use crate::model::tontine_rounds::{TontineRound, CreateTontineRound, UpdateTontineRound, TontineRoundWithBeneficiary, RoundStatus};
 use crate::errors::AppError;
+use crate::validation::Validator;
 
 pub struct TontineRoundRepository;
 
 impl TontineRoundRepository {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<TontineRoundWithBeneficiary>, AppError> {
+    pub async fn find_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<TontineRoundWithBeneficiary>, AppError> {
         let rounds = sqlx::query(
             "SELECT tr.*, u.full_name as beneficiary_name, u.email as beneficiary_email
              FROM tontine_rounds tr
              LEFT JOIN users u ON tr.beneficiary_user_id = u.id
+             WHERE tr.tontine_id IN (
+                SELECT id FROM tontines WHERE created_by = $1
+                UNION
+                SELECT tontine_id FROM tontine_members WHERE user_id = $1 AND is_active = true
+             )
              ORDER BY tr.created_at DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -129,7 +144,7 @@ impl TontineRoundRepository {
     pub async fn create(pool: &PgPool, round_data: &CreateTontineRound) -> Result<TontineRound, AppError> {
         // Vérifier si la tontine existe
         let tontine_exists = sqlx::query("SELECT id, max_members FROM tontines WHERE id = $1")
-            .bind(&round_data.tontine_id)
+            .bind(round_data.tontine_id)
             .fetch_optional(pool)
             .await?;
 
@@ -137,9 +152,23 @@ impl TontineRoundRepository {
             return Err(AppError::ValidationError("La tontine spécifiée n'existe pas".to_string()));
         }
 
+        Self::ensure_schedule_not_started(pool, round_data.tontine_id).await?;
+
+        let expected_amount = Self::expected_round_amount(pool, round_data.tontine_id).await?;
+        let mut validator = Validator::default();
+        validator
+            .check(round_data.round_number >= 1, "round_number", "Le numéro du round doit être supérieur ou égal à 1")
+            .positive("amount", round_data.amount);
+        if round_data.amount > Decimal::ZERO {
+            validator.check(round_data.amount == expected_amount, "amount", format!(
+                "Le montant du round doit être égal à la cotisation multipliée par le nombre de membres actifs ({})", expected_amount
+            ));
+        }
+        validator.finish()?;
+
         // Vérifier si le bénéficiaire existe
         let beneficiary_exists = sqlx::query("SELECT id FROM users WHERE id = $1")
-            .bind(&round_data.beneficiary_user_id)
+            .bind(round_data.beneficiary_user_id)
             .fetch_optional(pool)
             .await?;
 
@@ -151,8 +180,8 @@ impl TontineRoundRepository {
         let is_member = sqlx::query(
             "SELECT id FROM tontine_members WHERE tontine_id = $1 AND user_id = $2 AND is_active = true"
         )
-        .bind(&round_data.tontine_id)
-        .bind(&round_data.beneficiary_user_id)
+        .bind(round_data.tontine_id)
+        .bind(round_data.beneficiary_user_id)
         .fetch_optional(pool)
         .await?;
 
@@ -164,8 +193,8 @@ impl TontineRoundRepository {
         let existing_round = sqlx::query(
             "SELECT id FROM tontine_rounds WHERE tontine_id = $1 AND round_number = $2"
         )
-        .bind(&round_data.tontine_id)
-        .bind(&round_data.round_number)
+        .bind(round_data.tontine_id)
+        .bind(round_data.round_number)
         .fetch_optional(pool)
         .await?;
 
@@ -178,11 +207,11 @@ impl TontineRoundRepository {
              VALUES ($1, $2, $3, $4, $5) 
              RETURNING id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at"
         )
-        .bind(&round_data.tontine_id)
-        .bind(&round_data.round_number)
-        .bind(&round_data.beneficiary_user_id)
-        .bind(&round_data.amount)
-        .bind(&round_data.round_date)
+        .bind(round_data.tontine_id)
+        .bind(round_data.round_number)
+        .bind(round_data.beneficiary_user_id)
+        .bind(round_data.amount)
+        .bind(round_data.round_date)
         .fetch_one(pool)
         .await?;
 
@@ -201,8 +230,44 @@ impl TontineRoundRepository {
     .fetch_optional(pool)
     .await?;
 
-    if existing_round.is_none() {
-        return Err(AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)));
+    let existing = match &existing_round {
+        Some(round) => round,
+        None => return Err(AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id))),
+    };
+
+    if existing.status == String::from(RoundStatus::Disbursing) {
+        return Err(AppError::ValidationError("Ce tour est en cours de versement et ne peut pas être modifié".to_string()));
+    }
+    if existing.status != String::from(RoundStatus::Pending) {
+        return Err(AppError::ValidationError("Seul un tour en attente peut être modifié".to_string()));
+    }
+
+    // Le bénéficiaire désigné par une enchère ne peut plus être remplacé
+    if round_data.beneficiary_user_id.is_some() {
+        let has_winner: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM bids WHERE tontine_round_id = $1 AND status = 'won')"
+        )
+        .bind(round_id)
+        .fetch_one(pool)
+        .await?;
+
+        if has_winner {
+            return Err(AppError::ValidationError(
+                "Le bénéficiaire de ce tour a été désigné par enchère et ne peut pas être modifié".to_string()
+            ));
+        }
+    }
+
+    if let Some(amount) = round_data.amount {
+        let expected_amount = Self::expected_round_amount(pool, existing.tontine_id).await?;
+        let mut validator = Validator::default();
+        validator.positive("amount", amount);
+        if amount > Decimal::ZERO {
+            validator.check(amount == expected_amount, "amount", format!(
+                "Le montant du round doit être égal à la cotisation multipliée par le nombre de membres actifs ({})", expected_amount
+            ));
+        }
+        validator.finish()?;
     }
 
     // Construire la requête dynamiquement avec des types concrets
@@ -222,10 +287,6 @@ impl TontineRoundRepository {
         bind_values.push(format!("round_date = ${}", counter));
         counter += 1;
     }
-    if round_data.status.is_some() {
-        bind_values.push(format!("status = ${}", counter));
-        counter += 1;
-    }
 
     if bind_values.is_empty() {
         return Ok(existing_round.unwrap());
@@ -246,10 +307,6 @@ impl TontineRoundRepository {
     if let Some(round_date) = &round_data.round_date {
         query_builder = query_builder.bind(round_date);
     }
-    if let Some(status) = &round_data.status {
-        let status_str: String = status.clone().into();
-        query_builder = query_builder.bind(status_str);
-    }
     
     query_builder = query_builder.bind(round_id);
 
@@ -260,48 +317,92 @@ impl TontineRoundRepository {
 
 
     pub async fn delete(pool: &PgPool, round_id: Uuid) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM tontine_rounds WHERE id = $1")
+        let round = sqlx::query_as::<_, TontineRound>(
+            "SELECT id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at 
+             FROM tontine_rounds WHERE id = $1"
+        )
+        .bind(round_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        Self::ensure_schedule_not_started(pool, round.tontine_id).await?;
+
+        sqlx::query("DELETE FROM tontine_rounds WHERE id = $1")
             .bind(round_id)
             .execute(pool)
             .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)));
+        Ok(())
+    }
+
+    /// Une fois la tontine démarrée, le calendrier et `current_round` sont gérés par le démarrage,
+    /// le planificateur et les versements : les tours ne peuvent plus être ajoutés ni supprimés à la main
+    async fn ensure_schedule_not_started(pool: &PgPool, tontine_id: Uuid) -> Result<(), AppError> {
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM tontines WHERE id = $1")
+            .bind(tontine_id)
+            .fetch_optional(pool)
+            .await?;
+        let status = status.ok_or_else(|| AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id)))?;
+
+        if status != String::from(TontineStatus::Draft) && status != String::from(TontineStatus::Recruiting) {
+            return Err(AppError::ValidationError(
+                "Le calendrier des tours est fixé au démarrage de la tontine et ne peut plus être modifié".to_string()
+            ));
         }
 
         Ok(())
     }
 
-    pub async fn complete_round(pool: &PgPool, round_id: Uuid) -> Result<TontineRound, AppError> {
-        let round = sqlx::query_as::<_, TontineRound>(
-            "UPDATE tontine_rounds 
-             SET status = 'completed', round_date = $1 
-             WHERE id = $2 
-             RETURNING id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at"
+    /// Pot attendu d'un round : cotisation par membre multipliée par le nombre de membres actifs
+    pub async fn expected_round_amount(pool: &PgPool, tontine_id: Uuid) -> Result<Decimal, AppError> {
+        let expected: Option<Decimal> = sqlx::query_scalar(
+            "SELECT t.amount_per_member * (SELECT COUNT(*) FROM tontine_members tm WHERE tm.tontine_id = t.id AND tm.is_active = true)
+             FROM tontines t WHERE t.id = $1"
         )
-        .bind(Utc::now())
-        .bind(round_id)
-        .fetch_one(pool)
+        .bind(tontine_id)
+        .fetch_optional(pool)
         .await?;
 
-        Ok(round)
+        expected.ok_or_else(|| AppError::NotFound(format!("Tontine avec l'ID {} non trouvée", tontine_id)))
     }
 
     pub async fn cancel_round(pool: &PgPool, round_id: Uuid) -> Result<TontineRound, AppError> {
         let round = sqlx::query_as::<_, TontineRound>(
             "UPDATE tontine_rounds 
              SET status = 'cancelled'
-             WHERE id = $1 
+             WHERE id = $1 AND status = $2
              RETURNING id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at"
         )
         .bind(round_id)
-        .fetch_one(pool)
+        .bind(String::from(RoundStatus::Pending))
+        .fetch_optional(pool)
         .await?;
 
-        Ok(round)
+        if let Some(round) = round {
+            return Ok(round);
+        }
+
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM tontine_rounds WHERE id = $1")
+            .bind(round_id)
+            .fetch_optional(pool)
+            .await?;
+        let status = status.ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        let status = RoundStatus::try_from(status).map_err(AppError::InternalServerError)?;
+        if status.is_cancellable() {
+            return Err(AppError::Conflict("Ce tour a été modifié entre-temps, réessayez".to_string()));
+        }
+
+        let message = match status {
+            RoundStatus::Disbursing => "Le versement mobile money de ce tour est en attente de confirmation",
+            RoundStatus::Completed => "Ce tour a déjà été versé à son bénéficiaire et ne peut plus être annulé",
+            _ => "Ce tour est déjà annulé",
+        };
+        Err(AppError::ValidationError(message.to_string()))
     }
 
-    pub async fn get_rounds_by_status(pool: &PgPool, status: RoundStatus) -> Result<Vec<TontineRoundWithBeneficiary>, AppError> {
+    pub async fn get_rounds_by_status(pool: &PgPool, status: RoundStatus, user_id: Uuid) -> Result<Vec<TontineRoundWithBeneficiary>, AppError> {
         let status_str: String = status.into();
         
         let rounds = sqlx::query(
@@ -309,9 +410,15 @@ impl TontineRoundRepository {
              FROM tontine_rounds tr
              LEFT JOIN users u ON tr.beneficiary_user_id = u.id
              WHERE tr.status = $1
+               AND tr.tontine_id IN (
+                SELECT id FROM tontines WHERE created_by = $2
+                UNION
+                SELECT tontine_id FROM tontine_members WHERE user_id = $2 AND is_active = true
+             )
              ORDER BY tr.created_at DESC"
         )
         .bind(&status_str)
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -341,4 +448,246 @@ impl TontineRoundRepository {
 
         Ok(max_round.unwrap_or(0) + 1)
     }
+
+    /// Génère tout le calendrier du cycle : un tour par membre actif, avec une échéance
+    /// calculée selon la fréquence de la tontine et des bénéficiaires désignés selon sa
+    /// stratégie (ordre fixe, tirage complet enregistré, ou désignation tour par tour)
+    pub async fn generate_schedule(
+        pool: &PgPool,
+        tontine_id: Uuid,
+        start_date: DateTime<Utc>,
+        started_by: Uuid,
+    ) -> Result<Vec<TontineRound>, AppError> {
+        let mut tx = pool.begin().await?;
+
+        // recruiting -> running : vérifie aussi que tous les membres ont rejoint la tontine
+        let tontine = TontineRepository::transition(&mut tx, tontine_id, TontineStatus::Running, Some(started_by), None).await?;
+        let frequency = TontineFrequency::try_from(tontine.frequency).map_err(AppError::InternalServerError)?;
+        let strategy = PayoutStrategy::try_from(tontine.payout_strategy).map_err(AppError::InternalServerError)?;
+
+        let existing_rounds: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tontine_rounds WHERE tontine_id = $1")
+            .bind(tontine_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if existing_rounds > 0 {
+            return Err(AppError::ValidationError("Le calendrier des tours de cette tontine existe déjà".to_string()));
+        }
+
+        let candidates = Self::active_member_users(&mut tx, tontine_id).await?;
+
+        let seed = ordering::generate_seed();
+        let beneficiaries: Vec<Option<Uuid>> = match ordering::ordering_for(strategy).initial_order(&candidates, &seed) {
+            InitialOrder::Fixed(order) => order.into_iter().map(Some).collect(),
+            InitialOrder::Drawn(order) => {
+                BeneficiaryDrawRepository::create(&mut tx, tontine_id, None, &seed, &candidates, &order, Some(started_by)).await?;
+
+                // Les positions reflètent l'ordre tiré
+                for (index, user_id) in order.iter().enumerate() {
+                    sqlx::query("UPDATE tontine_members SET position_order = $1 WHERE tontine_id = $2 AND user_id = $3")
+                        .bind(index as i32 + 1)
+                        .bind(tontine_id)
+                        .bind(user_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+
+                order.into_iter().map(Some).collect()
+            }
+            InitialOrder::Deferred => vec![None; candidates.len()],
+        };
+
+        // Chaque tour redistribue la cotisation de tous les membres actifs
+        let amount = tontine.amount_per_member * Decimal::from(beneficiaries.len() as i64);
+
+        let mut rounds = Vec::with_capacity(beneficiaries.len());
+        for (index, beneficiary_user_id) in beneficiaries.into_iter().enumerate() {
+            let round = sqlx::query_as::<_, TontineRound>(
+                "INSERT INTO tontine_rounds (tontine_id, round_number, beneficiary_user_id, amount, round_date) 
+                 VALUES ($1, $2, $3, $4, $5) 
+                 RETURNING id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at"
+            )
+            .bind(tontine_id)
+            .bind(index as i32 + 1)
+            .bind(beneficiary_user_id)
+            .bind(amount)
+            .bind(frequency.due_date(start_date, index as u32))
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if let Some(round_date) = round.round_date {
+                ScheduledJobRepository::enqueue(&mut tx, JobType::OpenRound, tontine_id, Some(round.id), round_date).await?;
+            }
+
+            rounds.push(round);
+        }
+
+        sqlx::query("UPDATE tontines SET current_round = 1, updated_at = $1 WHERE id = $2")
+            .bind(Utc::now())
+            .bind(tontine_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(rounds)
+    }
+
+    /// Ouvre le tour arrivé à échéance : il devient le tour courant de la tontine et
+    /// le passage en retard des cotisations impayées est planifié après `grace_period`.
+    /// Renvoie `None` si le tour n'est plus en attente ou si la tontine n'est plus en cours.
+    pub async fn open_round(pool: &PgPool, round_id: Uuid, grace_period: Duration) -> Result<Option<TontineRound>, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT tr.round_number, tr.status, tr.round_date, tr.beneficiary_user_id,
+                    t.id as tontine_id, t.status as tontine_status, t.payout_strategy
+             FROM tontine_rounds tr
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tr.id = $1
+             FOR UPDATE OF tr, t"
+        )
+        .bind(round_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        let round_status: String = row.get("status");
+        let tontine_status: String = row.get("tontine_status");
+        if round_status != String::from(RoundStatus::Pending) || tontine_status != String::from(TontineStatus::Running) {
+            return Ok(None);
+        }
+
+        let tontine_id: Uuid = row.get("tontine_id");
+        let round_number: i32 = row.get("round_number");
+        let round_date: DateTime<Utc> = row.get::<Option<DateTime<Utc>>, _>("round_date").unwrap_or_else(Utc::now);
+
+        // Le tour courant ne recule jamais, même si les tâches s'exécutent dans le désordre
+        sqlx::query(
+            "UPDATE tontines SET current_round = GREATEST(current_round, $1), updated_at = $2 WHERE id = $3"
+        )
+        .bind(round_number)
+        .bind(Utc::now())
+        .bind(tontine_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if row.get::<Option<Uuid>, _>("beneficiary_user_id").is_none() {
+            let strategy = PayoutStrategy::try_from(row.get::<String, _>("payout_strategy")).map_err(AppError::InternalServerError)?;
+            Self::draw_beneficiary(&mut tx, tontine_id, round_id, strategy, None).await?;
+        }
+
+        ScheduledJobRepository::enqueue(
+            &mut tx,
+            JobType::MarkLateContributions,
+            tontine_id,
+            Some(round_id),
+            round_date + grace_period,
+        )
+        .await?;
+
+        let round = sqlx::query_as::<_, TontineRound>(
+            "SELECT id, tontine_id, round_number, beneficiary_user_id, amount, round_date, status, created_at 
+             FROM tontine_rounds WHERE id = $1"
+        )
+        .bind(round_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(round))
+    }
+
+    /// Tirage manuel du bénéficiaire d'un tour en attente (stratégie `per_round_draw`),
+    /// avant son ouverture automatique
+    pub async fn draw_round_beneficiary(pool: &PgPool, round_id: Uuid, drawn_by: Uuid) -> Result<BeneficiaryDraw, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT tr.status, tr.beneficiary_user_id, t.id as tontine_id, t.status as tontine_status, t.payout_strategy
+             FROM tontine_rounds tr
+             JOIN tontines t ON tr.tontine_id = t.id
+             WHERE tr.id = $1
+             FOR UPDATE OF tr, t"
+        )
+        .bind(round_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Round avec l'ID {} non trouvé", round_id)))?;
+
+        let round_status: String = row.get("status");
+        let tontine_status: String = row.get("tontine_status");
+        if round_status != String::from(RoundStatus::Pending) || tontine_status != String::from(TontineStatus::Running) {
+            return Err(AppError::ValidationError("Seul un tour en attente d'une tontine en cours peut faire l'objet d'un tirage".to_string()));
+        }
+        if row.get::<Option<Uuid>, _>("beneficiary_user_id").is_some() {
+            return Err(AppError::ValidationError("Le bénéficiaire de ce tour est déjà désigné".to_string()));
+        }
+
+        let strategy = PayoutStrategy::try_from(row.get::<String, _>("payout_strategy")).map_err(AppError::InternalServerError)?;
+        let draw = Self::draw_beneficiary(&mut tx, row.get("tontine_id"), round_id, strategy, Some(drawn_by))
+            .await?
+            .ok_or_else(|| AppError::ValidationError(format!(
+                "La stratégie {} ne désigne pas les bénéficiaires par tirage",
+                String::from(strategy)
+            )))?;
+
+        tx.commit().await?;
+
+        Ok(draw)
+    }
+
+    /// Tire le bénéficiaire du tour parmi les membres actifs qui n'ont pas encore de tour attribué,
+    /// et enregistre le tirage. Sans effet si la stratégie ne procède pas par tirage.
+    async fn draw_beneficiary(
+        conn: &mut PgConnection,
+        tontine_id: Uuid,
+        round_id: Uuid,
+        strategy: PayoutStrategy,
+        drawn_by: Option<Uuid>,
+    ) -> Result<Option<BeneficiaryDraw>, AppError> {
+        let assigned: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT beneficiary_user_id FROM tontine_rounds
+             WHERE tontine_id = $1 AND beneficiary_user_id IS NOT NULL AND status <> 'cancelled'"
+        )
+        .bind(tontine_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let candidates: Vec<Uuid> = Self::active_member_users(conn, tontine_id).await?
+            .into_iter()
+            .filter(|user_id| !assigned.contains(user_id))
+            .collect();
+
+        let seed = ordering::generate_seed();
+        let winner = match ordering::ordering_for(strategy).draw_round(&candidates, &seed) {
+            Some(winner) => winner,
+            None => return Ok(None),
+        };
+
+        sqlx::query("UPDATE tontine_rounds SET beneficiary_user_id = $1 WHERE id = $2")
+            .bind(winner)
+            .bind(round_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let draw = BeneficiaryDrawRepository::create(conn, tontine_id, Some(round_id), &seed, &candidates, &[winner], drawn_by).await?;
+
+        Ok(Some(draw))
+    }
+
+    /// Membres actifs (identifiants utilisateur) dans l'ordre de leurs positions
+    async fn active_member_users(conn: &mut PgConnection, tontine_id: Uuid) -> Result<Vec<Uuid>, AppError> {
+        let users = sqlx::query_scalar(
+            "SELECT user_id FROM tontine_members
+             WHERE tontine_id = $1 AND is_active = true
+             ORDER BY position_order NULLS LAST, join_date, id"
+        )
+        .bind(tontine_id)
+        .fetch_all(conn)
+        .await?;
+
+        Ok(users)
+    }
 }
\ No newline at end of file
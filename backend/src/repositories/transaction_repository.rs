@@ -1,14 +1,17 @@
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
 use uuid::Uuid;
 
 
 use crate::model::transactions::{Transaction, CreateTransaction, TransactionWithUsers, TransactionType, TransactionStatus};
+use crate::model::ledger::AccountKind;
+use crate::repositories::ledger_repository::LedgerRepository;
 use crate::errors::AppError;
+use crate::validation::Validator;
 
 pub struct TransactionRepository;
 
 impl TransactionRepository {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<TransactionWithUsers>, AppError> {
+    pub async fn find_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<TransactionWithUsers>, AppError> {
         let transactions = sqlx::query(
             "SELECT t.*, 
                     u_from.full_name as from_user_name,
@@ -18,8 +21,16 @@ impl TransactionRepository {
              LEFT JOIN users u_from ON t.from_user_id = u_from.id
              LEFT JOIN users u_to ON t.to_user_id = u_to.id
              JOIN tontines ton ON t.tontine_id = ton.id
+             WHERE t.from_user_id = $1 OR t.to_user_id = $1
+                OR ton.id IN (
+                    SELECT id FROM tontines WHERE created_by = $1
+                    UNION
+                    SELECT tontine_id FROM tontine_members
+                    WHERE user_id = $1 AND is_active = true AND role IN ('owner', 'treasurer')
+                )
              ORDER BY t.created_at DESC"
         )
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -150,9 +161,13 @@ impl TransactionRepository {
     }
 
     pub async fn create(pool: &PgPool, transaction_data: &CreateTransaction) -> Result<Transaction, AppError> {
+        let mut validator = Validator::default();
+        validator.positive("amount", transaction_data.amount);
+        validator.finish()?;
+
         // Vérifier si la tontine existe
         let tontine_exists = sqlx::query("SELECT id FROM tontines WHERE id = $1")
-            .bind(&transaction_data.tontine_id)
+            .bind(transaction_data.tontine_id)
             .fetch_optional(pool)
             .await?;
 
@@ -183,42 +198,42 @@ impl TransactionRepository {
             }
         }
 
+        let mut tx = pool.begin().await?;
+
+        let transaction = Self::insert(&mut tx, transaction_data, TransactionStatus::Completed).await?;
+
+        tx.commit().await?;
+
+        Ok(transaction)
+    }
+
+    /// Insère la transaction et la journalise si elle est réglée.
+    /// S'exécute dans la transaction de l'appelant.
+    pub async fn insert(
+        conn: &mut PgConnection,
+        transaction_data: &CreateTransaction,
+        status: TransactionStatus,
+    ) -> Result<Transaction, AppError> {
         let transaction_type_str: String = transaction_data.transaction_type.clone().into();
 
         let transaction = sqlx::query_as::<_, Transaction>(
-            "INSERT INTO transactions (tontine_id, from_user_id, to_user_id, amount, transaction_type, description) 
-             VALUES ($1, $2, $3, $4, $5, $6) 
+            "INSERT INTO transactions (tontine_id, from_user_id, to_user_id, amount, transaction_type, status, description) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7) 
              RETURNING id, tontine_id, from_user_id, to_user_id, amount, transaction_type, status, description, created_at"
         )
-        .bind(&transaction_data.tontine_id)
-        .bind(&transaction_data.from_user_id)
-        .bind(&transaction_data.to_user_id)
-        .bind(&transaction_data.amount)
+        .bind(transaction_data.tontine_id)
+        .bind(transaction_data.from_user_id)
+        .bind(transaction_data.to_user_id)
+        .bind(transaction_data.amount)
         .bind(&transaction_type_str)
+        .bind(String::from(status))
         .bind(&transaction_data.description)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
-        Ok(transaction)
-    }
-
-    pub async fn create_contribution_transaction(
-        pool: &PgPool,
-        tontine_id: Uuid,
-        from_user_id: Uuid,
-        amount: rust_decimal::Decimal,
-        description: Option<String>,
-    ) -> Result<Transaction, AppError> {
-        let transaction_data = CreateTransaction {
-            tontine_id,
-            from_user_id: Some(from_user_id),
-            to_user_id: None, // La tontine reçoit l'argent
-            amount,
-            transaction_type: TransactionType::Contribution,
-            description: description.or_else(|| Some("Cotisation tontine".to_string())),
-        };
+        LedgerRepository::sync_transaction(conn, &transaction).await?;
 
-        Self::create(pool, &transaction_data).await
+        Ok(transaction)
     }
 
     pub async fn create_payout_transaction(
@@ -261,6 +276,17 @@ impl TransactionRepository {
     }
 
     pub async fn update_status(pool: &PgPool, transaction_id: Uuid, status: TransactionStatus) -> Result<Transaction, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let transaction = Self::set_status(&mut tx, transaction_id, status).await?;
+
+        tx.commit().await?;
+
+        Ok(transaction)
+    }
+
+    /// Change le statut dans la transaction de l'appelant
+    pub async fn set_status(conn: &mut PgConnection, transaction_id: Uuid, status: TransactionStatus) -> Result<Transaction, AppError> {
         let status_str: String = status.into();
 
         let transaction = sqlx::query_as::<_, Transaction>(
@@ -271,13 +297,16 @@ impl TransactionRepository {
         )
         .bind(&status_str)
         .bind(transaction_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
+        // Journalisation au règlement, contre-passation si la transaction ne l'est plus
+        LedgerRepository::sync_transaction(conn, &transaction).await?;
+
         Ok(transaction)
     }
 
-    pub async fn get_transactions_by_type(pool: &PgPool, transaction_type: TransactionType) -> Result<Vec<TransactionWithUsers>, AppError> {
+    pub async fn get_transactions_by_type(pool: &PgPool, transaction_type: TransactionType, user_id: Uuid) -> Result<Vec<TransactionWithUsers>, AppError> {
         let type_str: String = transaction_type.into();
 
         let transactions = sqlx::query(
@@ -290,9 +319,17 @@ impl TransactionRepository {
              LEFT JOIN users u_to ON t.to_user_id = u_to.id
              JOIN tontines ton ON t.tontine_id = ton.id
              WHERE t.transaction_type = $1
+               AND (t.from_user_id = $2 OR t.to_user_id = $2
+                    OR ton.id IN (
+                        SELECT id FROM tontines WHERE created_by = $2
+                        UNION
+                        SELECT tontine_id FROM tontine_members
+                        WHERE user_id = $2 AND is_active = true AND role IN ('owner', 'treasurer')
+                    ))
              ORDER BY t.created_at DESC"
         )
         .bind(&type_str)
+        .bind(user_id)
         .fetch_all(pool)
         .await?;
 
@@ -321,8 +358,8 @@ impl TransactionRepository {
                 COALESCE(SUM(CASE WHEN transaction_type = 'contribution' THEN amount ELSE 0 END), 0) as total_contributions,
                 COALESCE(SUM(CASE WHEN transaction_type = 'payout' THEN amount ELSE 0 END), 0) as total_payouts,
                 COALESCE(SUM(CASE WHEN transaction_type = 'refund' THEN amount ELSE 0 END), 0) as total_refunds,
-                (COALESCE(SUM(CASE WHEN transaction_type = 'contribution' THEN amount ELSE 0 END), 0) - 
-                 COALESCE(SUM(CASE WHEN transaction_type IN ('payout', 'refund') THEN amount ELSE 0 END), 0)) as current_balance
+                COALESCE(SUM(CASE WHEN transaction_type = 'bid_discount' THEN amount ELSE 0 END), 0) as total_bid_discounts,
+                COALESCE(SUM(CASE WHEN transaction_type = 'bid_dividend' THEN amount ELSE 0 END), 0) as total_bid_dividends
              FROM transactions 
              WHERE tontine_id = $1 AND status = 'completed'"
         )
@@ -330,12 +367,22 @@ impl TransactionRepository {
         .fetch_one(pool)
         .await?;
 
+        // Soldes issus du grand livre : la caisse regroupe le pot et la réserve des remises
+        let pot_balance = LedgerRepository::balance_of(pool, tontine_id, AccountKind::TontinePot).await?;
+        let reserve_balance = LedgerRepository::balance_of(pool, tontine_id, AccountKind::TontineReserve).await?;
+        let penalties_balance = LedgerRepository::balance_of(pool, tontine_id, AccountKind::Penalties).await?;
+
         let financial_summary = TontineFinancialSummary {
             total_transactions: summary.get("total_transactions"),
             total_contributions: summary.get("total_contributions"),
             total_payouts: summary.get("total_payouts"),
             total_refunds: summary.get("total_refunds"),
-            current_balance: summary.get("current_balance"),
+            total_bid_discounts: summary.get("total_bid_discounts"),
+            total_bid_dividends: summary.get("total_bid_dividends"),
+            current_balance: pot_balance + reserve_balance,
+            pot_balance,
+            reserve_balance,
+            penalties_balance,
         };
 
         Ok(financial_summary)
@@ -346,9 +393,7 @@ impl TransactionRepository {
             "SELECT 
                 COUNT(*) as total_transactions,
                 COALESCE(SUM(CASE WHEN from_user_id = $1 THEN amount ELSE 0 END), 0) as total_sent,
-                COALESCE(SUM(CASE WHEN to_user_id = $1 THEN amount ELSE 0 END), 0) as total_received,
-                (COALESCE(SUM(CASE WHEN to_user_id = $1 THEN amount ELSE 0 END), 0) - 
-                 COALESCE(SUM(CASE WHEN from_user_id = $1 THEN amount ELSE 0 END), 0)) as net_balance
+                COALESCE(SUM(CASE WHEN to_user_id = $1 THEN amount ELSE 0 END), 0) as total_received
              FROM transactions 
              WHERE (from_user_id = $1 OR to_user_id = $1) AND status = 'completed'"
         )
@@ -356,11 +401,13 @@ impl TransactionRepository {
         .fetch_one(pool)
         .await?;
 
+        let net_balance = LedgerRepository::wallet_balance(pool, user_id).await?;
+
         let user_summary = UserFinancialSummary {
             total_transactions: summary.get("total_transactions"),
             total_sent: summary.get("total_sent"),
             total_received: summary.get("total_received"),
-            net_balance: summary.get("net_balance"),
+            net_balance,
         };
 
         Ok(user_summary)
@@ -373,7 +420,15 @@ pub struct TontineFinancialSummary {
     pub total_contributions: rust_decimal::Decimal,
     pub total_payouts: rust_decimal::Decimal,
     pub total_refunds: rust_decimal::Decimal,
+    /// Remises d'enchères retenues sur les versements (déjà reflétées dans le solde, le pot n'ayant pas été versé)
+    pub total_bid_discounts: rust_decimal::Decimal,
+    pub total_bid_dividends: rust_decimal::Decimal,
+    /// Pot et réserve, d'après le grand livre
     pub current_balance: rust_decimal::Decimal,
+    pub pot_balance: rust_decimal::Decimal,
+    pub reserve_balance: rust_decimal::Decimal,
+    /// Amendes encaissées
+    pub penalties_balance: rust_decimal::Decimal,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -381,5 +436,6 @@ pub struct UserFinancialSummary {
     pub total_transactions: i64,
     pub total_sent: rust_decimal::Decimal,
     pub total_received: rust_decimal::Decimal,
+    /// Solde des portefeuilles du membre d'après le grand livre (amendes payées comprises)
     pub net_balance: rust_decimal::Decimal,
 }
\ No newline at end of file
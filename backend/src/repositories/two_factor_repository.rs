@@ -0,0 +1,159 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::model::two_factor::{TwoFactorChallenge, TwoFactorSettings};
+use crate::errors::AppError;
+
+pub struct TwoFactorRepository;
+
+impl TwoFactorRepository {
+    pub async fn find_settings(pool: &PgPool, user_id: Uuid) -> Result<TwoFactorSettings, AppError> {
+        let settings = sqlx::query_as::<_, TwoFactorSettings>(
+            "SELECT totp_secret, totp_enabled_at, totp_last_used_step, totp_backup_codes FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match settings {
+            Some(settings) => Ok(settings),
+            None => Err(AppError::NotFound(format!("Utilisateur avec l'ID {} non trouvé", user_id))),
+        }
+    }
+
+    /// Enregistre un secret en attente de confirmation (la 2FA n'est pas encore active)
+    pub async fn set_pending_secret(pool: &PgPool, user_id: Uuid, secret: &str) -> Result<(), AppError> {
+        let result = sqlx::query(
+            "UPDATE users SET totp_secret = $1, totp_last_used_step = NULL WHERE id = $2 AND totp_enabled_at IS NULL"
+        )
+        .bind(secret)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::ValidationError("La double authentification est déjà activée".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn enable(pool: &PgPool, user_id: Uuid, backup_code_hashes: &[String]) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET totp_enabled_at = $1, totp_backup_codes = $2 WHERE id = $3")
+            .bind(Utc::now())
+            .bind(backup_code_hashes)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn disable(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE users
+             SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_used_step = NULL, totp_backup_codes = '{}'
+             WHERE id = $1"
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mémorise le pas TOTP utilisé ; faux si ce code (ou un plus récent) a déjà servi
+    pub async fn record_totp_step(pool: &PgPool, user_id: Uuid, step: i64) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE users SET totp_last_used_step = $1
+             WHERE id = $2 AND (totp_last_used_step IS NULL OR totp_last_used_step < $1)"
+        )
+        .bind(step)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Retire le code de secours de la liste ; faux s'il n'existe pas ou a déjà été utilisé
+    pub async fn consume_backup_code(pool: &PgPool, user_id: Uuid, code_hash: &str) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE users SET totp_backup_codes = array_remove(totp_backup_codes, $1)
+             WHERE id = $2 AND $1 = ANY(totp_backup_codes)"
+        )
+        .bind(code_hash)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn create_challenge(
+        pool: &PgPool,
+        user_id: Uuid,
+        challenge_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<TwoFactorChallenge, AppError> {
+        let challenge = sqlx::query_as::<_, TwoFactorChallenge>(
+            "INSERT INTO two_factor_challenges (user_id, challenge_hash, expires_at)
+             VALUES ($1, $2, $3)
+             RETURNING id, user_id, challenge_hash, attempts, expires_at, consumed_at, created_at"
+        )
+        .bind(user_id)
+        .bind(challenge_hash)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Défi en cours (ni consommé, ni expiré) correspondant au jeton présenté
+    pub async fn find_pending_challenge(pool: &PgPool, challenge_hash: &str) -> Result<TwoFactorChallenge, AppError> {
+        let challenge = sqlx::query_as::<_, TwoFactorChallenge>(
+            "SELECT id, user_id, challenge_hash, attempts, expires_at, consumed_at, created_at
+             FROM two_factor_challenges
+             WHERE challenge_hash = $1 AND consumed_at IS NULL AND expires_at > NOW()"
+        )
+        .bind(challenge_hash)
+        .fetch_optional(pool)
+        .await?;
+
+        challenge.ok_or_else(|| AppError::AuthenticationError("Défi de connexion invalide ou expiré".to_string()))
+    }
+
+    /// Compte un code erroné ; le défi est abandonné une fois le maximum atteint
+    pub async fn record_challenge_failure(pool: &PgPool, challenge_id: Uuid, max_attempts: i32) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE two_factor_challenges
+             SET attempts = attempts + 1,
+                 consumed_at = CASE WHEN attempts + 1 >= $1 THEN NOW() ELSE consumed_at END
+             WHERE id = $2"
+        )
+        .bind(max_attempts)
+        .bind(challenge_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Consomme le défi ; échoue s'il a déjà été utilisé entre-temps
+    pub async fn consume_challenge(pool: &PgPool, challenge_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query(
+            "UPDATE two_factor_challenges SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL"
+        )
+        .bind(challenge_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::AuthenticationError("Défi de connexion invalide ou expiré".to_string()));
+        }
+
+        Ok(())
+    }
+}
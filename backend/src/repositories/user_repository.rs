@@ -1,23 +1,14 @@
-use sqlx::{PgPool};
+use sqlx::{PgPool, Row};
 use uuid::Uuid;
 use bcrypt::{hash, verify, DEFAULT_COST};
 
-use crate::model::users::{User, CreateUser, UpdateUser, UserResponse};
+use crate::model::users::{User, CreateUser, UpdateUser, UserResponse, VerificationStatus};
+use crate::repositories::session_repository::SessionRepository;
 use crate::errors::AppError;
 
 pub struct UserRepository;
 
 impl UserRepository {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<UserResponse>, AppError> {
-        let users = sqlx::query_as::<_, User>(
-            "SELECT id, email, phone, full_name, password_hash, is_active, created_at, updated_at FROM users ORDER BY created_at DESC"
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(users.into_iter().map(|user| user.into()).collect())
-    }
-
     pub async fn find_by_id(pool: &PgPool, user_id: Uuid) -> Result<UserResponse, AppError> {
         let user = sqlx::query_as::<_, User>(
             "SELECT id, email, phone, full_name, password_hash, is_active, created_at, updated_at FROM users WHERE id = $1"
@@ -47,7 +38,20 @@ impl UserRepository {
         }
     }
 
-    
+    pub async fn find_by_phone(pool: &PgPool, phone: &str) -> Result<User, AppError> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, email, phone, full_name, password_hash, is_active, created_at, updated_at FROM users WHERE phone = $1"
+        )
+        .bind(phone)
+        .fetch_optional(pool)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(AppError::NotFound(format!("Utilisateur avec le téléphone {} non trouvé", phone))),
+        }
+    }
+
     pub async fn create(pool: &PgPool, user_data: &CreateUser) -> Result<UserResponse, AppError> {
         // Vérifier si l'email existe déjà
         let existing_user = sqlx::query("SELECT id FROM users WHERE email = $1 OR phone = $2")
@@ -64,9 +68,10 @@ impl UserRepository {
         let password_hash = hash(&user_data.password, DEFAULT_COST)
             .map_err(|e| AppError::InternalServerError(e.to_string()))?;
 
+        // Le compte reste inactif tant que l'email et le téléphone ne sont pas vérifiés
         let user = sqlx::query_as::<_, User>(
-            "INSERT INTO users (email, phone, full_name, password_hash) 
-             VALUES ($1, $2, $3, $4) 
+            "INSERT INTO users (email, phone, full_name, password_hash, is_active) 
+             VALUES ($1, $2, $3, $4, false) 
              RETURNING id, email, phone, full_name, password_hash, is_active, created_at, updated_at"
         )
         .bind(&user_data.email)
@@ -88,22 +93,29 @@ impl UserRepository {
         let mut params: Vec<String> = Vec::new();
         let mut counter = 1;
 
-        if let Some(email) = &user_data.email {
+        if user_data.email.is_some() {
             params.push(format!("email = ${}", counter));
             counter += 1;
         }
-        if let Some(phone) = &user_data.phone {
+        if user_data.phone.is_some() {
             params.push(format!("phone = ${}", counter));
             counter += 1;
         }
-        if let Some(full_name) = &user_data.full_name {
+        if user_data.full_name.is_some() {
             params.push(format!("full_name = ${}", counter));
             counter += 1;
         }
-        if let Some(is_active) = user_data.is_active {
+        if user_data.is_active.is_some() {
             params.push(format!("is_active = ${}", counter));
             counter += 1;
         }
+        // Un nouvel email ou téléphone doit être vérifié à nouveau avant de servir à la connexion
+        if user_data.email.as_ref().is_some_and(|email| *email != existing_user.email) {
+            params.push("email_verified_at = NULL".to_string());
+        }
+        if user_data.phone.as_ref().is_some_and(|phone| *phone != existing_user.phone) {
+            params.push("phone_verified_at = NULL".to_string());
+        }
 
         if params.is_empty() {
             return Ok(existing_user);
@@ -129,7 +141,15 @@ impl UserRepository {
 
         query_builder = query_builder.bind(user_id);
 
-        let user = query_builder.fetch_one(pool).await?;
+        let mut tx = pool.begin().await?;
+        let user = query_builder.fetch_one(&mut *tx).await?;
+
+        // Un compte désactivé perd immédiatement toutes ses sessions
+        if user_data.is_active == Some(false) {
+            SessionRepository::revoke_all_for_user(&mut tx, user_id, None).await?;
+        }
+
+        tx.commit().await?;
         Ok(user.into())
     }
 
@@ -151,20 +171,32 @@ impl UserRepository {
             .map_err(|e| AppError::AuthenticationError(e.to_string()))
     }
 
-    pub async fn change_password(pool: &PgPool, user_id: Uuid, new_password: &str) -> Result<(), AppError> {
+    /// Change le mot de passe et révoque les autres sessions de l'utilisateur
+    pub async fn change_password(
+        pool: &PgPool,
+        user_id: Uuid,
+        new_password: &str,
+        current_session_id: Option<Uuid>,
+    ) -> Result<(), AppError> {
         let password_hash = hash(new_password, DEFAULT_COST)
             .map_err(|e| AppError::InternalServerError(e.to_string()))?;
 
+        let mut tx = pool.begin().await?;
+
         let result = sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
             .bind(&password_hash)
             .bind(user_id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound(format!("Utilisateur avec l'ID {} non trouvé", user_id)));
         }
 
+        SessionRepository::revoke_all_for_user(&mut tx, user_id, current_session_id).await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -183,4 +215,57 @@ impl UserRepository {
         }
     }
 
+    pub async fn find_verification_status(pool: &PgPool, user_id: Uuid) -> Result<VerificationStatus, AppError> {
+        let row = sqlx::query(
+            "SELECT email_verified_at IS NOT NULL AS email_verified, phone_verified_at IS NOT NULL AS phone_verified
+             FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(VerificationStatus {
+                email_verified: row.get("email_verified"),
+                phone_verified: row.get("phone_verified"),
+            }),
+            None => Err(AppError::NotFound(format!("Utilisateur avec l'ID {} non trouvé", user_id))),
+        }
+    }
+
+    /// Marque l'email comme vérifié ; le compte est activé si le téléphone l'est déjà
+    pub async fn mark_email_verified(pool: &PgPool, user_id: Uuid) -> Result<UserResponse, AppError> {
+        Self::mark_contact_verified(pool, user_id, "email_verified_at", "phone_verified_at").await
+    }
+
+    /// Marque le téléphone comme vérifié ; le compte est activé si l'email l'est déjà
+    pub async fn mark_phone_verified(pool: &PgPool, user_id: Uuid) -> Result<UserResponse, AppError> {
+        Self::mark_contact_verified(pool, user_id, "phone_verified_at", "email_verified_at").await
+    }
+
+    async fn mark_contact_verified(
+        pool: &PgPool,
+        user_id: Uuid,
+        column: &str,
+        other_column: &str,
+    ) -> Result<UserResponse, AppError> {
+        // L'activation n'a lieu qu'au passage à l'état vérifié, pour ne pas réactiver un compte désactivé
+        let query = format!(
+            "UPDATE users
+             SET is_active = is_active OR ({column} IS NULL AND {other_column} IS NOT NULL),
+                 {column} = COALESCE({column}, NOW())
+             WHERE id = $1
+             RETURNING id, email, phone, full_name, password_hash, is_active, created_at, updated_at"
+        );
+
+        let user = sqlx::query_as::<_, User>(&query)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+        match user {
+            Some(user) => Ok(user.into()),
+            None => Err(AppError::NotFound(format!("Utilisateur avec l'ID {} non trouvé", user_id))),
+        }
+    }
 }
\ No newline at end of file
@@ -1,26 +1,44 @@
 use actix_web::web;
+use actix_web::middleware::from_fn;
 use actix_web_httpauth::middleware::HttpAuthentication;
 
 use crate::handlers::auth_handler::AuthHandler;
+use crate::handlers::two_factor_handler::TwoFactorHandler;
 use crate::auth::middleware::validator;
+use crate::auth::rate_limit::rate_limit;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     let auth_middleware = HttpAuthentication::bearer(validator);
 
+    // Seules les routes publiques et les vérifications de code 2FA sont limitées : les autres routes protégées exigent déjà un jeton
     cfg.service(
         web::scope("/api/auth")
             // Routes publiques
-            .route("/login", web::post().to(AuthHandler::login))
-            .route("/logout", web::post().to(AuthHandler::logout))
-            .route("/request-password-reset", web::post().to(AuthHandler::request_password_reset))
-            .route("/confirm-password-reset", web::post().to(AuthHandler::confirm_password_reset))
+            .route("/register", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::register))
+            .route("/verify-email", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::verify_email))
+            .route("/verify-phone", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::verify_phone))
+            .route("/resend-verification", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::resend_verification))
+            .route("/login", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::login))
+            .route("/otp/request", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::request_otp))
+            .route("/otp/login", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::login_with_otp))
+            .route("/2fa/login", web::post().wrap(from_fn(rate_limit)).to(TwoFactorHandler::login))
+            .route("/refresh-token", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::refresh_token))
+            .route("/request-password-reset", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::request_password_reset))
+            .route("/confirm-password-reset", web::post().wrap(from_fn(rate_limit)).to(AuthHandler::confirm_password_reset))
             // Routes protégées
             .service(
                 web::scope("")
                     .wrap(auth_middleware)
                     .route("/me", web::get().to(AuthHandler::get_me))
                     .route("/change-password", web::put().to(AuthHandler::change_password))
-                    .route("/refresh-token", web::post().to(AuthHandler::refresh_token))
+                    .route("/logout", web::post().to(AuthHandler::logout))
+                    .route("/logout-all", web::post().to(AuthHandler::logout_all))
+                    .route("/sessions", web::get().to(AuthHandler::get_sessions))
+                    .route("/sessions/{id}", web::delete().to(AuthHandler::revoke_session))
+                    .route("/2fa/setup", web::post().to(TwoFactorHandler::setup))
+                    .route("/2fa/enable", web::post().wrap(from_fn(rate_limit)).to(TwoFactorHandler::enable))
+                    .route("/2fa/disable", web::post().wrap(from_fn(rate_limit)).to(TwoFactorHandler::disable))
+                    .route("/2fa/verify", web::post().wrap(from_fn(rate_limit)).to(TwoFactorHandler::verify))
             )
     );
 }
\ No newline at end of file
@@ -1,11 +1,19 @@
 use actix_web::web;
+use actix_web::middleware::from_fn;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::contribution_handlers::ContributionHandler;
+use crate::auth::middleware::validator;
+use crate::idempotency::idempotency;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
         web::scope("/api/contributions")
+            .wrap(auth_middleware)
             .route("", web::get().to(ContributionHandler::get_contributions))
-            .route("", web::post().to(ContributionHandler::create_contribution))
+            .route("", web::post().wrap(from_fn(idempotency)).to(ContributionHandler::create_contribution))
             .route("/round/{round_id}", web::get().to(ContributionHandler::get_round_contributions))
             .route("/round/{round_id}/summary", web::get().to(ContributionHandler::get_round_summary))
             .route("/member/{member_id}", web::get().to(ContributionHandler::get_member_contributions))
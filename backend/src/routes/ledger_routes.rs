@@ -0,0 +1,17 @@
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+use crate::handlers::ledger_handlers::LedgerHandler;
+use crate::auth::middleware::validator;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
+    cfg.service(
+        web::scope("/api/ledger")
+            .wrap(auth_middleware)
+            .route("/tontine/{tontine_id}/balances", web::get().to(LedgerHandler::get_balances))
+            .route("/tontine/{tontine_id}/journal", web::get().to(LedgerHandler::get_journal))
+            .route("/tontine/{tontine_id}/check", web::get().to(LedgerHandler::check))
+    );
+}
@@ -5,6 +5,10 @@ pub mod tontine_round_routes;
 pub mod  contribution_routes;
 pub mod transaction_routes;
 pub mod auth_routes;
+pub mod penalty_routes;
+pub mod ledger_routes;
+pub mod payment_routes;
+pub mod reconciliation_routes;
 
 use actix_web::web;
 
@@ -16,4 +20,8 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     contribution_routes::config(cfg);
     transaction_routes::config(cfg);
     auth_routes::config(cfg);
+    penalty_routes::config(cfg);
+    ledger_routes::config(cfg);
+    payment_routes::config(cfg);
+    reconciliation_routes::config(cfg);
 }
\ No newline at end of file
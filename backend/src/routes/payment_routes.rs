@@ -0,0 +1,25 @@
+use actix_web::web;
+use actix_web::middleware::from_fn;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+use crate::handlers::payment_handlers::PaymentHandler;
+use crate::auth::middleware::validator;
+use crate::idempotency::idempotency;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
+    // Notifications des opérateurs : authentifiées par leur signature, déclarées avant le scope protégé
+    cfg.service(
+        web::scope("/api/payments/callback")
+            .route("/{provider}", web::post().to(PaymentHandler::callback))
+    );
+
+    cfg.service(
+        web::scope("/api/payments")
+            .wrap(auth_middleware)
+            .route("/contribution/{contribution_id}", web::get().to(PaymentHandler::get_contribution_payments))
+            .route("/{id}", web::get().to(PaymentHandler::get_payment))
+            .route("/{id}/refresh", web::post().wrap(from_fn(idempotency)).to(PaymentHandler::refresh_payment))
+    );
+}
@@ -0,0 +1,21 @@
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+use crate::handlers::penalty_handlers::PenaltyHandler;
+use crate::auth::middleware::validator;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
+    cfg.service(
+        web::scope("/api/penalties")
+            .wrap(auth_middleware)
+            .route("/tontine/{tontine_id}", web::get().to(PenaltyHandler::get_tontine_penalties))
+            .route("/tontine/{tontine_id}/rule", web::get().to(PenaltyHandler::get_rule))
+            .route("/tontine/{tontine_id}/rule", web::put().to(PenaltyHandler::set_rule))
+            .route("/tontine/{tontine_id}/rule", web::delete().to(PenaltyHandler::delete_rule))
+            .route("/member/{member_id}", web::get().to(PenaltyHandler::get_member_penalties))
+            .route("/{id}/mark-paid", web::put().to(PenaltyHandler::mark_as_paid))
+            .route("/{id}/waive", web::put().to(PenaltyHandler::waive))
+    );
+}
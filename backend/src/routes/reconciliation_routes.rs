@@ -0,0 +1,22 @@
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+use crate::handlers::reconciliation_handlers::ReconciliationHandler;
+use crate::auth::middleware::validator;
+
+/// Taille maximale d'un relevé importé
+const MAX_STATEMENT_SIZE: usize = 5 * 1024 * 1024;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
+    cfg.service(
+        web::scope("/api/reconciliation")
+            .wrap(auth_middleware)
+            .app_data(web::PayloadConfig::new(MAX_STATEMENT_SIZE))
+            .route("/tontine/{tontine_id}/import", web::post().to(ReconciliationHandler::import_statement))
+            .route("/tontine/{tontine_id}/runs", web::get().to(ReconciliationHandler::get_runs))
+            .route("/runs/{run_id}", web::get().to(ReconciliationHandler::get_report))
+            .route("/items/{item_id}/resolve", web::put().to(ReconciliationHandler::resolve_item))
+    );
+}
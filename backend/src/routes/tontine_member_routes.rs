@@ -1,9 +1,15 @@
 use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::tontine_member_handlers::TontineMemberHandler;
+use crate::auth::middleware::validator;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
         web::scope("/api/tontine-members")
+            .wrap(auth_middleware)
             .route("", web::get().to(TontineMemberHandler::get_members))
             .route("", web::post().to(TontineMemberHandler::create_member))
             .route("/tontine/{tontine_id}", web::get().to(TontineMemberHandler::get_tontine_members))
@@ -1,9 +1,18 @@
 use actix_web::web;
+use actix_web::middleware::from_fn;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::tontine_round_handlers::TontineRoundHandler;
+use crate::handlers::bid_handlers::BidHandler;
+use crate::auth::middleware::validator;
+use crate::idempotency::idempotency;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
         web::scope("/api/tontine-rounds")
+            .wrap(auth_middleware)
             .route("", web::get().to(TontineRoundHandler::get_rounds))
             .route("", web::post().to(TontineRoundHandler::create_round))
             .route("/status/{status}", web::get().to(TontineRoundHandler::get_rounds_by_status))
@@ -13,7 +22,15 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/{id}", web::get().to(TontineRoundHandler::get_round))
             .route("/{id}", web::put().to(TontineRoundHandler::update_round))
             .route("/{id}", web::delete().to(TontineRoundHandler::delete_round))
-            .route("/{id}/complete", web::put().to(TontineRoundHandler::complete_round))
+            .route("/{id}/payout", web::get().to(TontineRoundHandler::get_round_payout))
+            .route("/{id}/payout", web::post().wrap(from_fn(idempotency)).to(TontineRoundHandler::pay_round))
+            .route("/{id}/draw", web::post().to(TontineRoundHandler::draw_beneficiary))
             .route("/{id}/cancel", web::put().to(TontineRoundHandler::cancel_round))
+            .route("/{id}/bidding", web::get().to(BidHandler::get_bidding))
+            .route("/{id}/bidding/open", web::post().to(BidHandler::open_bidding))
+            .route("/{id}/bidding/close", web::post().to(BidHandler::close_bidding))
+            .route("/{id}/bidding/resolve", web::post().wrap(from_fn(idempotency)).to(BidHandler::resolve_bidding))
+            .route("/{id}/bids", web::post().to(BidHandler::submit_bid))
+            .route("/{id}/bids", web::delete().to(BidHandler::withdraw_bid))
     );
 }
\ No newline at end of file
@@ -1,9 +1,17 @@
 use actix_web::web;
+use actix_web::middleware::from_fn;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::tontine_handlers::TontineHandler;
+use crate::auth::middleware::validator;
+use crate::idempotency::idempotency;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
         web::scope("/api/tontines")
+            .wrap(auth_middleware)
             .route("", web::get().to(TontineHandler::get_tontines))
             .route("", web::post().to(TontineHandler::create_tontine))
             .route("/active", web::get().to(TontineHandler::get_active_tontines))
@@ -12,6 +20,15 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/{id}/details", web::get().to(TontineHandler::get_tontine_with_creator))
             .route("/{id}", web::put().to(TontineHandler::update_tontine))
             .route("/{id}", web::delete().to(TontineHandler::delete_tontine))
+            .route("/{id}/open", web::post().to(TontineHandler::open_recruitment))
+            .route("/{id}/start", web::post().to(TontineHandler::start_tontine))
+            .route("/{id}/complete", web::post().to(TontineHandler::complete_tontine))
+            .route("/{id}/cancel", web::post().wrap(from_fn(idempotency)).to(TontineHandler::cancel_tontine))
+            .route("/{id}/history", web::get().to(TontineHandler::get_status_history))
+            .route("/{id}/draws", web::get().to(TontineHandler::get_draws))
+            .route("/{id}/draws/{draw_id}/verify", web::get().to(TontineHandler::verify_draw))
+            .route("/{id}/jobs", web::get().to(TontineHandler::get_scheduled_jobs))
+            .route("/{id}/payouts", web::get().to(TontineHandler::get_payouts))
             .route("/{id}/increment-round", web::put().to(TontineHandler::increment_round))
     );
 }
\ No newline at end of file
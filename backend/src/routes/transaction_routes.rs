@@ -1,13 +1,20 @@
 use actix_web::web;
+use actix_web::middleware::from_fn;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::transaction_handlers::TransactionHandler;
+use crate::auth::middleware::validator;
+use crate::idempotency::idempotency;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
         web::scope("/api/transactions")
+            .wrap(auth_middleware)
             .route("", web::get().to(TransactionHandler::get_transactions))
-            .route("", web::post().to(TransactionHandler::create_transaction))
-            .route("/contribution", web::post().to(TransactionHandler::create_contribution_transaction))
-            .route("/payout", web::post().to(TransactionHandler::create_payout_transaction))
+            .route("", web::post().wrap(from_fn(idempotency)).to(TransactionHandler::create_transaction))
+            .route("/payout", web::post().wrap(from_fn(idempotency)).to(TransactionHandler::create_payout_transaction))
             .route("/type/{transaction_type}", web::get().to(TransactionHandler::get_transactions_by_type))
             .route("/tontine/{tontine_id}", web::get().to(TransactionHandler::get_tontine_transactions))
             .route("/tontine/{tontine_id}/summary", web::get().to(TransactionHandler::get_tontine_financial_summary))
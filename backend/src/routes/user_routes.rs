@@ -1,14 +1,19 @@
 use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
 use crate::handlers::user_handlers::UserHandler;
+use crate::auth::middleware::validator;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth_middleware = HttpAuthentication::bearer(validator);
+
     cfg.service(
+        // L'inscription passe par /api/auth/register
         web::scope("/api/users")
-            .route("", web::get().to(UserHandler::get_users))
-            .route("", web::post().to(UserHandler::create_user))
+            .wrap(auth_middleware)
             .route("/{id}", web::get().to(UserHandler::get_user))
             .route("/{id}", web::put().to(UserHandler::update_user))
             .route("/{id}", web::delete().to(UserHandler::delete_user))
             .route("/{id}/change-password", web::put().to(UserHandler::change_password))
     );
-}
\ No newline at end of file
+}
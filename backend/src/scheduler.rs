@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::model::scheduled_jobs::{JobType, ScheduledJob};
+use crate::payments::PaymentProviders;
+use crate::payments::service::PaymentService;
+use crate::repositories::contributions_repository::ContributionRepository;
+use crate::repositories::idempotency_key_repository::IdempotencyKeyRepository;
+use crate::repositories::scheduled_job_repository::ScheduledJobRepository;
+use crate::repositories::tontine_round_repository::TontineRoundRepository;
+use crate::settings::SchedulerSettings;
+
+/// Nombre maximal de tâches traitées à chaque passage
+const BATCH_SIZE: i64 = 50;
+/// Une tâche verrouillée depuis plus longtemps est considérée comme abandonnée (arrêt du serveur)
+const STALE_LOCK_MINUTES: i64 = 10;
+/// Délai avant le premier nouvel essai d'une tâche en échec, doublé à chaque tentative
+const RETRY_BASE_SECONDS: i64 = 60;
+
+/// Planificateur exécuté en tâche de fond : les tâches sont stockées dans `scheduled_jobs`,
+/// ce qui permet de reprendre après un redémarrage
+pub struct Scheduler {
+    pool: PgPool,
+    settings: SchedulerSettings,
+    payment_providers: Arc<PaymentProviders>,
+}
+
+impl Scheduler {
+    pub fn new(pool: PgPool, settings: SchedulerSettings, payment_providers: Arc<PaymentProviders>) -> Self {
+        Self { pool, settings, payment_providers }
+    }
+
+    /// Lance la boucle du planificateur sur le runtime courant
+    pub fn spawn(self) {
+        if !self.settings.enabled {
+            println!(" Planificateur désactivé (SCHEDULER_ENABLED=false)");
+            return;
+        }
+
+        actix_web::rt::spawn(async move {
+            let mut interval = tokio::time::interval(StdDuration::from_secs(self.settings.interval_seconds));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run_due_jobs().await {
+                    eprintln!("[scheduler] Erreur lors de la récupération des tâches : {}", e);
+                }
+                if let Err(e) = IdempotencyKeyRepository::purge_expired(&self.pool).await {
+                    eprintln!("[scheduler] Erreur lors de la purge des clés d'idempotence : {}", e);
+                }
+            }
+        });
+    }
+
+    /// Exécute toutes les tâches arrivées à échéance
+    pub async fn run_due_jobs(&self) -> Result<usize, AppError> {
+        let stale_before = Utc::now() - Duration::minutes(STALE_LOCK_MINUTES);
+        let jobs = ScheduledJobRepository::claim_due(&self.pool, BATCH_SIZE, stale_before).await?;
+        let count = jobs.len();
+
+        for job in jobs {
+            match self.execute(&job).await {
+                Ok(()) => ScheduledJobRepository::mark_done(&self.pool, job.id).await?,
+                Err(e) => {
+                    eprintln!("[scheduler] Échec de la tâche {} ({}) : {}", job.id, job.job_type, e);
+                    let retry_at = Utc::now() + Duration::seconds(RETRY_BASE_SECONDS << (job.attempts - 1).clamp(0, 10));
+                    ScheduledJobRepository::mark_failed(&self.pool, job.id, &e.to_string(), self.settings.max_attempts, retry_at).await?;
+                    if job.attempts >= self.settings.max_attempts {
+                        self.abandon(&job, &e.to_string()).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn execute(&self, job: &ScheduledJob) -> Result<(), AppError> {
+        let job_type = JobType::try_from(job.job_type.clone()).map_err(AppError::InternalServerError)?;
+        let round_id = job.tontine_round_id
+            .ok_or_else(|| AppError::InternalServerError(format!("Tâche {} sans tour associé", job.id)))?;
+
+        match job_type {
+            JobType::OpenRound => {
+                let grace_period = Duration::hours(self.settings.grace_period_hours);
+                if let Some(round) = TontineRoundRepository::open_round(&self.pool, round_id, grace_period).await? {
+                    println!("[scheduler] Tour {} ouvert pour la tontine {}", round.round_number, round.tontine_id);
+                }
+            }
+            JobType::MarkLateContributions => {
+                let late = ContributionRepository::mark_late_for_round(&self.pool, round_id).await?;
+                if late > 0 {
+                    println!("[scheduler] {} cotisation(s) en retard pour le tour {}", late, round_id);
+                }
+            }
+            JobType::DisbursePayout => {
+                PaymentService::process_disbursement(&self.pool, &self.payment_providers, round_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tâche abandonnée après le dernier essai
+    async fn abandon(&self, job: &ScheduledJob, error: &str) -> Result<(), AppError> {
+        if job.job_type == String::from(JobType::DisbursePayout)
+            && let Some(round_id) = job.tontine_round_id
+        {
+            PaymentService::abandon_disbursement(&self.pool, &self.payment_providers, round_id, error).await?;
+        }
+        Ok(())
+    }
+}
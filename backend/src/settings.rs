@@ -0,0 +1,669 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// Fichier lu au démarrage s'il existe, sauf si `SETTINGS_FILE` désigne un autre chemin
+const DEFAULT_SETTINGS_FILE: &str = "settings.toml";
+/// Longueur minimale du secret de signature des jetons
+const MIN_JWT_SECRET_LENGTH: usize = 32;
+const PAYMENT_PROVIDERS: [&str; 3] = ["mock", "mtn", "orange"];
+const NOTIFIERS: [&str; 2] = ["log", "file"];
+
+/// Configuration de l'application, validée au démarrage
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub database: DatabaseSettings,
+    pub jwt: JwtSettings,
+    pub scheduler: SchedulerSettings,
+    pub payments: PaymentSettings,
+    pub idempotency: IdempotencySettings,
+    pub rate_limit: RateLimitSettings,
+    pub notifier: NotifierSettings,
+    pub allowed_origins: Vec<String>,
+    pub log_level: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerSettings {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseSettings {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct JwtSettings {
+    pub secret: String,
+    pub expiration: i64,
+    pub refresh_expiration: i64,
+}
+
+/// Tâches de fond : ouverture des tours et relance des cotisations impayées
+#[derive(Debug, Clone)]
+pub struct SchedulerSettings {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    /// Délai accordé après l'ouverture d'un tour avant qu'une cotisation impayée soit en retard
+    pub grace_period_hours: i64,
+    pub max_attempts: i32,
+}
+
+/// Clés `Idempotency-Key` des requêtes qui déplacent de l'argent
+#[derive(Debug, Clone)]
+pub struct IdempotencySettings {
+    /// Durée de conservation d'une clé et de la réponse rejouée
+    pub retention_hours: i64,
+}
+
+/// Limitation des routes publiques d'authentification (requêtes par fenêtre glissante)
+#[derive(Debug, Clone)]
+pub struct RateLimitSettings {
+    pub per_ip: usize,
+    pub per_account: usize,
+    pub window_seconds: u64,
+}
+
+/// Envoi des emails et SMS aux utilisateurs
+#[derive(Debug, Clone)]
+pub struct NotifierSettings {
+    /// `log` (sortie standard) ou `file`
+    pub kind: String,
+    /// Fichier auquel les messages sont ajoutés avec `file`
+    pub file: String,
+}
+
+/// Encaissements mobile money : opérateur utilisé par défaut et accès aux API des opérateurs
+#[derive(Debug, Clone)]
+pub struct PaymentSettings {
+    /// `mock`, `mtn` ou `orange`
+    pub default_provider: String,
+    /// URL publique du backend, transmise aux opérateurs pour leurs notifications
+    pub callback_base_url: String,
+    /// Clé HMAC-SHA256 des notifications des opérateurs ; sans clé, elles sont refusées
+    pub webhook_secret: String,
+    pub currency: String,
+    pub mtn: MtnSettings,
+    pub orange: OrangeSettings,
+    /// Issue simulée par l'opérateur local : `success`, `failure` ou `pending`
+    pub mock_outcome: String,
+}
+
+/// API Collection de MTN Mobile Money (MoMo Open API)
+#[derive(Debug, Clone)]
+pub struct MtnSettings {
+    pub base_url: String,
+    pub subscription_key: String,
+    pub api_user: String,
+    pub api_key: String,
+    /// `sandbox` ou l'environnement de production attribué par MTN (ex. `mtncameroon`)
+    pub target_environment: String,
+    /// Produit Disbursement, souscrit séparément : clé et utilisateur API propres
+    pub disbursement_subscription_key: String,
+    pub disbursement_api_user: String,
+    pub disbursement_api_key: String,
+}
+
+/// API Merchant Payment d'Orange Money
+#[derive(Debug, Clone)]
+pub struct OrangeSettings {
+    pub base_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub auth_token: String,
+    /// Numéro du compte marchand qui encaisse les cotisations et verse les tours
+    pub channel_msisdn: String,
+    pub pin: String,
+}
+
+impl MtnSettings {
+    pub fn is_configured(&self) -> bool {
+        !self.subscription_key.is_empty() && !self.api_user.is_empty() && !self.api_key.is_empty()
+    }
+
+    pub fn disbursement_configured(&self) -> bool {
+        !self.disbursement_subscription_key.is_empty() && !self.disbursement_api_user.is_empty()
+            && !self.disbursement_api_key.is_empty()
+    }
+}
+
+impl OrangeSettings {
+    pub fn is_configured(&self) -> bool {
+        !self.client_id.is_empty() && !self.client_secret.is_empty() && !self.auth_token.is_empty()
+            && !self.channel_msisdn.is_empty() && !self.pin.is_empty()
+    }
+}
+
+/// Liste de tous les paramètres invalides, pour tout corriger en une fois
+#[derive(Debug)]
+pub struct SettingsError(pub Vec<String>);
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Configuration invalide ({} erreur(s)) :", self.0.len())?;
+        for error in &self.0 {
+            writeln!(f, "  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Contenu du fichier TOML : toutes les clés sont optionnelles
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileSettings {
+    server: FileServerSettings,
+    database: FileDatabaseSettings,
+    jwt: FileJwtSettings,
+    scheduler: FileSchedulerSettings,
+    payments: FilePaymentSettings,
+    idempotency: FileIdempotencySettings,
+    rate_limit: FileRateLimitSettings,
+    notifier: FileNotifierSettings,
+    cors: FileCorsSettings,
+    log_level: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileServerSettings {
+    host: Option<String>,
+    port: Option<u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileDatabaseSettings {
+    url: Option<String>,
+    max_connections: Option<u32>,
+    min_connections: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileJwtSettings {
+    secret: Option<String>,
+    expiration: Option<i64>,
+    refresh_expiration: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileSchedulerSettings {
+    enabled: Option<bool>,
+    interval_seconds: Option<u64>,
+    grace_period_hours: Option<i64>,
+    max_attempts: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilePaymentSettings {
+    default_provider: Option<String>,
+    callback_base_url: Option<String>,
+    webhook_secret: Option<String>,
+    currency: Option<String>,
+    mock_outcome: Option<String>,
+    mtn: FileMtnSettings,
+    orange: FileOrangeSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileIdempotencySettings {
+    retention_hours: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileRateLimitSettings {
+    per_ip: Option<usize>,
+    per_account: Option<usize>,
+    window_seconds: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileNotifierSettings {
+    kind: Option<String>,
+    file: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileMtnSettings {
+    base_url: Option<String>,
+    subscription_key: Option<String>,
+    api_user: Option<String>,
+    api_key: Option<String>,
+    target_environment: Option<String>,
+    disbursement_subscription_key: Option<String>,
+    disbursement_api_user: Option<String>,
+    disbursement_api_key: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileOrangeSettings {
+    base_url: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    auth_token: Option<String>,
+    channel_msisdn: Option<String>,
+    pin: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileCorsSettings {
+    allowed_origins: Option<Vec<String>>,
+}
+
+impl Settings {
+    /// Charge les valeurs par défaut, puis le fichier TOML, puis les variables d'environnement
+    /// (qui ont priorité), et valide le tout
+    pub fn load() -> Result<Self, SettingsError> {
+        dotenvy::dotenv().ok();
+
+        let mut errors = Vec::new();
+        let file = read_file(&mut errors);
+
+        Self::build(file, &|name| env::var(name).ok(), errors)
+    }
+
+    /// Assemble et valide la configuration à partir du fichier et de `env`
+    /// (lecture des variables d'environnement, remplaçable dans les tests)
+    fn build(file: FileSettings, env: Env, mut errors: Vec<String>) -> Result<Self, SettingsError> {
+        let settings = Settings {
+            server: ServerSettings {
+                host: env_or(env, "HOST", file.server.host, "127.0.0.1".to_string(), &mut errors),
+                port: env_or(env, "PORT", file.server.port, 8080, &mut errors),
+            },
+            database: DatabaseSettings {
+                url: env_or(env, "DATABASE_URL", file.database.url, String::new(), &mut errors),
+                max_connections: env_or(env, "DATABASE_MAX_CONNECTIONS", file.database.max_connections, 10, &mut errors),
+                min_connections: env_or(env, "DATABASE_MIN_CONNECTIONS", file.database.min_connections, 1, &mut errors),
+            },
+            jwt: JwtSettings {
+                secret: env_or(env, "JWT_SECRET", file.jwt.secret, String::new(), &mut errors),
+                expiration: env_or(env, "JWT_EXPIRATION", file.jwt.expiration, 900, &mut errors),
+                refresh_expiration: env_or(env, "REFRESH_TOKEN_EXPIRATION", file.jwt.refresh_expiration, 2_592_000, &mut errors),
+            },
+            scheduler: SchedulerSettings {
+                enabled: env_or(env, "SCHEDULER_ENABLED", file.scheduler.enabled, true, &mut errors),
+                interval_seconds: env_or(env, "SCHEDULER_INTERVAL_SECONDS", file.scheduler.interval_seconds, 60, &mut errors),
+                grace_period_hours: env_or(env, "CONTRIBUTION_GRACE_PERIOD_HOURS", file.scheduler.grace_period_hours, 48, &mut errors),
+                max_attempts: env_or(env, "SCHEDULER_MAX_ATTEMPTS", file.scheduler.max_attempts, 5, &mut errors),
+            },
+            payments: PaymentSettings {
+                default_provider: env_or(env, "PAYMENT_DEFAULT_PROVIDER", file.payments.default_provider, String::new(), &mut errors),
+                callback_base_url: env_or(env, "PAYMENT_CALLBACK_BASE_URL", file.payments.callback_base_url, String::new(), &mut errors),
+                webhook_secret: env_or(env, "PAYMENT_WEBHOOK_SECRET", file.payments.webhook_secret, String::new(), &mut errors),
+                currency: env_or(env, "PAYMENT_CURRENCY", file.payments.currency, "XAF".to_string(), &mut errors),
+                mock_outcome: env_or(env, "MOCK_PAYMENT_OUTCOME", file.payments.mock_outcome, "success".to_string(), &mut errors),
+                mtn: MtnSettings {
+                    base_url: env_or(env, "MTN_MOMO_BASE_URL", file.payments.mtn.base_url, "https://sandbox.momodeveloper.mtn.com".to_string(), &mut errors),
+                    subscription_key: env_or(env, "MTN_MOMO_SUBSCRIPTION_KEY", file.payments.mtn.subscription_key, String::new(), &mut errors),
+                    api_user: env_or(env, "MTN_MOMO_API_USER", file.payments.mtn.api_user, String::new(), &mut errors),
+                    api_key: env_or(env, "MTN_MOMO_API_KEY", file.payments.mtn.api_key, String::new(), &mut errors),
+                    target_environment: env_or(env, "MTN_MOMO_TARGET_ENVIRONMENT", file.payments.mtn.target_environment, "sandbox".to_string(), &mut errors),
+                    disbursement_subscription_key: env_or(env, "MTN_MOMO_DISBURSEMENT_SUBSCRIPTION_KEY", file.payments.mtn.disbursement_subscription_key, String::new(), &mut errors),
+                    disbursement_api_user: env_or(env, "MTN_MOMO_DISBURSEMENT_API_USER", file.payments.mtn.disbursement_api_user, String::new(), &mut errors),
+                    disbursement_api_key: env_or(env, "MTN_MOMO_DISBURSEMENT_API_KEY", file.payments.mtn.disbursement_api_key, String::new(), &mut errors),
+                },
+                orange: OrangeSettings {
+                    base_url: env_or(env, "ORANGE_MONEY_BASE_URL", file.payments.orange.base_url, "https://api-s1.orange.cm".to_string(), &mut errors),
+                    client_id: env_or(env, "ORANGE_MONEY_CLIENT_ID", file.payments.orange.client_id, String::new(), &mut errors),
+                    client_secret: env_or(env, "ORANGE_MONEY_CLIENT_SECRET", file.payments.orange.client_secret, String::new(), &mut errors),
+                    auth_token: env_or(env, "ORANGE_MONEY_AUTH_TOKEN", file.payments.orange.auth_token, String::new(), &mut errors),
+                    channel_msisdn: env_or(env, "ORANGE_MONEY_CHANNEL_MSISDN", file.payments.orange.channel_msisdn, String::new(), &mut errors),
+                    pin: env_or(env, "ORANGE_MONEY_PIN", file.payments.orange.pin, String::new(), &mut errors),
+                },
+            },
+            idempotency: IdempotencySettings {
+                retention_hours: env_or(env, "IDEMPOTENCY_RETENTION_HOURS", file.idempotency.retention_hours, 24, &mut errors),
+            },
+            rate_limit: RateLimitSettings {
+                per_ip: env_or(env, "AUTH_RATE_LIMIT_PER_IP", file.rate_limit.per_ip, 30, &mut errors),
+                per_account: env_or(env, "AUTH_RATE_LIMIT_PER_ACCOUNT", file.rate_limit.per_account, 10, &mut errors),
+                window_seconds: env_or(env, "AUTH_RATE_LIMIT_WINDOW_SECONDS", file.rate_limit.window_seconds, 60, &mut errors),
+            },
+            notifier: NotifierSettings {
+                kind: env_or(env, "NOTIFIER", file.notifier.kind, "log".to_string(), &mut errors),
+                file: env_or(env, "NOTIFIER_FILE", file.notifier.file, "notifications.log".to_string(), &mut errors),
+            },
+            allowed_origins: match env("CORS_ALLOWED_ORIGINS") {
+                Some(value) => value.split(',')
+                    .map(|origin| origin.trim().to_string())
+                    .filter(|origin| !origin.is_empty())
+                    .collect(),
+                None => file.cors.allowed_origins.unwrap_or_else(|| vec![
+                    "http://localhost:8080".to_string(),
+                    "http://127.0.0.1:8080".to_string(),
+                    "http://localhost:3000".to_string(),
+                    "http://127.0.0.1:3000".to_string(),
+                ]),
+            },
+            log_level: env_or(env, "RUST_LOG", file.log_level, "info".to_string(), &mut errors),
+        };
+
+        settings.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(settings)
+        } else {
+            Err(SettingsError(errors))
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.server.host.trim().is_empty() {
+            errors.push("HOST ne doit pas être vide".to_string());
+        }
+        if self.server.port == 0 {
+            errors.push("PORT doit être compris entre 1 et 65535".to_string());
+        }
+
+        if self.database.url.is_empty() {
+            errors.push("DATABASE_URL est obligatoire".to_string());
+        } else if !self.database.url.starts_with("postgres://") && !self.database.url.starts_with("postgresql://") {
+            errors.push("DATABASE_URL doit commencer par postgres:// ou postgresql://".to_string());
+        }
+        if self.database.max_connections == 0 {
+            errors.push("DATABASE_MAX_CONNECTIONS doit être supérieur à 0".to_string());
+        }
+        if self.database.min_connections > self.database.max_connections {
+            errors.push(format!(
+                "DATABASE_MIN_CONNECTIONS ({}) ne doit pas dépasser DATABASE_MAX_CONNECTIONS ({})",
+                self.database.min_connections, self.database.max_connections
+            ));
+        }
+
+        if self.jwt.secret.is_empty() {
+            errors.push("JWT_SECRET est obligatoire".to_string());
+        } else if self.jwt.secret.len() < MIN_JWT_SECRET_LENGTH {
+            errors.push(format!(
+                "JWT_SECRET doit contenir au moins {} caractères (actuellement {})",
+                MIN_JWT_SECRET_LENGTH,
+                self.jwt.secret.len()
+            ));
+        }
+        if self.jwt.expiration <= 0 {
+            errors.push("JWT_EXPIRATION doit être un nombre de secondes positif".to_string());
+        }
+        if self.jwt.refresh_expiration <= self.jwt.expiration {
+            errors.push("REFRESH_TOKEN_EXPIRATION doit être supérieur à JWT_EXPIRATION".to_string());
+        }
+
+        if self.scheduler.interval_seconds == 0 {
+            errors.push("SCHEDULER_INTERVAL_SECONDS doit être supérieur à 0".to_string());
+        }
+        if self.scheduler.grace_period_hours < 0 {
+            errors.push("CONTRIBUTION_GRACE_PERIOD_HOURS ne doit pas être négatif".to_string());
+        }
+        if self.scheduler.max_attempts <= 0 {
+            errors.push("SCHEDULER_MAX_ATTEMPTS doit être supérieur à 0".to_string());
+        }
+
+        if self.idempotency.retention_hours <= 0 {
+            errors.push("IDEMPOTENCY_RETENTION_HOURS doit être supérieur à 0".to_string());
+        }
+
+        // Pas de valeur par défaut : l'opérateur mock valide tout paiement et doit être choisi explicitement
+        if self.payments.default_provider.is_empty() {
+            errors.push(format!(
+                "PAYMENT_DEFAULT_PROVIDER est obligatoire ({})",
+                PAYMENT_PROVIDERS.join(", ")
+            ));
+        } else if !PAYMENT_PROVIDERS.contains(&self.payments.default_provider.as_str()) {
+            errors.push(format!(
+                "PAYMENT_DEFAULT_PROVIDER invalide : {} (attendu : {})",
+                self.payments.default_provider,
+                PAYMENT_PROVIDERS.join(", ")
+            ));
+        }
+        if self.payments.default_provider == "mtn" && !self.payments.mtn.is_configured() {
+            errors.push("MTN_MOMO_SUBSCRIPTION_KEY, MTN_MOMO_API_USER et MTN_MOMO_API_KEY sont obligatoires avec l'opérateur mtn".to_string());
+        }
+        if self.payments.default_provider == "orange" && !self.payments.orange.is_configured() {
+            errors.push("Les identifiants ORANGE_MONEY_* sont obligatoires avec l'opérateur orange".to_string());
+        }
+        if !self.payments.callback_base_url.is_empty()
+            && !self.payments.callback_base_url.starts_with("http://")
+            && !self.payments.callback_base_url.starts_with("https://") {
+            errors.push("PAYMENT_CALLBACK_BASE_URL doit commencer par http:// ou https://".to_string());
+        }
+        if self.payments.webhook_secret.is_empty() && !matches!(self.payments.default_provider.as_str(), "mock" | "") {
+            errors.push(format!(
+                "PAYMENT_WEBHOOK_SECRET est obligatoire avec l'opérateur {}",
+                self.payments.default_provider
+            ));
+        } else if !self.payments.webhook_secret.is_empty() && self.payments.webhook_secret.len() < MIN_JWT_SECRET_LENGTH {
+            errors.push(format!(
+                "PAYMENT_WEBHOOK_SECRET doit contenir au moins {} caractères",
+                MIN_JWT_SECRET_LENGTH
+            ));
+        }
+        if self.payments.currency.trim().is_empty() {
+            errors.push("PAYMENT_CURRENCY ne doit pas être vide".to_string());
+        }
+        if !["success", "failure", "pending"].contains(&self.payments.mock_outcome.as_str()) {
+            errors.push(format!(
+                "MOCK_PAYMENT_OUTCOME invalide : {} (attendu : success, failure, pending)",
+                self.payments.mock_outcome
+            ));
+        }
+
+        if self.allowed_origins.is_empty() {
+            errors.push("CORS_ALLOWED_ORIGINS doit contenir au moins une origine".to_string());
+        }
+        for origin in &self.allowed_origins {
+            if !origin.starts_with("http://") && !origin.starts_with("https://") {
+                errors.push(format!("Origine CORS invalide : {} (http:// ou https:// attendu)", origin));
+            }
+        }
+
+        if self.rate_limit.per_ip == 0 || self.rate_limit.per_account == 0 {
+            errors.push("AUTH_RATE_LIMIT_PER_IP et AUTH_RATE_LIMIT_PER_ACCOUNT doivent être supérieurs à 0".to_string());
+        }
+        if self.rate_limit.window_seconds == 0 {
+            errors.push("AUTH_RATE_LIMIT_WINDOW_SECONDS doit être supérieur à 0".to_string());
+        }
+
+        if !NOTIFIERS.contains(&self.notifier.kind.as_str()) {
+            errors.push(format!(
+                "NOTIFIER invalide : {} (attendu : {})",
+                self.notifier.kind,
+                NOTIFIERS.join(", ")
+            ));
+        } else if self.notifier.kind == "file" && self.notifier.file.trim().is_empty() {
+            errors.push("NOTIFIER_FILE est obligatoire avec NOTIFIER=file".to_string());
+        }
+
+        // Même syntaxe que le logger : `info`, `actix_web=debug`, `info,sqlx=warn`, ...
+        if let Err(e) = env_filter::Builder::new().try_parse(&self.log_level) {
+            errors.push(format!("Niveau de log invalide : {} ({})", self.log_level, e));
+        }
+    }
+}
+
+fn read_file(errors: &mut Vec<String>) -> FileSettings {
+    let (path, required) = match env::var("SETTINGS_FILE") {
+        Ok(path) => (path, true),
+        Err(_) => (DEFAULT_SETTINGS_FILE.to_string(), false),
+    };
+
+    if !Path::new(&path).exists() {
+        if required {
+            errors.push(format!("Fichier de configuration introuvable : {}", path));
+        }
+        return FileSettings::default();
+    }
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            errors.push(format!("Lecture de {} impossible : {}", path, e));
+            return FileSettings::default();
+        }
+    };
+
+    toml::from_str(&content).unwrap_or_else(|e| {
+        errors.push(format!("Fichier {} invalide : {}", path, e.to_string().trim()));
+        FileSettings::default()
+    })
+}
+
+/// Lecture d'une variable d'environnement
+type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// Valeur de la variable d'environnement si elle est définie, sinon celle du fichier, sinon la valeur par défaut
+fn env_or<T: FromStr>(env: Env, name: &str, file_value: Option<T>, default: T, errors: &mut Vec<String>) -> T {
+    match env(name) {
+        Some(value) => value.trim().parse().unwrap_or_else(|_| {
+            errors.push(format!("{} invalide : {}", name, value));
+            file_value.unwrap_or(default)
+        }),
+        None => file_value.unwrap_or(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const JWT_SECRET: &str = "un_secret_de_test_suffisamment_long_pour_valider";
+
+    /// Variables minimales d'une configuration valide
+    fn base_env() -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("DATABASE_URL", "postgres://tontine@localhost/tontine".to_string()),
+            ("JWT_SECRET", JWT_SECRET.to_string()),
+            ("PAYMENT_DEFAULT_PROVIDER", "mock".to_string()),
+        ])
+    }
+
+    fn build(file: &str, vars: &HashMap<&'static str, String>) -> Result<Settings, SettingsError> {
+        let file: FileSettings = toml::from_str(file).expect("TOML de test valide");
+        Settings::build(file, &|name| vars.get(name).cloned(), Vec::new())
+    }
+
+    fn errors(file: &str, vars: &HashMap<&'static str, String>) -> Vec<String> {
+        match build(file, vars) {
+            Ok(_) => Vec::new(),
+            Err(SettingsError(errors)) => errors,
+        }
+    }
+
+    #[test]
+    fn defaults_apply_without_file_or_env() {
+        let settings = build("", &base_env()).unwrap();
+
+        assert_eq!(settings.server.host, "127.0.0.1");
+        assert_eq!(settings.server.port, 8080);
+        assert_eq!(settings.rate_limit.per_ip, 30);
+        assert_eq!(settings.notifier.kind, "log");
+        assert_eq!(settings.log_level, "info");
+    }
+
+    #[test]
+    fn file_overrides_defaults_and_env_overrides_file() {
+        let file = "[server]\nhost = \"0.0.0.0\"\nport = 9000\n\n[rate_limit]\nper_ip = 50\n";
+        let mut vars = base_env();
+
+        let settings = build(file, &vars).unwrap();
+        assert_eq!(settings.server.host, "0.0.0.0");
+        assert_eq!(settings.server.port, 9000);
+        assert_eq!(settings.rate_limit.per_ip, 50);
+
+        vars.insert("PORT", "9100".to_string());
+        vars.insert("AUTH_RATE_LIMIT_PER_IP", "5".to_string());
+        let settings = build(file, &vars).unwrap();
+        assert_eq!(settings.server.host, "0.0.0.0");
+        assert_eq!(settings.server.port, 9100);
+        assert_eq!(settings.rate_limit.per_ip, 5);
+    }
+
+    #[test]
+    fn unparsable_env_value_is_reported() {
+        let mut vars = base_env();
+        vars.insert("PORT", "huit-mille".to_string());
+
+        assert_eq!(errors("", &vars), vec!["PORT invalide : huit-mille".to_string()]);
+    }
+
+    #[test]
+    fn unknown_file_key_is_rejected() {
+        assert!(toml::from_str::<FileSettings>("[server]\nhots = \"0.0.0.0\"\n").is_err());
+    }
+
+    #[test]
+    fn required_values_are_reported_together() {
+        let errors = errors("", &HashMap::new());
+
+        assert!(errors.contains(&"DATABASE_URL est obligatoire".to_string()));
+        assert!(errors.contains(&"JWT_SECRET est obligatoire".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("PAYMENT_DEFAULT_PROVIDER est obligatoire")));
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn invalid_values_are_reported() {
+        let mut vars = base_env();
+        vars.insert("JWT_SECRET", "court".to_string());
+        vars.insert("DATABASE_MIN_CONNECTIONS", "20".to_string());
+        vars.insert("NOTIFIER", "pigeon".to_string());
+        vars.insert("AUTH_RATE_LIMIT_WINDOW_SECONDS", "0".to_string());
+        vars.insert("CORS_ALLOWED_ORIGINS", "example.com".to_string());
+
+        let errors = errors("", &vars);
+        assert!(errors.iter().any(|e| e.starts_with("JWT_SECRET doit contenir au moins")));
+        assert!(errors.iter().any(|e| e.starts_with("DATABASE_MIN_CONNECTIONS (20)")));
+        assert!(errors.iter().any(|e| e.starts_with("NOTIFIER invalide : pigeon")));
+        assert!(errors.contains(&"AUTH_RATE_LIMIT_WINDOW_SECONDS doit être supérieur à 0".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("Origine CORS invalide : example.com")));
+        assert_eq!(errors.len(), 5);
+    }
+
+    #[test]
+    fn log_level_accepts_logger_directives() {
+        for level in ["debug", "actix_web=info", "info,sqlx=warn", "backend::payments=trace,warn", "actix_web"] {
+            let mut vars = base_env();
+            vars.insert("RUST_LOG", level.to_string());
+            assert!(errors("", &vars).is_empty(), "{} refusé", level);
+        }
+    }
+
+    #[test]
+    fn log_level_rejects_unknown_levels() {
+        let mut vars = base_env();
+        vars.insert("RUST_LOG", "actix_web=bavard".to_string());
+
+        let errors = errors("", &vars);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Niveau de log invalide : actix_web=bavard"));
+    }
+
+    #[test]
+    fn real_provider_requires_credentials_and_webhook_secret() {
+        let mut vars = base_env();
+        vars.insert("PAYMENT_DEFAULT_PROVIDER", "mtn".to_string());
+
+        let errors = errors("", &vars);
+        assert!(errors.iter().any(|e| e.starts_with("MTN_MOMO_SUBSCRIPTION_KEY")));
+        assert!(errors.contains(&"PAYMENT_WEBHOOK_SECRET est obligatoire avec l'opérateur mtn".to_string()));
+    }
+}
@@ -0,0 +1,44 @@
+use rust_decimal::Decimal;
+
+use crate::errors::{AppError, FieldError};
+
+/// Accumule les erreurs de validation d'une requête pour les renvoyer toutes ensemble,
+/// chacune rattachée à son champ
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Enregistre `message` pour `field` si la condition n'est pas remplie
+    pub fn check(&mut self, valid: bool, field: &str, message: impl Into<String>) -> &mut Self {
+        if !valid {
+            self.errors.push(FieldError {
+                field: field.to_string(),
+                message: message.into(),
+            });
+        }
+        self
+    }
+
+    /// Montant strictement positif
+    pub fn positive(&mut self, field: &str, value: Decimal) -> &mut Self {
+        self.check(value > Decimal::ZERO, field, "Le montant doit être strictement positif")
+    }
+
+    /// Montant strictement positif s'il est fourni
+    pub fn positive_opt(&mut self, field: &str, value: Option<Decimal>) -> &mut Self {
+        match value {
+            Some(value) => self.positive(field, value),
+            None => self,
+        }
+    }
+
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::InvalidFields(self.errors))
+        }
+    }
+}
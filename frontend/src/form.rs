@@ -30,7 +30,7 @@ pub fn form() -> Html {
             let message_async = message.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                let url = "http://localhost:8080/api/users";
+                let url = "http://localhost:8080/api/auth/register";
                 let body = serde_json::json!({
                     "email": email_val,
                     "password": password_val,
@@ -52,7 +52,7 @@ pub fn form() -> Html {
                 match req.send().await {
                     Ok(response) => {
                         if response.status() == 200 || response.status() == 201 {
-                            message_async.set("✅ Utilisateur créé avec succès !".to_string());
+                            message_async.set("✅ Compte créé ! Saisissez les codes reçus par email et SMS pour l'activer.".to_string());
                         } else {
                             let error_text = response.text().await.unwrap_or_else(|_| "Erreur inconnue".to_string());
                             message_async.set(format!("❌ Erreur {} : {}", response.status(), error_text));